@@ -0,0 +1,1221 @@
+//! `simcity`: operate and inspect `simcity_build` cities from the terminal.
+//!
+//! Writes to the city are routed like the web client does it: to the ER
+//! while the city is delegated, to the base layer otherwise. `--layer`
+//! overrides the routing for reads.
+
+use std::error::Error;
+
+use anchor_lang::AnchorDeserialize;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use clap::{Parser, Subcommand, ValueEnum};
+use counter::achievements::{Achievement, Achievements};
+use counter::budget::{Budget, BudgetReport, ServiceFunding};
+use counter::buildings::{self, BuildingType};
+use counter::chunks::Chunk;
+use counter::config::GameConfig;
+use counter::difficulty::Difficulty;
+use counter::elevation::{self, TerraformAction};
+use counter::large_map::LargeMap;
+use counter::mayor::{self, MayorProfile};
+use counter::ordinances::Ordinance;
+use counter::quests::QuestStatus;
+use counter::randomness::RandomnessMode;
+use counter::research::Tech;
+use counter::scenario::{ScenarioParams, ScenarioStatus};
+use counter::specialization::Specialization;
+use counter::terrain::{self, Terrain};
+use counter::unlocks::BuildingUnlock;
+use counter::{BulldozeArgs, City, CityScore, PlaceBuildingArgs, GRID_SIZE};
+use simcity_client::instructions::{self, UpdateOptions};
+use simcity_client::routing::{self, Endpoints, Route, DEFAULT_BASE_URL, DEFAULT_ER_URL};
+use simcity_client::{pda, state};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+
+type CliResult<T = ()> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(name = "simcity", about = "Operate and inspect simcity_build cities")]
+struct Cli {
+    /// Base layer RPC URL
+    #[arg(long, env = "SIMCITY_BASE_URL", default_value = DEFAULT_BASE_URL)]
+    url: String,
+    /// Ephemeral rollup RPC URL
+    #[arg(long, env = "SIMCITY_ER_URL", default_value = DEFAULT_ER_URL)]
+    er_url: String,
+    /// Signer keypair, defaults to the Solana CLI keypair
+    #[arg(long, short)]
+    keypair: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the global game config (program upgrade authority only)
+    InitConfig,
+    /// Accept randomness oracle answers signed by `oracle` (config admin
+    /// only)
+    SetOracle { oracle: Pubkey },
+    /// Choose the source every city's steps draw from (config admin only)
+    SetRandomness {
+        #[arg(value_enum)]
+        source: RandomnessArg,
+    },
+    /// Set the feature flags new cities start with, as a bitmask (config
+    /// admin only)
+    SetDefaultFeatures { features: u32 },
+    /// Create the signer's city
+    InitCity {
+        /// Build for free without disasters or a score
+        #[arg(long)]
+        sandbox: bool,
+        #[arg(long, value_enum, default_value_t = DifficultyArg::Normal)]
+        difficulty: DifficultyArg,
+        #[arg(long, value_enum, default_value_t = SpecializationArg::None)]
+        specialization: SpecializationArg,
+    },
+    /// Place a building on the signer's city
+    Place {
+        x: u8,
+        y: u8,
+        building_type: u8,
+        /// Fail instead of replacing an occupied tile
+        #[arg(long)]
+        no_overwrite: bool,
+        /// Credit the mayor profile with experience
+        #[arg(long)]
+        profile: bool,
+    },
+    /// Clear a tile of the signer's city
+    Bulldoze { x: u8, y: u8 },
+    /// Zone a rectangle of the signer's city (2 residential, 3 commercial,
+    /// 4 industrial, 0 to unzone)
+    Zone {
+        x: u8,
+        y: u8,
+        width: u8,
+        height: u8,
+        zone: u8,
+    },
+    /// Create a 64x64 large map for the signer's city
+    InitLargeMap,
+    /// Place a building on the signer's large map
+    PlaceLarge { x: u16, y: u16, building_type: u8 },
+    /// Clear a tile of the signer's large map
+    BulldozeLarge { x: u16, y: u16 },
+    /// Buy chunk (CX, CY) for the signer's city
+    Expand {
+        #[arg(allow_hyphen_values = true)]
+        cx: i8,
+        #[arg(allow_hyphen_values = true)]
+        cy: i8,
+        /// Owned chunk the new one borders, when it doesn't border the
+        /// city's own grid
+        #[arg(long, num_args = 2, value_names = ["CX", "CY"], allow_hyphen_values = true)]
+        via: Option<Vec<i8>>,
+    },
+    /// Place a building at (x, y) within chunk (CX, CY)
+    PlaceChunk {
+        #[arg(allow_hyphen_values = true)]
+        cx: i8,
+        #[arg(allow_hyphen_values = true)]
+        cy: i8,
+        x: u8,
+        y: u8,
+        building_type: u8,
+    },
+    /// Clear (x, y) within chunk (CX, CY)
+    BulldozeChunk {
+        #[arg(allow_hyphen_values = true)]
+        cx: i8,
+        #[arg(allow_hyphen_values = true)]
+        cy: i8,
+        x: u8,
+        y: u8,
+    },
+    /// Delegate chunk (CX, CY) of the signer's city to the ER
+    DelegateChunk {
+        #[arg(allow_hyphen_values = true)]
+        cx: i8,
+        #[arg(allow_hyphen_values = true)]
+        cy: i8,
+        /// Pin the delegation to a specific ER validator
+        #[arg(long)]
+        validator: Option<Pubkey>,
+    },
+    /// Commit and hand chunk (CX, CY) back to the base layer
+    UndelegateChunk {
+        #[arg(allow_hyphen_values = true)]
+        cx: i8,
+        #[arg(allow_hyphen_values = true)]
+        cy: i8,
+    },
+    /// Print chunk (CX, CY) of a city
+    ShowChunk {
+        #[arg(allow_hyphen_values = true)]
+        cx: i8,
+        #[arg(allow_hyphen_values = true)]
+        cy: i8,
+        /// City authority, defaults to the signer
+        #[arg(long)]
+        authority: Option<Pubkey>,
+        #[arg(long, value_enum, default_value_t = Layer::Auto)]
+        layer: Layer,
+    },
+    /// Lock a tile of the signer's city against demolition
+    Protect { x: u8, y: u8 },
+    /// Remove a tile's demolition lock
+    Unprotect { x: u8, y: u8 },
+    /// Borrow into the signer's treasury
+    Borrow { amount: u64 },
+    /// Pay back part or all of the signer's loan
+    Repay { amount: u64 },
+    /// Roll a bankrupt city's overdraft into its loan, with a surcharge
+    Restructure,
+    /// Insure the signer's city against disasters
+    Insure,
+    /// Cancel the signer's disaster insurance
+    CancelInsurance,
+    /// Set utilities and parks funding levels of the signer's city, in
+    /// percent; police and fire are set with `funding`
+    Budget { utilities: u8, parks: u8 },
+    /// Set the residential tax rate of the signer's city, in basis points
+    Tax { bps: u16 },
+    /// Set service funding levels of the signer's city, in percent
+    Funding {
+        police: u8,
+        fire: u8,
+        health: u8,
+        education: u8,
+    },
+    /// Print the population each building unlocks at
+    Unlocks,
+    /// Print what the next step would collect and spend
+    Report {
+        /// City authority, defaults to the signer
+        #[arg(long)]
+        authority: Option<Pubkey>,
+    },
+    /// Advance the signer's city by the simulation steps due
+    Step {
+        /// Unlock achievements earned along the way
+        #[arg(long)]
+        achievements: bool,
+        /// Credit the mayor profile with experience
+        #[arg(long)]
+        profile: bool,
+    },
+    /// Pay to resolve a fire or crime on a tile of the signer's city
+    Dispatch { x: u8, y: u8 },
+    /// Restore a worn building on the signer's city to full condition
+    Repair { x: u8, y: u8 },
+    /// Raise a zone building to the next density
+    UpgradeDensity { x: u8, y: u8 },
+    /// Upgrade a road tile to the next tier
+    UpgradeRoad { x: u8, y: u8 },
+    /// Fill in a water tile of the signer's city bordering land
+    Reclaim { x: u8, y: u8 },
+    /// Raise, lower or flatten an empty tile of the signer's city
+    Terraform {
+        x: u8,
+        y: u8,
+        #[arg(value_enum)]
+        action: TerraformArg,
+    },
+    /// Collect the reward of a milestone the signer's city reached
+    Claim {
+        milestone: u8,
+        /// Credit the mayor profile and add its level bonus
+        #[arg(long)]
+        profile: bool,
+    },
+    /// Print today's quest and the city's progress on it
+    Quest {
+        /// City authority, defaults to the signer
+        #[arg(long)]
+        authority: Option<Pubkey>,
+    },
+    /// Collect the reward of today's quest
+    ClaimQuest {
+        /// Add the mayor profile's level bonus
+        #[arg(long)]
+        profile: bool,
+    },
+    /// Start researching a tech on the signer's city
+    Research {
+        #[arg(value_enum)]
+        tech: TechArg,
+    },
+    /// Enact an ordinance on the signer's city, or repeal it
+    Ordinance {
+        #[arg(value_enum)]
+        ordinance: OrdinanceArg,
+        #[arg(long)]
+        repeal: bool,
+    },
+    /// Create the signer's achievements account
+    InitAchievements,
+    /// Create the signer's mayor profile
+    InitProfile,
+    /// Start the signer's city over for a permanent income bonus
+    Prestige,
+    /// Delegate the signer's city to the ER
+    Delegate {
+        /// Pin the delegation to a specific ER validator
+        #[arg(long)]
+        validator: Option<Pubkey>,
+    },
+    /// Publish a scenario (config admin only)
+    CreateScenario {
+        id: u32,
+        starting_money: u64,
+        target_population: u32,
+        max_pollution: u8,
+        step_limit: u64,
+    },
+    /// Play a scenario on the signer's new city
+    StartScenario { id: u32 },
+    /// Mark the signer's scenario won or lost if it is decided
+    CheckScenario { id: u32 },
+    /// Let a viewer spectate the signer's city
+    IssuePass { viewer: Pubkey },
+    /// Revoke a viewer's spectator pass
+    RevokePass { viewer: Pubkey },
+    /// Commit the signer's city from the ER to the base layer
+    Commit,
+    /// Commit and hand the signer's city back to the base layer
+    Undelegate,
+    /// Print a decoded account
+    Show {
+        #[arg(value_enum, default_value_t = ShowTarget::City)]
+        target: ShowTarget,
+        /// City authority, defaults to the signer
+        #[arg(long)]
+        authority: Option<Pubkey>,
+        #[arg(long, value_enum, default_value_t = Layer::Auto)]
+        layer: Layer,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ShowTarget {
+    City,
+    Score,
+    Config,
+    Achievements,
+    Profile,
+    LargeMap,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DifficultyArg {
+    Easy,
+    Normal,
+    Hard,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SpecializationArg {
+    None,
+    Industrial,
+    Tourist,
+    Tech,
+    Agricultural,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OrdinanceArg {
+    FreeTransit,
+    PollutionControls,
+    NeighborhoodWatch,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RandomnessArg {
+    City,
+    SlotHashes,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TerraformArg {
+    Raise,
+    Lower,
+    Flatten,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TechArg {
+    Photovoltaics,
+    Recycling,
+    Railways,
+    Aviation,
+    HighRise,
+    Automation,
+    Logistics,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Layer {
+    /// ER when the account is delegated, base layer otherwise
+    Auto,
+    Base,
+    Er,
+}
+
+struct Context {
+    endpoints: Endpoints,
+    payer: Keypair,
+}
+
+impl Context {
+    fn client(&self, route: Route) -> RpcClient {
+        RpcClient::new_with_commitment(
+            self.endpoints.url(route).to_string(),
+            CommitmentConfig::confirmed(),
+        )
+    }
+
+    /// Where the account currently lives, based on its base layer owner
+    fn route_of(&self, address: &Pubkey) -> CliResult<Route> {
+        let account = self.client(Route::BaseLayer).get_account(address)?;
+        Ok(routing::route(&account.owner))
+    }
+
+    fn send(&self, route: Route, ixs: &[Instruction]) -> CliResult<Signature> {
+        let client = self.client(route);
+        let blockhash = client.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            blockhash,
+        );
+        Ok(client.send_and_confirm_transaction(&tx)?)
+    }
+
+    /// Send a city instruction wherever the city currently lives
+    fn send_to_city(&self, ix: Instruction) -> CliResult<Signature> {
+        let route = self.route_of(&pda::city(&self.payer.pubkey()))?;
+        self.send(route, &[ix])
+    }
+
+    /// Simulate a view instruction wherever `city` lives and return its
+    /// return data
+    fn simulate(&self, city: &Pubkey, ix: Instruction) -> CliResult<Vec<u8>> {
+        let client = self.client(self.route_of(city)?);
+        let blockhash = client.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            blockhash,
+        );
+        let result = client.simulate_transaction(&tx)?.value;
+        if let Some(err) = result.err {
+            return Err(format!("simulation failed: {err}").into());
+        }
+        let return_data = result.return_data.ok_or("no return data")?;
+        Ok(BASE64_STANDARD.decode(return_data.data.0)?)
+    }
+
+    fn fetch(&self, address: &Pubkey, layer: Layer) -> CliResult<Vec<u8>> {
+        let route = match layer {
+            Layer::Auto => self.route_of(address)?,
+            Layer::Base => Route::BaseLayer,
+            Layer::Er => Route::EphemeralRollup,
+        };
+        Ok(self.client(route).get_account(address)?.data)
+    }
+}
+
+fn default_keypair_path() -> String {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    format!("{home}/.config/solana/id.json")
+}
+
+fn main() -> CliResult {
+    let cli = Cli::parse();
+    let keypair_path = cli.keypair.unwrap_or_else(default_keypair_path);
+    let ctx = Context {
+        endpoints: Endpoints {
+            base_url: cli.url,
+            er_url: cli.er_url,
+        },
+        payer: read_keypair_file(&keypair_path)
+            .map_err(|err| format!("reading keypair {keypair_path}: {err}"))?,
+    };
+    let authority = ctx.payer.pubkey();
+
+    let signature = match cli.command {
+        Command::InitConfig => ctx.send(
+            Route::BaseLayer,
+            &[instructions::initialize_config(&authority)],
+        )?,
+        Command::SetOracle { oracle } => ctx.send(
+            Route::BaseLayer,
+            &[instructions::set_randomness_oracle(&authority, &oracle)],
+        )?,
+        Command::SetRandomness { source } => ctx.send(
+            Route::BaseLayer,
+            &[instructions::set_randomness_source(
+                &authority,
+                match source {
+                    RandomnessArg::City => RandomnessMode::City,
+                    RandomnessArg::SlotHashes => RandomnessMode::SlotHashes,
+                },
+            )],
+        )?,
+        Command::SetDefaultFeatures { features } => ctx.send(
+            Route::BaseLayer,
+            &[instructions::set_default_features(&authority, features)],
+        )?,
+        Command::InitCity {
+            sandbox,
+            difficulty,
+            specialization,
+        } => ctx.send(
+            Route::BaseLayer,
+            &[instructions::initialize_city(
+                &authority,
+                sandbox,
+                match difficulty {
+                    DifficultyArg::Easy => Difficulty::Easy,
+                    DifficultyArg::Normal => Difficulty::Normal,
+                    DifficultyArg::Hard => Difficulty::Hard,
+                },
+                match specialization {
+                    SpecializationArg::None => Specialization::None,
+                    SpecializationArg::Industrial => Specialization::Industrial,
+                    SpecializationArg::Tourist => Specialization::Tourist,
+                    SpecializationArg::Tech => Specialization::Tech,
+                    SpecializationArg::Agricultural => Specialization::Agricultural,
+                },
+            )],
+        )?,
+        Command::Place {
+            x,
+            y,
+            building_type,
+            no_overwrite,
+            profile,
+        } => ctx.send_to_city(instructions::place_building(
+            &authority,
+            &authority,
+            UpdateOptions {
+                profile,
+                ..UpdateOptions::default()
+            },
+            PlaceBuildingArgs::V2 {
+                x,
+                y,
+                building_type: BuildingType::try_from(building_type)?,
+                overwrite: !no_overwrite,
+            },
+        ))?,
+        Command::Bulldoze { x, y } => ctx.send_to_city(instructions::bulldoze(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            BulldozeArgs::V1 { x, y },
+        ))?,
+        Command::InitLargeMap => ctx.send(
+            Route::BaseLayer,
+            &[instructions::initialize_large_map(&authority)],
+        )?,
+        Command::PlaceLarge {
+            x,
+            y,
+            building_type,
+        } => ctx.send_to_city(instructions::place_on_large_map(
+            &authority,
+            &authority,
+            None,
+            x,
+            y,
+            BuildingType::try_from(building_type)?,
+        ))?,
+        Command::BulldozeLarge { x, y } => ctx.send_to_city(instructions::bulldoze_large_map(
+            &authority, &authority, None, x, y,
+        ))?,
+        Command::Expand { cx, cy, via } => ctx.send(
+            Route::BaseLayer,
+            &[instructions::expand_map(
+                &authority,
+                cx,
+                cy,
+                via.map(|via| (via[0], via[1])),
+            )],
+        )?,
+        Command::PlaceChunk {
+            cx,
+            cy,
+            x,
+            y,
+            building_type,
+        } => ctx.send_to_city(instructions::place_on_chunk(
+            &authority,
+            &authority,
+            None,
+            (cx, cy),
+            x,
+            y,
+            BuildingType::try_from(building_type)?,
+        ))?,
+        Command::BulldozeChunk { cx, cy, x, y } => ctx.send_to_city(
+            instructions::bulldoze_chunk(&authority, &authority, None, (cx, cy), x, y),
+        )?,
+        Command::DelegateChunk { cx, cy, validator } => ctx.send(
+            Route::BaseLayer,
+            &[instructions::delegate_chunk(&authority, cx, cy, validator)],
+        )?,
+        Command::UndelegateChunk { cx, cy } => ctx.send(
+            Route::EphemeralRollup,
+            &[instructions::undelegate_chunk(&authority, cx, cy)],
+        )?,
+        Command::ShowChunk {
+            cx,
+            cy,
+            authority: owner,
+            layer,
+        } => {
+            let owner = owner.unwrap_or(authority);
+            print_chunk(&state::decode_chunk(
+                &ctx.fetch(&pda::chunk(&owner, cx, cy), layer)?,
+            )?);
+            return Ok(());
+        }
+        Command::Protect { x, y } => ctx.send_to_city(instructions::protect_tile(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            x,
+            y,
+        ))?,
+        Command::Unprotect { x, y } => ctx.send_to_city(instructions::unprotect_tile(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            x,
+            y,
+        ))?,
+        Command::Borrow { amount } => ctx.send_to_city(instructions::take_loan(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            amount,
+        ))?,
+        Command::Repay { amount } => ctx.send_to_city(instructions::repay_loan(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            amount,
+        ))?,
+        Command::Restructure => ctx.send_to_city(instructions::restructure_debt(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+        ))?,
+        Command::Insure => ctx.send_to_city(instructions::buy_insurance(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+        ))?,
+        Command::CancelInsurance => ctx.send_to_city(instructions::cancel_insurance(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+        ))?,
+        Command::Budget { utilities, parks } => ctx.send_to_city(instructions::set_budget(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            Budget {
+                utilities_pct: utilities,
+                parks_pct: parks,
+            },
+        ))?,
+        Command::Tax { bps } => ctx.send_to_city(instructions::set_tax_rate(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            bps,
+        ))?,
+        Command::Funding {
+            police,
+            fire,
+            health,
+            education,
+        } => ctx.send_to_city(instructions::set_funding(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            ServiceFunding {
+                police_pct: police,
+                fire_pct: fire,
+                health_pct: health,
+                education_pct: education,
+            },
+        ))?,
+        Command::Report { authority: owner } => {
+            let owner = owner.unwrap_or(authority);
+            let data = ctx.simulate(
+                &pda::city(&owner),
+                instructions::collect_budget_report(&owner),
+            )?;
+            print_report(&BudgetReport::deserialize(&mut &data[..])?);
+            return Ok(());
+        }
+        Command::Unlocks => {
+            let data = ctx.simulate(&pda::city(&authority), instructions::building_unlocks())?;
+            for unlock in Vec::<BuildingUnlock>::deserialize(&mut &data[..])? {
+                println!(
+                    "{:<16} tier {} ({} residents)",
+                    format!("{:?}", unlock.building_type),
+                    unlock.tier,
+                    unlock.population
+                );
+            }
+            return Ok(());
+        }
+        Command::Step {
+            achievements,
+            profile,
+        } => ctx.send_to_city(instructions::step_simulation(
+            &authority,
+            &authority,
+            UpdateOptions {
+                achievements,
+                profile,
+                ..UpdateOptions::default()
+            },
+        ))?,
+        Command::Dispatch { x, y } => ctx.send_to_city(instructions::dispatch_emergency(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            x,
+            y,
+        ))?,
+        Command::Zone {
+            x,
+            y,
+            width,
+            height,
+            zone,
+        } => ctx.send_to_city(instructions::zone_tiles(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            (x, y),
+            (width, height),
+            BuildingType::try_from(zone)?,
+        ))?,
+        Command::Repair { x, y } => ctx.send_to_city(instructions::repair_building(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            x,
+            y,
+        ))?,
+        Command::UpgradeDensity { x, y } => ctx.send_to_city(instructions::upgrade_density(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            x,
+            y,
+        ))?,
+        Command::UpgradeRoad { x, y } => ctx.send_to_city(instructions::upgrade_road(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            x,
+            y,
+        ))?,
+        Command::Reclaim { x, y } => ctx.send_to_city(instructions::reclaim_land(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            x,
+            y,
+        ))?,
+        Command::Terraform { x, y, action } => ctx.send_to_city(instructions::terraform(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            x,
+            y,
+            match action {
+                TerraformArg::Raise => TerraformAction::Raise,
+                TerraformArg::Lower => TerraformAction::Lower,
+                TerraformArg::Flatten => TerraformAction::Flatten,
+            },
+        ))?,
+        Command::Claim { milestone, profile } => {
+            ctx.send_to_city(instructions::claim_milestone(
+                &authority,
+                &authority,
+                UpdateOptions {
+                    profile,
+                    ..UpdateOptions::default()
+                },
+                milestone,
+            ))?
+        }
+        Command::Quest { authority: owner } => {
+            let owner = owner.unwrap_or(authority);
+            let data = ctx.simulate(&pda::city(&owner), instructions::daily_quest(&owner))?;
+            let status = QuestStatus::deserialize(&mut &data[..])?;
+            println!("day:      {}", status.day);
+            println!("goal:     {:?}", status.quest.goal);
+            println!("reward:   {}", status.quest.reward);
+            println!("progress: {} / {}", status.progress, status.target);
+            println!("claimed:  {}", status.claimed);
+            return Ok(());
+        }
+        Command::ClaimQuest { profile } => ctx.send_to_city(instructions::claim_quest_reward(
+            &authority,
+            &authority,
+            UpdateOptions {
+                profile,
+                ..UpdateOptions::default()
+            },
+        ))?,
+        Command::Research { tech } => ctx.send_to_city(instructions::research(
+            &authority,
+            &authority,
+            UpdateOptions::default(),
+            match tech {
+                TechArg::Photovoltaics => Tech::Photovoltaics,
+                TechArg::Recycling => Tech::Recycling,
+                TechArg::Railways => Tech::Railways,
+                TechArg::Aviation => Tech::Aviation,
+                TechArg::HighRise => Tech::HighRise,
+                TechArg::Automation => Tech::Automation,
+                TechArg::Logistics => Tech::Logistics,
+            },
+        ))?,
+        Command::Ordinance { ordinance, repeal } => {
+            ctx.send_to_city(instructions::set_ordinance(
+                &authority,
+                &authority,
+                UpdateOptions::default(),
+                match ordinance {
+                    OrdinanceArg::FreeTransit => Ordinance::FreeTransit,
+                    OrdinanceArg::PollutionControls => Ordinance::PollutionControls,
+                    OrdinanceArg::NeighborhoodWatch => Ordinance::NeighborhoodWatch,
+                },
+                !repeal,
+            ))?
+        }
+        Command::InitAchievements => ctx.send(
+            Route::BaseLayer,
+            &[instructions::initialize_achievements(&authority)],
+        )?,
+        Command::InitProfile => ctx.send(
+            Route::BaseLayer,
+            &[instructions::initialize_profile(&authority)],
+        )?,
+        Command::Prestige => ctx.send_to_city(instructions::prestige_city(&authority))?,
+        Command::Delegate { validator } => ctx.send(
+            Route::BaseLayer,
+            &[instructions::delegate(&authority, validator)],
+        )?,
+        Command::CreateScenario {
+            id,
+            starting_money,
+            target_population,
+            max_pollution,
+            step_limit,
+        } => ctx.send(
+            Route::BaseLayer,
+            &[instructions::create_scenario(
+                &authority,
+                id,
+                ScenarioParams {
+                    starting_money,
+                    target_population,
+                    max_pollution,
+                    step_limit,
+                },
+            )],
+        )?,
+        Command::StartScenario { id } => {
+            ctx.send_to_city(instructions::start_scenario(&authority, id))?
+        }
+        Command::CheckScenario { id } => {
+            ctx.send_to_city(instructions::check_scenario_completion(&authority, id))?
+        }
+        Command::IssuePass { viewer } => ctx.send(
+            Route::BaseLayer,
+            &[instructions::issue_spectator_pass(&authority, &viewer)],
+        )?,
+        Command::RevokePass { viewer } => ctx.send(
+            Route::BaseLayer,
+            &[instructions::revoke_spectator_pass(&authority, &viewer)],
+        )?,
+        Command::Commit => ctx.send(Route::EphemeralRollup, &[instructions::commit(&authority)])?,
+        Command::Undelegate => ctx.send(
+            Route::EphemeralRollup,
+            &[instructions::undelegate(&authority)],
+        )?,
+        Command::Show {
+            target,
+            authority: owner,
+            layer,
+        } => {
+            let owner = owner.unwrap_or(authority);
+            match target {
+                ShowTarget::City => {
+                    print_city(&state::decode_city(&ctx.fetch(&pda::city(&owner), layer)?)?)
+                }
+                ShowTarget::Score => print_score(&state::decode_score(
+                    &ctx.fetch(&pda::score(&owner), layer)?,
+                )?),
+                ShowTarget::Config => {
+                    print_config(&state::decode_config(&ctx.fetch(&pda::config(), layer)?)?)
+                }
+                ShowTarget::Achievements => print_achievements(&state::decode_achievements(
+                    &ctx.fetch(&pda::achievements(&owner), layer)?,
+                )?),
+                ShowTarget::Profile => print_profile(&state::decode_profile(
+                    &ctx.fetch(&pda::profile(&owner), layer)?,
+                )?),
+                ShowTarget::LargeMap => print_large_map(&state::decode_large_map(
+                    &ctx.fetch(&pda::large_map(&owner), layer)?,
+                )?),
+            }
+            return Ok(());
+        }
+    };
+    println!("{signature}");
+    Ok(())
+}
+
+// ========================================
+// Output
+// ========================================
+
+fn print_city(city: &City) {
+    println!("authority:      {}", city.authority);
+    println!("population:     {}", city.population);
+    println!("homeless:       {}", city.homeless);
+    println!("garbage:        {} uncollected", city.garbage);
+    println!("crime:          {}", city.crime);
+    println!("health:         {}", city.health);
+    println!("education:      {}", city.education);
+    println!(
+        "demand:         R {} C {} I {}",
+        city.demand.residential, city.demand.commercial, city.demand.industrial
+    );
+    println!("jobs:           {}", city.jobs);
+    println!("workforce:      {}", city.workforce);
+    let resources = &city.resources;
+    println!(
+        "power:          {} / {}",
+        resources.power_consumed, resources.power_produced
+    );
+    println!(
+        "water:          {} / {}",
+        resources.water_consumed, resources.water_produced
+    );
+    println!(
+        "goods:          {} / {}",
+        resources.goods_consumed, resources.goods_produced
+    );
+    let powered: u32 = city.powered.iter().map(|row| row.count_ones()).sum();
+    println!("powered tiles:  {powered}");
+    let dry: u32 = city.dry.iter().map(|row| row.count_ones()).sum();
+    println!("dry tiles:      {dry}");
+    println!("congestion:     {}%", city.congestion);
+    println!("commerce:       {}", city.commerce_revenue);
+    println!("money:          {}", city.money);
+    println!("tax_rate:       {} bps", city.tax_rate_bps);
+    println!("chunks:         {} bought", city.chunks_owned);
+    let count = |kind: Terrain| {
+        city.terrain
+            .iter()
+            .flatten()
+            .filter(|&&code| terrain::terrain_of(code) == kind)
+            .count()
+    };
+    println!(
+        "terrain:        {} water, {} forest, {} mountain (seed {})",
+        count(Terrain::Water),
+        count(Terrain::Forest),
+        count(Terrain::Mountain),
+        city.terrain_seed
+    );
+    let flood_prone = (0..GRID_SIZE)
+        .flat_map(|y| (0..GRID_SIZE).map(move |x| (x, y)))
+        .filter(|&(x, y)| elevation::flood_prone(&city.elevation, &city.terrain, x, y))
+        .count();
+    println!(
+        "elevation:      up to {}, {} tiles flood-prone",
+        city.elevation.iter().flatten().max().unwrap_or(&0),
+        flood_prone
+    );
+    println!(
+        "loan:           {} at {} bps{}",
+        city.loan_principal,
+        city.loan_rate_bps,
+        if city.in_default { " (in default)" } else { "" }
+    );
+    println!(
+        "overdraft:      {}{}",
+        city.overdraft,
+        if city.bankrupt { " (bankrupt)" } else { "" }
+    );
+    println!("insured:        {}", city.insured);
+    println!("last_updated:   {}", city.last_updated);
+    println!("happiness:      {}", city.happiness);
+    println!(
+        "approval:       {}{}",
+        city.approval,
+        if city.caretaker { " (caretaker)" } else { "" }
+    );
+    let burning = city
+        .fires
+        .iter()
+        .flatten()
+        .filter(|&&steps| steps > 0)
+        .count();
+    println!("burning tiles:  {burning}");
+    let sites = city
+        .construction
+        .iter()
+        .flatten()
+        .filter(|&&steps| steps > 0)
+        .count();
+    println!("construction:   {sites} sites");
+    let polluted = city
+        .pollution
+        .iter()
+        .flatten()
+        .filter(|&&level| level > 0)
+        .count();
+    let peak = city.pollution.iter().flatten().max().copied().unwrap_or(0);
+    println!("pollution:      {polluted} tiles, peak {peak}");
+    println!(
+        "budget:         utilities {}%, parks {}%",
+        city.budget.utilities_pct, city.budget.parks_pct
+    );
+    println!(
+        "funding:        police {}%, fire {}%, health {}%, education {}%",
+        city.funding.police_pct,
+        city.funding.fire_pct,
+        city.funding.health_pct,
+        city.funding.education_pct
+    );
+    println!("features:       {:#b}", city.features);
+    println!("ordinances:     {:#b}", city.ordinances);
+    println!("layout_version: {}", city.layout_version);
+    println!("steps:          {}", city.step_count);
+    println!("season:         {:?}", city.season);
+    println!("time of day:    {:?}", city.day_phase);
+    println!("difficulty:     {:?}", city.difficulty);
+    println!("specialization: {:?}", city.specialization);
+    println!("techs:          {:#b}", city.techs);
+    println!(
+        "milestones:     {:#b} reached, {:#b} claimed",
+        city.milestones_reached, city.milestones_claimed
+    );
+    println!("prestige:       +{}%", city.prestige_pct);
+    if let Some(tech) = city.researching {
+        println!(
+            "researching:    {:?}, {} steps left",
+            tech, city.research_steps_left
+        );
+    }
+    if city.sandbox {
+        println!("sandbox:        true");
+    }
+    if city.scenario_status != ScenarioStatus::None {
+        println!(
+            "scenario:       {} ({:?})",
+            city.scenario, city.scenario_status
+        );
+    }
+    println!("spectator views: {}", city.spectator_views);
+    println!("tiles:");
+    for row in city.tiles.iter() {
+        let row: Vec<String> = row.iter().map(|tile| format!("{tile:>2}")).collect();
+        println!("  {}", row.join(" "));
+    }
+}
+
+fn print_score(score: &CityScore) {
+    println!("authority:    {}", score.authority);
+    println!("population:   {}", score.population);
+    println!("money:        {}", score.money);
+    println!("last_updated: {}", score.last_updated);
+}
+
+fn print_achievements(achievements: &Achievements) {
+    println!("authority: {}", achievements.authority);
+    for achievement in Achievement::ALL {
+        let mark = if achievements.has(achievement) {
+            "x"
+        } else {
+            " "
+        };
+        println!("[{mark}] {achievement:?}");
+    }
+}
+
+fn print_profile(profile: &MayorProfile) {
+    println!("authority: {}", profile.authority);
+    println!(
+        "level:     {} ({})",
+        profile.level,
+        mayor::title(profile.level)
+    );
+    println!("xp:        {}", profile.xp);
+    if let Some(next) = mayor::LEVEL_XP.get(profile.level as usize) {
+        println!("next:      {next}");
+    }
+    println!(
+        "prestige:  {} runs, +{}%",
+        profile.prestige_count, profile.prestige_pct
+    );
+}
+
+fn print_chunk(chunk: &Chunk) {
+    println!("city:  {}", chunk.city);
+    println!("chunk: ({}, {})", chunk.x, chunk.y);
+    println!("tiles:");
+    for row in chunk.tiles.iter() {
+        let row: Vec<String> = row.iter().map(|tile| format!("{tile:>2}")).collect();
+        println!("  {}", row.join(" "));
+    }
+}
+
+fn print_large_map(large_map: &LargeMap) {
+    println!("city:           {}", large_map.city);
+    println!("layout_version: {}", large_map.layout_version);
+    for (code, &count) in large_map.buildings.iter().enumerate() {
+        if count > 0 {
+            println!("{:?}: {count}", buildings::tile_type(code as u8));
+        }
+    }
+}
+
+fn print_config(config: &GameConfig) {
+    let params = &config.params;
+    println!("admin:                  {}", config.admin);
+    println!("randomness:             {:?}", config.randomness_source);
+    println!("default_features:       {:#b}", config.default_features);
+    println!("step_interval:          {}s", params.step_interval_seconds);
+    println!(
+        "season_length:          {} steps",
+        params.season_length_steps
+    );
+    println!("day_length:             {}s", params.day_length_seconds);
+    println!("growth_rate:            {}%", params.growth_rate_pct);
+    println!(
+        "housing_per_residential: {}",
+        params.housing_per_residential
+    );
+    println!("jobs_per_workplace:     {}", params.jobs_per_workplace);
+    println!("welfare_per_unemployed: {}", params.welfare_per_unemployed);
+    println!(
+        "residential_tax:        {}",
+        params.residential_tax_per_capita
+    );
+    println!(
+        "commercial_per_worker:  {}",
+        params.commercial_income_per_worker
+    );
+    println!(
+        "industrial_per_worker:  {}",
+        params.industrial_income_per_worker
+    );
+    println!(
+        "education_income_bonus: {}%",
+        params.education_income_bonus_pct
+    );
+    println!(
+        "commercial_near_homes:  +{}%",
+        params.commercial_near_homes_bonus_pct
+    );
+    println!(
+        "industry_near_homes:    -{} happiness",
+        params.industry_near_homes_penalty
+    );
+    println!(
+        "traffic_income_penalty: {}%",
+        params.traffic_income_penalty_pct
+    );
+    println!("crime_chance:           {}%", params.crime_chance_pct);
+    println!("crime_theft:            {}", params.crime_theft);
+    println!(
+        "unrest:                 below {} happiness, -{}% residents",
+        params.unrest_happiness, params.unrest_decline_pct
+    );
+    println!(
+        "residents_per_crime:    {}",
+        params.residents_per_crime_point
+    );
+    println!(
+        "crime_income_penalty:   {}%",
+        params.crime_income_penalty_pct
+    );
+    println!(
+        "tax_approval_penalty:   -{} approval",
+        params.tax_approval_penalty
+    );
+    println!(
+        "elections:              every {} steps, {} approval to win",
+        params.election_interval_steps, params.approval_threshold
+    );
+    println!(
+        "caretaker_penalty:      {}%",
+        params.caretaker_income_penalty_pct
+    );
+    println!("fire_chance:            {}%", params.fire_chance_pct);
+    println!("fire_burn_steps:        {}", params.fire_burn_steps);
+    println!("disaster_chance:        {}%", params.disaster_chance_pct);
+    println!("flood_chance:           {}%", params.flood_chance_pct);
+    println!("insurance_premium:      {}", params.insurance_premium);
+    println!("insurance_payout:       {}%", params.insurance_payout_pct);
+    println!("quest_seed:             {}", params.quest_seed);
+    println!("dispatch_cost:          {}", params.dispatch_cost);
+    println!("price_drift:            {}%", params.price_drift_pct);
+    println!("zoning_fee:             {}", params.zoning_fee);
+    println!("construction_steps:     {}", params.construction_steps);
+    println!("decay_per_step:         {}", params.decay_per_step);
+    println!("repair_cost:            {}%", params.repair_cost_pct);
+    println!("refund:                 {}%", params.refund_pct);
+    println!(
+        "reseed_interval:        {} steps",
+        params.reseed_interval_steps
+    );
+    println!(
+        "randomness_timeout:     {}s",
+        params.randomness_timeout_seconds
+    );
+    println!("police_radius:          {}", params.police_radius);
+    println!("fire_radius:            {}", params.fire_radius);
+    println!("health_radius:          {}", params.health_radius);
+    println!("education_radius:       {}", params.education_radius);
+}
+
+fn print_report(report: &BudgetReport) {
+    println!("residential tax:    {}", report.residential_tax);
+    println!("commercial income:  {}", report.commercial_income);
+    println!("industrial income:  {}", report.industrial_income);
+    println!("welfare:           -{}", report.welfare);
+    println!("safety:            -{}", report.safety_spending);
+    println!("utilities:         -{}", report.utilities_spending);
+    println!("parks:             -{}", report.parks_spending);
+    println!("other upkeep:      -{}", report.other_upkeep);
+    println!("loan interest:     -{}", report.loan_interest);
+    println!("insurance:         -{}", report.insurance_premium);
+    println!("ordinances:        -{}", report.ordinances);
+    println!("net:                {}", report.net);
+}
@@ -0,0 +1,1065 @@
+//! Typed instruction builders.
+//!
+//! Builders take the city authority and fill in every derived account.
+//! Instructions sharing the `UpdateCity` accounts can also be built from
+//! their raw `counter::instruction::*` data with [`update_city`].
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use counter::budget::{Budget, ServiceFunding};
+use counter::buildings::BuildingType;
+use counter::config::GameParams;
+use counter::difficulty::Difficulty;
+use counter::elevation::TerraformAction;
+use counter::ordinances::Ordinance;
+use counter::randomness::RandomnessMode;
+use counter::research::Tech;
+use counter::scenario::ScenarioParams;
+use counter::specialization::Specialization;
+use counter::{accounts, instruction, Action, BulldozeArgs, PlaceBuildingArgs};
+use ephemeral_rollups_sdk::consts::{MAGIC_CONTEXT_ID, MAGIC_PROGRAM_ID};
+
+use crate::{pda, PROGRAM_ID};
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Optional accounts of `UpdateCity`-based instructions
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateOptions {
+    /// Session token when `signer` is a session key rather than the authority
+    pub session_token: Option<Pubkey>,
+    /// Pass the coverage overlay so it is maintained incrementally
+    pub coverage: bool,
+    /// Pass the achievements account so newly earned ones are unlocked
+    pub achievements: bool,
+    /// Pass the mayor profile so it is credited with experience
+    pub profile: bool,
+}
+
+fn update_city_accounts(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+) -> accounts::UpdateCity {
+    accounts::UpdateCity {
+        city: pda::city(authority),
+        signer: *signer,
+        session_token: options.session_token,
+        // The config decides whether steps roll from it, so always pass it
+        slot_hashes: Some(anchor_lang::solana_program::sysvar::slot_hashes::ID),
+        config: pda::config(),
+        coverage: options.coverage.then(|| pda::coverage(authority)),
+        achievements: options.achievements.then(|| pda::achievements(authority)),
+        profile: options.profile.then(|| pda::profile(authority)),
+    }
+}
+
+/// Any instruction using the `UpdateCity` accounts
+pub fn update_city(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    data: impl InstructionData,
+) -> Instruction {
+    build(update_city_accounts(authority, signer, options), data)
+}
+
+pub fn initialize_city(
+    authority: &Pubkey,
+    sandbox: bool,
+    difficulty: Difficulty,
+    specialization: Specialization,
+) -> Instruction {
+    build(
+        accounts::InitializeCity {
+            city: pda::city(authority),
+            config: pda::config(),
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeCity {
+            sandbox,
+            difficulty,
+            specialization,
+        },
+    )
+}
+
+pub fn place_building(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    args: PlaceBuildingArgs,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::PlaceBuilding { args },
+    )
+}
+
+pub fn bulldoze(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    args: BulldozeArgs,
+) -> Instruction {
+    update_city(authority, signer, options, instruction::Bulldoze { args })
+}
+
+pub fn do_actions(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    actions: Vec<Action>,
+    idempotency_key: Option<u64>,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::DoActions {
+            actions,
+            idempotency_key,
+        },
+    )
+}
+
+pub fn step_simulation(authority: &Pubkey, signer: &Pubkey, options: UpdateOptions) -> Instruction {
+    update_city(authority, signer, options, instruction::StepSimulation {})
+}
+
+pub fn buy_insurance(authority: &Pubkey, signer: &Pubkey, options: UpdateOptions) -> Instruction {
+    update_city(authority, signer, options, instruction::BuyInsurance {})
+}
+
+pub fn cancel_insurance(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+) -> Instruction {
+    update_city(authority, signer, options, instruction::CancelInsurance {})
+}
+
+/// Oracle answer to a `RandomnessRequested` event for `authority`'s city
+pub fn consume_randomness(
+    authority: &Pubkey,
+    oracle: &Pubkey,
+    randomness: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::ConsumeRandomness {
+            city: pda::city(authority),
+            config: pda::config(),
+            oracle: *oracle,
+        },
+        instruction::ConsumeRandomness { randomness },
+    )
+}
+
+pub fn take_loan(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    amount: u64,
+) -> Instruction {
+    update_city(authority, signer, options, instruction::TakeLoan { amount })
+}
+
+pub fn repay_loan(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    amount: u64,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::RepayLoan { amount },
+    )
+}
+
+pub fn restructure_debt(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+) -> Instruction {
+    update_city(authority, signer, options, instruction::RestructureDebt {})
+}
+
+pub fn set_ordinance(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    ordinance: Ordinance,
+    enabled: bool,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::SetOrdinance { ordinance, enabled },
+    )
+}
+
+pub fn set_budget(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    budget: Budget,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::SetBudget { budget },
+    )
+}
+
+pub fn set_tax_rate(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    bps: u16,
+) -> Instruction {
+    update_city(authority, signer, options, instruction::SetTaxRate { bps })
+}
+
+pub fn set_funding(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    funding: ServiceFunding,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::SetFunding { funding },
+    )
+}
+
+pub fn protect_tile(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    x: u8,
+    y: u8,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::ProtectTile { x, y },
+    )
+}
+
+pub fn unprotect_tile(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    x: u8,
+    y: u8,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::UnprotectTile { x, y },
+    )
+}
+
+pub fn dispatch_emergency(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    x: u8,
+    y: u8,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::DispatchEmergency { x, y },
+    )
+}
+
+/// Zone the `(width, height)` rectangle whose top-left tile is `(x, y)`
+pub fn zone_tiles(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    (x, y): (u8, u8),
+    (width, height): (u8, u8),
+    zone: BuildingType,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::ZoneTiles {
+            x,
+            y,
+            width,
+            height,
+            zone,
+        },
+    )
+}
+
+pub fn repair_building(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    x: u8,
+    y: u8,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::RepairBuilding { x, y },
+    )
+}
+
+pub fn upgrade_density(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    x: u8,
+    y: u8,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::UpgradeDensity { x, y },
+    )
+}
+
+pub fn claim_milestone(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    milestone: u8,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::ClaimMilestone { milestone },
+    )
+}
+
+pub fn claim_quest_reward(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+) -> Instruction {
+    update_city(authority, signer, options, instruction::ClaimQuestReward {})
+}
+
+pub fn research(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    tech: Tech,
+) -> Instruction {
+    update_city(authority, signer, options, instruction::Research { tech })
+}
+
+pub fn upgrade_road(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    x: u8,
+    y: u8,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::UpgradeRoad { x, y },
+    )
+}
+
+pub fn reclaim_land(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    x: u8,
+    y: u8,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::ReclaimLand { x, y },
+    )
+}
+
+pub fn terraform(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    options: UpdateOptions,
+    x: u8,
+    y: u8,
+    action: TerraformAction,
+) -> Instruction {
+    update_city(
+        authority,
+        signer,
+        options,
+        instruction::Terraform { x, y, action },
+    )
+}
+
+fn view_city_accounts(authority: &Pubkey) -> accounts::ViewCity {
+    accounts::ViewCity {
+        city: pda::city(authority),
+        config: pda::config(),
+    }
+}
+
+/// Simulate to read the projection from the transaction's return data
+pub fn project_steps(authority: &Pubkey, steps: u16) -> Instruction {
+    build(
+        view_city_accounts(authority),
+        instruction::ProjectSteps { steps },
+    )
+}
+
+/// Simulate to read the `BudgetReport` from the transaction's return data
+pub fn collect_budget_report(authority: &Pubkey) -> Instruction {
+    build(
+        view_city_accounts(authority),
+        instruction::CollectBudgetReport {},
+    )
+}
+
+/// Simulate to read today's quest from the transaction's return data
+pub fn daily_quest(authority: &Pubkey) -> Instruction {
+    build(view_city_accounts(authority), instruction::DailyQuest {})
+}
+
+/// Simulate to read the unlock table from the transaction's return data
+pub fn building_unlocks() -> Instruction {
+    build(accounts::ViewUnlocks {}, instruction::BuildingUnlocks {})
+}
+
+/// Simulate to read the quoted cost from the transaction's return data
+pub fn quote_placement(
+    authority: &Pubkey,
+    x: u8,
+    y: u8,
+    building_type: BuildingType,
+) -> Instruction {
+    build(
+        view_city_accounts(authority),
+        instruction::QuotePlacement {
+            x,
+            y,
+            building_type,
+        },
+    )
+}
+
+// ========================================
+// Delegation
+// ========================================
+
+fn with_validator(mut ix: Instruction, validator: Option<Pubkey>) -> Instruction {
+    if let Some(validator) = validator {
+        ix.accounts
+            .push(AccountMeta::new_readonly(validator, false));
+    }
+    ix
+}
+
+/// Delegate the city to the ER, optionally pinned to `validator`
+pub fn delegate(authority: &Pubkey, validator: Option<Pubkey>) -> Instruction {
+    let city = pda::city(authority);
+    let pdas = pda::delegation(&city);
+    let ix = build(
+        accounts::DelegateInput {
+            payer: *authority,
+            buffer_pda: pdas.buffer,
+            delegation_record_pda: pdas.delegation_record,
+            delegation_metadata_pda: pdas.delegation_metadata,
+            pda: city,
+            owner_program: PROGRAM_ID,
+            delegation_program: ephemeral_rollups_sdk::id(),
+            system_program: system_program::ID,
+        },
+        instruction::Delegate {},
+    );
+    with_validator(ix, validator)
+}
+
+fn commit_accounts(authority: &Pubkey) -> accounts::CommitInput {
+    accounts::CommitInput {
+        payer: *authority,
+        city: pda::city(authority),
+        magic_program: MAGIC_PROGRAM_ID,
+        magic_context: MAGIC_CONTEXT_ID,
+    }
+}
+
+/// Commit the city state from the ER to the base layer (send to the ER)
+pub fn commit(authority: &Pubkey) -> Instruction {
+    build(commit_accounts(authority), instruction::Commit {})
+}
+
+/// Commit and hand the city back to the base layer (send to the ER)
+pub fn undelegate(authority: &Pubkey) -> Instruction {
+    build(commit_accounts(authority), instruction::Undelegate {})
+}
+
+// ========================================
+// Score
+// ========================================
+
+pub fn initialize_score(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::InitializeScore {
+            score: pda::score(authority),
+            city: pda::city(authority),
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeScore {},
+    )
+}
+
+pub fn delegate_score(authority: &Pubkey, validator: Option<Pubkey>) -> Instruction {
+    let score = pda::score(authority);
+    let pdas = pda::delegation(&score);
+    let ix = build(
+        accounts::DelegateScoreInput {
+            payer: *authority,
+            buffer_pda: pdas.buffer,
+            delegation_record_pda: pdas.delegation_record,
+            delegation_metadata_pda: pdas.delegation_metadata,
+            pda: score,
+            owner_program: PROGRAM_ID,
+            delegation_program: ephemeral_rollups_sdk::id(),
+            system_program: system_program::ID,
+        },
+        instruction::DelegateScore {},
+    );
+    with_validator(ix, validator)
+}
+
+fn commit_score_accounts(authority: &Pubkey) -> accounts::CommitScoreInput {
+    accounts::CommitScoreInput {
+        payer: *authority,
+        city: pda::city(authority),
+        score: pda::score(authority),
+        magic_program: MAGIC_PROGRAM_ID,
+        magic_context: MAGIC_CONTEXT_ID,
+    }
+}
+
+pub fn commit_score(authority: &Pubkey) -> Instruction {
+    build(
+        commit_score_accounts(authority),
+        instruction::CommitScore {},
+    )
+}
+
+pub fn undelegate_score(authority: &Pubkey) -> Instruction {
+    build(
+        commit_score_accounts(authority),
+        instruction::UndelegateScore {},
+    )
+}
+
+// ========================================
+// Coverage Overlay
+// ========================================
+
+pub fn initialize_coverage(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::InitializeCoverage {
+            coverage: pda::coverage(authority),
+            city: pda::city(authority),
+            config: pda::config(),
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeCoverage {},
+    )
+}
+
+pub fn delegate_coverage(authority: &Pubkey, validator: Option<Pubkey>) -> Instruction {
+    let coverage = pda::coverage(authority);
+    let pdas = pda::delegation(&coverage);
+    let ix = build(
+        accounts::DelegateCoverageInput {
+            payer: *authority,
+            buffer_pda: pdas.buffer,
+            delegation_record_pda: pdas.delegation_record,
+            delegation_metadata_pda: pdas.delegation_metadata,
+            pda: coverage,
+            owner_program: PROGRAM_ID,
+            delegation_program: ephemeral_rollups_sdk::id(),
+            system_program: system_program::ID,
+        },
+        instruction::DelegateCoverage {},
+    );
+    with_validator(ix, validator)
+}
+
+pub fn undelegate_coverage(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::UndelegateCoverageInput {
+            payer: *authority,
+            coverage: pda::coverage(authority),
+            magic_program: MAGIC_PROGRAM_ID,
+            magic_context: MAGIC_CONTEXT_ID,
+        },
+        instruction::UndelegateCoverage {},
+    )
+}
+
+// ========================================
+// Large Map
+// ========================================
+
+pub fn initialize_large_map(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::InitializeLargeMap {
+            large_map: pda::large_map(authority),
+            city: pda::city(authority),
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeLargeMap {},
+    )
+}
+
+fn update_large_map_accounts(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    session_token: Option<Pubkey>,
+) -> accounts::UpdateLargeMap {
+    accounts::UpdateLargeMap {
+        city: pda::city(authority),
+        large_map: pda::large_map(authority),
+        signer: *signer,
+        session_token,
+        config: pda::config(),
+    }
+}
+
+pub fn place_on_large_map(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    session_token: Option<Pubkey>,
+    x: u16,
+    y: u16,
+    building_type: BuildingType,
+) -> Instruction {
+    build(
+        update_large_map_accounts(authority, signer, session_token),
+        instruction::PlaceOnLargeMap {
+            x,
+            y,
+            building_type,
+        },
+    )
+}
+
+pub fn bulldoze_large_map(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    session_token: Option<Pubkey>,
+    x: u16,
+    y: u16,
+) -> Instruction {
+    build(
+        update_large_map_accounts(authority, signer, session_token),
+        instruction::BulldozeLargeMap { x, y },
+    )
+}
+
+pub fn delegate_large_map(authority: &Pubkey, validator: Option<Pubkey>) -> Instruction {
+    let large_map = pda::large_map(authority);
+    let pdas = pda::delegation(&large_map);
+    let ix = build(
+        accounts::DelegateLargeMapInput {
+            payer: *authority,
+            buffer_pda: pdas.buffer,
+            delegation_record_pda: pdas.delegation_record,
+            delegation_metadata_pda: pdas.delegation_metadata,
+            pda: large_map,
+            owner_program: PROGRAM_ID,
+            delegation_program: ephemeral_rollups_sdk::id(),
+            system_program: system_program::ID,
+        },
+        instruction::DelegateLargeMap {},
+    );
+    with_validator(ix, validator)
+}
+
+pub fn undelegate_large_map(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::UndelegateLargeMapInput {
+            payer: *authority,
+            large_map: pda::large_map(authority),
+            magic_program: MAGIC_PROGRAM_ID,
+            magic_context: MAGIC_CONTEXT_ID,
+        },
+        instruction::UndelegateLargeMap {},
+    )
+}
+
+// ========================================
+// Chunks
+// ========================================
+
+/// Buy chunk (x, y), bordering the city's own grid or the owned chunk
+/// `neighbor`
+pub fn expand_map(authority: &Pubkey, x: i8, y: i8, neighbor: Option<(i8, i8)>) -> Instruction {
+    build(
+        accounts::ExpandMap {
+            chunk: pda::chunk(authority, x, y),
+            city: pda::city(authority),
+            neighbor: neighbor.map(|(nx, ny)| pda::chunk(authority, nx, ny)),
+            config: pda::config(),
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::ExpandMap { x, y },
+    )
+}
+
+fn update_chunk_accounts(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    session_token: Option<Pubkey>,
+    chunk: (i8, i8),
+) -> accounts::UpdateChunk {
+    accounts::UpdateChunk {
+        city: pda::city(authority),
+        chunk: pda::chunk(authority, chunk.0, chunk.1),
+        signer: *signer,
+        session_token,
+        config: pda::config(),
+    }
+}
+
+/// Place a building at (x, y) within `chunk`
+pub fn place_on_chunk(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    session_token: Option<Pubkey>,
+    chunk: (i8, i8),
+    x: u8,
+    y: u8,
+    building_type: BuildingType,
+) -> Instruction {
+    build(
+        update_chunk_accounts(authority, signer, session_token, chunk),
+        instruction::PlaceOnChunk {
+            x,
+            y,
+            building_type,
+        },
+    )
+}
+
+/// Clear (x, y) within `chunk`
+pub fn bulldoze_chunk(
+    authority: &Pubkey,
+    signer: &Pubkey,
+    session_token: Option<Pubkey>,
+    chunk: (i8, i8),
+    x: u8,
+    y: u8,
+) -> Instruction {
+    build(
+        update_chunk_accounts(authority, signer, session_token, chunk),
+        instruction::BulldozeChunk { x, y },
+    )
+}
+
+pub fn delegate_chunk(authority: &Pubkey, x: i8, y: i8, validator: Option<Pubkey>) -> Instruction {
+    let chunk = pda::chunk(authority, x, y);
+    let pdas = pda::delegation(&chunk);
+    let ix = build(
+        accounts::DelegateChunkInput {
+            payer: *authority,
+            city: pda::city(authority),
+            buffer_pda: pdas.buffer,
+            delegation_record_pda: pdas.delegation_record,
+            delegation_metadata_pda: pdas.delegation_metadata,
+            pda: chunk,
+            owner_program: PROGRAM_ID,
+            delegation_program: ephemeral_rollups_sdk::id(),
+            system_program: system_program::ID,
+        },
+        instruction::DelegateChunk { x, y },
+    );
+    with_validator(ix, validator)
+}
+
+pub fn undelegate_chunk(authority: &Pubkey, x: i8, y: i8) -> Instruction {
+    build(
+        accounts::UndelegateChunkInput {
+            payer: *authority,
+            city: pda::city(authority),
+            chunk: pda::chunk(authority, x, y),
+            magic_program: MAGIC_PROGRAM_ID,
+            magic_context: MAGIC_CONTEXT_ID,
+        },
+        instruction::UndelegateChunk {},
+    )
+}
+
+// ========================================
+// Achievements
+// ========================================
+
+pub fn initialize_achievements(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::InitializeAchievements {
+            achievements: pda::achievements(authority),
+            city: pda::city(authority),
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeAchievements {},
+    )
+}
+
+pub fn delegate_achievements(authority: &Pubkey, validator: Option<Pubkey>) -> Instruction {
+    let achievements = pda::achievements(authority);
+    let pdas = pda::delegation(&achievements);
+    let ix = build(
+        accounts::DelegateAchievementsInput {
+            payer: *authority,
+            buffer_pda: pdas.buffer,
+            delegation_record_pda: pdas.delegation_record,
+            delegation_metadata_pda: pdas.delegation_metadata,
+            pda: achievements,
+            owner_program: PROGRAM_ID,
+            delegation_program: ephemeral_rollups_sdk::id(),
+            system_program: system_program::ID,
+        },
+        instruction::DelegateAchievements {},
+    );
+    with_validator(ix, validator)
+}
+
+pub fn undelegate_achievements(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::UndelegateAchievementsInput {
+            payer: *authority,
+            achievements: pda::achievements(authority),
+            magic_program: MAGIC_PROGRAM_ID,
+            magic_context: MAGIC_CONTEXT_ID,
+        },
+        instruction::UndelegateAchievements {},
+    )
+}
+
+// ========================================
+// Mayor Profile
+// ========================================
+
+pub fn initialize_profile(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::InitializeProfile {
+            profile: pda::profile(authority),
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeProfile {},
+    )
+}
+
+pub fn delegate_profile(authority: &Pubkey, validator: Option<Pubkey>) -> Instruction {
+    let profile = pda::profile(authority);
+    let pdas = pda::delegation(&profile);
+    let ix = build(
+        accounts::DelegateProfileInput {
+            payer: *authority,
+            buffer_pda: pdas.buffer,
+            delegation_record_pda: pdas.delegation_record,
+            delegation_metadata_pda: pdas.delegation_metadata,
+            pda: profile,
+            owner_program: PROGRAM_ID,
+            delegation_program: ephemeral_rollups_sdk::id(),
+            system_program: system_program::ID,
+        },
+        instruction::DelegateProfile {},
+    );
+    with_validator(ix, validator)
+}
+
+pub fn undelegate_profile(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::UndelegateProfileInput {
+            payer: *authority,
+            profile: pda::profile(authority),
+            magic_program: MAGIC_PROGRAM_ID,
+            magic_context: MAGIC_CONTEXT_ID,
+        },
+        instruction::UndelegateProfile {},
+    )
+}
+
+/// Must be signed by the city authority itself, not a session key
+pub fn prestige_city(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::PrestigeCity {
+            city: pda::city(authority),
+            profile: pda::profile(authority),
+            authority: *authority,
+        },
+        instruction::PrestigeCity {},
+    )
+}
+
+// ========================================
+// Game Config
+// ========================================
+
+/// Must be signed by the program's upgrade authority
+pub fn initialize_config(admin: &Pubkey) -> Instruction {
+    build(
+        accounts::InitializeConfig {
+            config: pda::config(),
+            admin: *admin,
+            program: PROGRAM_ID,
+            program_data: pda::program_data(),
+            system_program: system_program::ID,
+        },
+        instruction::InitializeConfig {},
+    )
+}
+
+pub fn update_config(admin: &Pubkey, params: GameParams) -> Instruction {
+    build(
+        accounts::UpdateConfig {
+            config: pda::config(),
+            admin: *admin,
+        },
+        instruction::UpdateConfig { params },
+    )
+}
+
+pub fn set_randomness_oracle(admin: &Pubkey, oracle: &Pubkey) -> Instruction {
+    build(
+        accounts::UpdateConfig {
+            config: pda::config(),
+            admin: *admin,
+        },
+        instruction::SetRandomnessOracle { oracle: *oracle },
+    )
+}
+
+pub fn set_default_features(admin: &Pubkey, features: u32) -> Instruction {
+    build(
+        accounts::UpdateConfig {
+            config: pda::config(),
+            admin: *admin,
+        },
+        instruction::SetDefaultFeatures { features },
+    )
+}
+
+pub fn set_randomness_source(admin: &Pubkey, source: RandomnessMode) -> Instruction {
+    build(
+        accounts::UpdateConfig {
+            config: pda::config(),
+            admin: *admin,
+        },
+        instruction::SetRandomnessSource { source },
+    )
+}
+
+// ========================================
+// Scenarios
+// ========================================
+
+pub fn create_scenario(admin: &Pubkey, id: u32, params: ScenarioParams) -> Instruction {
+    build(
+        accounts::CreateScenario {
+            scenario: pda::scenario(id),
+            config: pda::config(),
+            admin: *admin,
+            system_program: system_program::ID,
+        },
+        instruction::CreateScenario { id, params },
+    )
+}
+
+pub fn start_scenario(authority: &Pubkey, id: u32) -> Instruction {
+    build(
+        accounts::StartScenario {
+            city: pda::city(authority),
+            scenario: pda::scenario(id),
+            authority: *authority,
+        },
+        instruction::StartScenario {},
+    )
+}
+
+pub fn check_scenario_completion(authority: &Pubkey, id: u32) -> Instruction {
+    build(
+        accounts::CheckScenario {
+            city: pda::city(authority),
+            scenario: pda::scenario(id),
+        },
+        instruction::CheckScenarioCompletion {},
+    )
+}
+
+// ========================================
+// Spectators
+// ========================================
+
+pub fn issue_spectator_pass(authority: &Pubkey, viewer: &Pubkey) -> Instruction {
+    build(
+        accounts::IssueSpectatorPass {
+            pass: pda::spectator_pass(authority, viewer),
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::IssueSpectatorPass { viewer: *viewer },
+    )
+}
+
+pub fn revoke_spectator_pass(authority: &Pubkey, viewer: &Pubkey) -> Instruction {
+    build(
+        accounts::RevokeSpectatorPass {
+            pass: pda::spectator_pass(authority, viewer),
+            authority: *authority,
+        },
+        instruction::RevokeSpectatorPass {},
+    )
+}
+
+/// Signed by the viewer; send wherever the city lives
+pub fn spectate(authority: &Pubkey, viewer: &Pubkey) -> Instruction {
+    build(
+        accounts::Spectate {
+            city: pda::city(authority),
+            pass: pda::spectator_pass(authority, viewer),
+            viewer: *viewer,
+        },
+        instruction::Spectate {},
+    )
+}
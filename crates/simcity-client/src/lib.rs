@@ -0,0 +1,15 @@
+//! Client SDK for the `simcity_build` program.
+//!
+//! - [`pda`]: address derivation for every program account
+//! - [`instructions`]: typed instruction builders
+//! - [`state`]: account decoding for `City` and friends
+//! - [`routing`]: picking the base layer or the ephemeral rollup per account
+
+pub mod instructions;
+pub mod pda;
+pub mod routing;
+pub mod state;
+
+pub use counter::buildings::BuildingType;
+pub use counter::ID as PROGRAM_ID;
+pub use counter::{Action, BulldozeArgs, City, CityScore, PlaceBuildingArgs, Projection};
@@ -0,0 +1,117 @@
+//! PDA derivation helpers
+
+use anchor_lang::prelude::Pubkey;
+use counter::achievements::ACHIEVEMENTS_SEED;
+use counter::chunks::CHUNK_SEED;
+use counter::config::CONFIG_SEED;
+use counter::coverage::COVERAGE_SEED;
+use counter::large_map::LARGE_MAP_SEED;
+use counter::mayor::PROFILE_SEED;
+use counter::scenario::SCENARIO_SEED;
+use counter::spectator::SPECTATOR_SEED;
+use counter::SCORE_SEED;
+use ephemeral_rollups_sdk::pda::{
+    DELEGATE_BUFFER_TAG, DELEGATION_METADATA_TAG, DELEGATION_RECORD_TAG,
+};
+
+use crate::PROGRAM_ID;
+
+/// City account owned by `authority`
+pub fn city(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[authority.as_ref()], &PROGRAM_ID).0
+}
+
+/// Compact score account of `authority`'s city
+pub fn score(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[SCORE_SEED, authority.as_ref()], &PROGRAM_ID).0
+}
+
+/// Cached coverage overlay of `authority`'s city
+pub fn coverage(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[COVERAGE_SEED, authority.as_ref()], &PROGRAM_ID).0
+}
+
+/// Large map of `authority`'s city
+pub fn large_map(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[LARGE_MAP_SEED, authority.as_ref()], &PROGRAM_ID).0
+}
+
+/// Chunk (x, y) of `authority`'s city
+pub fn chunk(authority: &Pubkey, x: i8, y: i8) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            CHUNK_SEED,
+            city(authority).as_ref(),
+            &x.to_le_bytes(),
+            &y.to_le_bytes(),
+        ],
+        &PROGRAM_ID,
+    )
+    .0
+}
+
+/// Achievements account of `authority`
+pub fn achievements(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ACHIEVEMENTS_SEED, authority.as_ref()], &PROGRAM_ID).0
+}
+
+/// Mayor profile of `authority`
+pub fn profile(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[PROFILE_SEED, authority.as_ref()], &PROGRAM_ID).0
+}
+
+/// Pass letting `viewer` spectate `authority`'s city
+pub fn spectator_pass(authority: &Pubkey, viewer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[SPECTATOR_SEED, authority.as_ref(), viewer.as_ref()],
+        &PROGRAM_ID,
+    )
+    .0
+}
+
+/// Scenario published under `id`
+pub fn scenario(id: u32) -> Pubkey {
+    Pubkey::find_program_address(&[SCENARIO_SEED, &id.to_le_bytes()], &PROGRAM_ID).0
+}
+
+/// Global game config
+pub fn config() -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG_SEED], &PROGRAM_ID).0
+}
+
+/// ProgramData account of the program, needed by `initialize_config`
+pub fn program_data() -> Pubkey {
+    Pubkey::find_program_address(
+        &[PROGRAM_ID.as_ref()],
+        &anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+    )
+    .0
+}
+
+/// Accounts the delegation program needs alongside a delegated PDA
+pub struct DelegationPdas {
+    pub buffer: Pubkey,
+    pub delegation_record: Pubkey,
+    pub delegation_metadata: Pubkey,
+}
+
+pub fn delegation(delegated: &Pubkey) -> DelegationPdas {
+    let delegation_program = ephemeral_rollups_sdk::id();
+    DelegationPdas {
+        buffer: Pubkey::find_program_address(
+            &[DELEGATE_BUFFER_TAG, delegated.as_ref()],
+            &PROGRAM_ID,
+        )
+        .0,
+        delegation_record: Pubkey::find_program_address(
+            &[DELEGATION_RECORD_TAG, delegated.as_ref()],
+            &delegation_program,
+        )
+        .0,
+        delegation_metadata: Pubkey::find_program_address(
+            &[DELEGATION_METADATA_TAG, delegated.as_ref()],
+            &delegation_program,
+        )
+        .0,
+    }
+}
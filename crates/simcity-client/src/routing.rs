@@ -0,0 +1,59 @@
+//! Base layer vs ephemeral rollup routing.
+//!
+//! While an account is delegated, the base layer copy is frozen and owned
+//! by the delegation program; reads and writes must go to the ER instead.
+
+use anchor_lang::prelude::Pubkey;
+
+pub const DEFAULT_BASE_URL: &str = "https://api.devnet.solana.com";
+pub const DEFAULT_ER_URL: &str = "https://rpc.magicblock.app/devnet";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Route {
+    BaseLayer,
+    EphemeralRollup,
+}
+
+/// True when the account's base-layer owner is the delegation program
+pub fn is_delegated(base_layer_owner: &Pubkey) -> bool {
+    *base_layer_owner == ephemeral_rollups_sdk::id()
+}
+
+/// Where to send reads and writes for an account with this base-layer owner
+pub fn route(base_layer_owner: &Pubkey) -> Route {
+    if is_delegated(base_layer_owner) {
+        Route::EphemeralRollup
+    } else {
+        Route::BaseLayer
+    }
+}
+
+/// RPC endpoints of a deployment
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Endpoints {
+    pub base_url: String,
+    pub er_url: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            er_url: DEFAULT_ER_URL.to_string(),
+        }
+    }
+}
+
+impl Endpoints {
+    pub fn url(&self, route: Route) -> &str {
+        match route {
+            Route::BaseLayer => &self.base_url,
+            Route::EphemeralRollup => &self.er_url,
+        }
+    }
+
+    /// RPC URL to use for an account with this base-layer owner
+    pub fn for_owner(&self, base_layer_owner: &Pubkey) -> &str {
+        self.url(route(base_layer_owner))
+    }
+}
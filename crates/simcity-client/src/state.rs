@@ -0,0 +1,61 @@
+//! Account decoding
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::{AccountDeserialize, Discriminator, Result};
+use counter::achievements::Achievements;
+use counter::chunks::Chunk;
+use counter::config::GameConfig;
+use counter::coverage::CoverageOverlay;
+use counter::large_map::LargeMap;
+use counter::mayor::MayorProfile;
+use counter::spectator::SpectatorPass;
+use counter::{City, CityScore};
+
+/// Decode raw account data (discriminator included) into a program account
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_achievements(data: &[u8]) -> Result<Achievements> {
+    decode(data)
+}
+
+pub fn decode_city(data: &[u8]) -> Result<City> {
+    decode(data)
+}
+
+pub fn decode_chunk(data: &[u8]) -> Result<Chunk> {
+    decode(data)
+}
+
+pub fn decode_score(data: &[u8]) -> Result<CityScore> {
+    decode(data)
+}
+
+pub fn decode_config(data: &[u8]) -> Result<GameConfig> {
+    decode(data)
+}
+
+pub fn decode_coverage(data: &[u8]) -> Result<CoverageOverlay> {
+    decode(data)
+}
+
+/// Large maps are zero-copy accounts, read straight from their bytes
+pub fn decode_large_map(data: &[u8]) -> Result<LargeMap> {
+    let discriminator = LargeMap::DISCRIMINATOR;
+    if !data.starts_with(discriminator) {
+        return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+    }
+    let body = data
+        .get(discriminator.len()..discriminator.len() + std::mem::size_of::<LargeMap>())
+        .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+    Ok(bytemuck::pod_read_unaligned(body))
+}
+
+pub fn decode_profile(data: &[u8]) -> Result<MayorProfile> {
+    decode(data)
+}
+
+pub fn decode_spectator_pass(data: &[u8]) -> Result<SpectatorPass> {
+    decode(data)
+}
@@ -0,0 +1,237 @@
+//! Integration harness for `simcity_build`.
+//!
+//! Loads the program's SBF build into `solana-program-test` twice: once as
+//! the base layer and once as a mocked ephemeral rollup (ER). Anchor's CPIs
+//! only work inside the SBF VM, so the program can't run as a native
+//! processor. Build it first, then run the tests with the `sbf` feature;
+//! without it they are ignored, so a plain `cargo test --workspace` passes
+//! without an SBF toolchain. CI runs them on every push, and its `ci` job,
+//! the one to require before merging, waits on them; see
+//! `.github/workflows/ci.yml`:
+//!
+//! ```sh
+//! cargo build-sbf --manifest-path programs/simcity/Cargo.toml
+//! SBF_OUT_DIR=target/deploy cargo test -p simcity-tests --features sbf
+//! ```
+//!
+//! - On the base layer, a native mock delegation program stands in for the real
+//!   one: it restores the delegated account's data from the delegation
+//!   buffer, leaving the account owned by the delegation program.
+//! - The mock ER is a second bank seeded with the delegated accounts, owned
+//!   by the program again as on a real ER. A mock magic program records
+//!   scheduled commits, and [`MockEphemeralRollup::settle`] copies committed
+//!   state back to the base layer (returning ownership on undelegation).
+
+use std::sync::Mutex;
+
+use anchor_lang::prelude::{AccountInfo, Clock, Pubkey};
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{AccountSerialize, Space};
+use counter::config::{GameConfig, GameParams};
+use counter::randomness::RandomnessMode;
+use ephemeral_rollups_sdk::consts::{MAGIC_CONTEXT_ID, MAGIC_PROGRAM_ID};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::{Account, AccountSharedData};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+
+pub use simcity_client::{instructions, pda, state, PROGRAM_ID};
+
+const FUNDED_LAMPORTS: u64 = 100_000_000_000;
+
+/// File stem of the program's SBF build (`counter.so`)
+pub const PROGRAM_SO_NAME: &str = "counter";
+
+/// Delegate instruction accounts:
+/// [payer, delegated, owner_program, buffer, record, metadata, system_program]
+fn process_mock_delegation(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if data.get(..8) == Some(&[0u8; 8]) {
+        let mut delegated = accounts[1].try_borrow_mut_data()?;
+        let buffer = accounts[3].try_borrow_data()?;
+        delegated.copy_from_slice(&buffer);
+    }
+    Ok(())
+}
+
+/// A commit scheduled through the mock magic program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduledCommit {
+    pub accounts: Vec<Pubkey>,
+    pub undelegate: bool,
+}
+
+// Native processors can't reach the harness directly, so scheduled commits
+// are handed over through this queue. Keep one lifecycle per test binary
+// or run with `--test-threads=1` when adding more.
+static SCHEDULED_COMMITS: Mutex<Vec<ScheduledCommit>> = Mutex::new(Vec::new());
+
+/// Magic program accounts: [payer, magic_context, committed accounts..]
+fn process_mock_magic(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // bincode enum tag: 1 = ScheduleCommit, 2 = ScheduleCommitAndUndelegate
+    let tag = data
+        .get(..4)
+        .map(|tag| u32::from_le_bytes(tag.try_into().unwrap()))
+        .unwrap_or_default();
+    SCHEDULED_COMMITS.lock().unwrap().push(ScheduledCommit {
+        accounts: accounts.iter().skip(2).map(|acc| *acc.key).collect(),
+        undelegate: tag == 2,
+    });
+    Ok(())
+}
+
+/// Serialized global config with default balance parameters
+pub fn config_account(admin: Pubkey) -> Account {
+    let config = GameConfig {
+        admin,
+        params: GameParams::default(),
+        randomness_oracle: Pubkey::default(),
+        randomness_source: RandomnessMode::default(),
+        default_features: counter::features::DEFAULT,
+    };
+    let mut data = Vec::with_capacity(8 + GameConfig::INIT_SPACE);
+    config.try_serialize(&mut data).unwrap();
+    data.resize(8 + GameConfig::INIT_SPACE, 0);
+    Account {
+        lamports: FUNDED_LAMPORTS,
+        data,
+        owner: PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(true);
+    program_test.add_program(PROGRAM_SO_NAME, PROGRAM_ID, None);
+    // The mocks below are native processors
+    program_test.prefer_bpf(false);
+    program_test
+}
+
+/// Base layer bank with the program, the mock delegation program and the
+/// game config. The context payer is the city authority.
+pub async fn start_base_layer() -> ProgramTestContext {
+    let mut program_test = program_test();
+    program_test.add_program(
+        "delegation_program",
+        ephemeral_rollups_sdk::id(),
+        processor!(process_mock_delegation),
+    );
+    program_test.add_account(pda::config(), config_account(Pubkey::default()));
+    program_test.start_with_context().await
+}
+
+/// Sign with `signer` (also the fee payer) and process on `ctx`
+pub async fn send(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await?;
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&signer.pubkey()),
+        &[signer],
+        blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await
+}
+
+pub async fn get_account(ctx: &mut ProgramTestContext, address: Pubkey) -> Option<Account> {
+    ctx.banks_client.get_account(address).await.unwrap()
+}
+
+/// Move the bank's clock to `unix_timestamp`, e.g. to make simulation
+/// steps due
+pub async fn warp_clock(ctx: &mut ProgramTestContext, unix_timestamp: i64) {
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    ctx.set_sysvar(&clock);
+}
+
+/// A second bank standing in for the ephemeral rollup validator
+pub struct MockEphemeralRollup {
+    pub ctx: ProgramTestContext,
+}
+
+impl MockEphemeralRollup {
+    /// Clone `delegated` accounts from the base layer (owned by the program
+    /// again, as on a real ER), along with read-only `cloned` accounts, and
+    /// fund `payers` so they can sign.
+    pub async fn start(
+        base: &mut ProgramTestContext,
+        delegated: &[Pubkey],
+        cloned: &[Pubkey],
+        payers: &[Pubkey],
+    ) -> Self {
+        let mut program_test = program_test();
+        program_test.add_program(
+            "magic_program",
+            MAGIC_PROGRAM_ID,
+            processor!(process_mock_magic),
+        );
+        program_test.add_account(
+            MAGIC_CONTEXT_ID,
+            Account {
+                lamports: FUNDED_LAMPORTS,
+                data: vec![0; 1024],
+                owner: MAGIC_PROGRAM_ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        for address in delegated {
+            let mut account = get_account(base, *address)
+                .await
+                .expect("delegated account missing on base layer");
+            account.owner = PROGRAM_ID;
+            program_test.add_account(*address, account);
+        }
+        for address in cloned {
+            let account = get_account(base, *address)
+                .await
+                .expect("cloned account missing on base layer");
+            program_test.add_account(*address, account);
+        }
+        for payer in payers {
+            program_test.add_account(
+                *payer,
+                Account::new(FUNDED_LAMPORTS, 0, &solana_sdk::system_program::ID),
+            );
+        }
+        SCHEDULED_COMMITS.lock().unwrap().clear();
+
+        Self {
+            ctx: program_test.start_with_context().await,
+        }
+    }
+
+    /// Apply every commit scheduled so far to the base layer and return them
+    pub async fn settle(&mut self, base: &mut ProgramTestContext) -> Vec<ScheduledCommit> {
+        let commits: Vec<_> = SCHEDULED_COMMITS.lock().unwrap().drain(..).collect();
+        for commit in commits.iter() {
+            for address in commit.accounts.iter() {
+                let mut account = get_account(&mut self.ctx, *address)
+                    .await
+                    .expect("committed account missing on the ER");
+                account.owner = if commit.undelegate {
+                    PROGRAM_ID
+                } else {
+                    ephemeral_rollups_sdk::id()
+                };
+                base.set_account(address, &AccountSharedData::from(account));
+            }
+        }
+        commits
+    }
+}
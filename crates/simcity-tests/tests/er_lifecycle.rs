@@ -0,0 +1,197 @@
+use counter::config::GameParams;
+use counter::difficulty::Difficulty;
+use counter::specialization::Specialization;
+use simcity_client::instructions::UpdateOptions;
+use simcity_client::{BuildingType, BulldozeArgs, PlaceBuildingArgs, PROGRAM_ID};
+use simcity_tests::{
+    get_account, instructions, pda, send, start_base_layer, state, warp_clock, MockEphemeralRollup,
+};
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+#[cfg_attr(
+    not(feature = "sbf"),
+    ignore = "needs the SBF build and `--features sbf`, see the simcity-tests crate docs"
+)]
+async fn delegate_play_commit_undelegate() {
+    let mut base = start_base_layer().await;
+    let authority = base.payer.insecure_clone();
+    let me = authority.pubkey();
+    let city = pda::city(&me);
+    let options = UpdateOptions::default();
+    let step_interval = GameParams::default().step_interval_seconds as i64;
+
+    // Base layer: create the city and play a move
+    send(
+        &mut base,
+        &[instructions::initialize_city(
+            &me,
+            false,
+            Difficulty::Normal,
+            Specialization::None,
+        )],
+        &authority,
+    )
+    .await
+    .unwrap();
+    send(
+        &mut base,
+        &[instructions::place_building(
+            &me,
+            &me,
+            options,
+            PlaceBuildingArgs::V1 {
+                x: 0,
+                y: 0,
+                building_type: BuildingType::Road,
+            },
+        )],
+        &authority,
+    )
+    .await
+    .unwrap();
+
+    // Delegate: the base copy is handed to the delegation program
+    send(&mut base, &[instructions::delegate(&me, None)], &authority)
+        .await
+        .unwrap();
+    let delegated = get_account(&mut base, city).await.unwrap();
+    assert_eq!(delegated.owner, ephemeral_rollups_sdk::id());
+    assert_eq!(state::decode_city(&delegated.data).unwrap().money, 9_990);
+
+    // The base layer rejects gameplay while delegated
+    assert!(send(
+        &mut base,
+        &[instructions::step_simulation(&me, &me, options)],
+        &authority,
+    )
+    .await
+    .is_err());
+
+    // Play on the ER
+    let last_updated = state::decode_city(&delegated.data).unwrap().last_updated;
+    let mut er = MockEphemeralRollup::start(&mut base, &[city], &[pda::config()], &[me]).await;
+    send(
+        &mut er.ctx,
+        &[
+            instructions::place_building(
+                &me,
+                &me,
+                options,
+                PlaceBuildingArgs::V1 {
+                    x: 0,
+                    y: 1,
+                    building_type: BuildingType::Residential,
+                },
+            ),
+            // Residents need power and water to move in; both only reach
+            // homes through adjacent tiles, and every building needs a road
+            instructions::place_building(
+                &me,
+                &me,
+                options,
+                PlaceBuildingArgs::V1 {
+                    x: 1,
+                    y: 1,
+                    building_type: BuildingType::Road,
+                },
+            ),
+            // 2x2 footprint at (1, 2)-(2, 3)
+            instructions::place_building(
+                &me,
+                &me,
+                options,
+                PlaceBuildingArgs::V1 {
+                    x: 1,
+                    y: 2,
+                    building_type: BuildingType::PowerPlant,
+                },
+            ),
+            instructions::place_building(
+                &me,
+                &me,
+                options,
+                PlaceBuildingArgs::V1 {
+                    x: 2,
+                    y: 1,
+                    building_type: BuildingType::WaterPump,
+                },
+            ),
+        ],
+        &authority,
+    )
+    .await
+    .unwrap();
+
+    // Two steps later: construction, then the buildings work
+    warp_clock(&mut er.ctx, last_updated + 2 * step_interval).await;
+    send(
+        &mut er.ctx,
+        &[instructions::step_simulation(&me, &me, options)],
+        &authority,
+    )
+    .await
+    .unwrap();
+
+    // Commit: base layer sees the ER state, still delegated
+    send(&mut er.ctx, &[instructions::commit(&me)], &authority)
+        .await
+        .unwrap();
+    let commits = er.settle(&mut base).await;
+    assert_eq!(commits.len(), 1);
+    assert!(!commits[0].undelegate);
+    let committed = get_account(&mut base, city).await.unwrap();
+    assert_eq!(committed.owner, ephemeral_rollups_sdk::id());
+    let committed_city = state::decode_city(&committed.data).unwrap();
+    assert_eq!(committed_city.tiles[1][0], 2);
+    assert_eq!(committed_city.population, 10);
+    assert_eq!(
+        committed_city.last_updated,
+        last_updated + 2 * step_interval
+    );
+
+    // Nothing more is due until another interval passes
+    assert!(send(
+        &mut er.ctx,
+        &[instructions::step_simulation(&me, &me, options)],
+        &authority,
+    )
+    .await
+    .is_err());
+
+    // More play, then undelegate
+    send(
+        &mut er.ctx,
+        &[instructions::bulldoze(
+            &me,
+            &me,
+            options,
+            BulldozeArgs::V1 { x: 0, y: 1 },
+        )],
+        &authority,
+    )
+    .await
+    .unwrap();
+    send(&mut er.ctx, &[instructions::undelegate(&me)], &authority)
+        .await
+        .unwrap();
+    let commits = er.settle(&mut base).await;
+    assert_eq!(commits.len(), 1);
+    assert!(commits[0].undelegate);
+
+    // Back on the base layer with the final ER state
+    let undelegated = get_account(&mut base, city).await.unwrap();
+    assert_eq!(undelegated.owner, PROGRAM_ID);
+    let final_city = state::decode_city(&undelegated.data).unwrap();
+    assert_eq!(final_city.tiles[1][0], 0);
+    assert_eq!(final_city.population, 10);
+
+    warp_clock(&mut base, final_city.last_updated + step_interval).await;
+    send(
+        &mut base,
+        &[instructions::step_simulation(&me, &me, options)],
+        &authority,
+    )
+    .await
+    .unwrap();
+}
@@ -0,0 +1,311 @@
+//! WASM bindings for the web frontend.
+//!
+//! Account decoding and simulation previews run the program's own code, so
+//! what the UI shows matches what the program computes byte for byte.
+//!
+//! Build with `wasm-pack build crates/simcity-wasm --target web`.
+
+use anchor_lang::AccountDeserialize;
+use counter::config::GameConfig;
+use counter::day_night::DayPhase;
+use counter::{noise, parks, power, procgen, simulation, water, City, GRID_SIZE};
+use wasm_bindgen::prelude::*;
+
+fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T, JsError> {
+    T::try_deserialize(&mut &data[..]).map_err(|err| JsError::new(&err.to_string()))
+}
+
+/// Terrain codes, in row-major order, generated from `seed` the way
+/// `initialize_city` does it
+#[wasm_bindgen(js_name = generateTerrain)]
+pub fn generate_terrain(seed: u64) -> Vec<u8> {
+    procgen::generate(seed).iter().flatten().copied().collect()
+}
+
+/// Tile heights, in row-major order, generated from `seed` the way
+/// `initialize_city` does it
+#[wasm_bindgen(js_name = generateElevation)]
+pub fn generate_elevation(seed: u64) -> Vec<u8> {
+    procgen::generate_elevation(seed, &procgen::generate(seed))
+        .iter()
+        .flatten()
+        .copied()
+        .collect()
+}
+
+/// Decoded `City` account
+#[wasm_bindgen]
+pub struct CityView {
+    city: City,
+}
+
+#[wasm_bindgen]
+impl CityView {
+    /// Decode raw `City` account data (discriminator included)
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &[u8]) -> Result<CityView, JsError> {
+        Ok(CityView {
+            city: decode(data)?,
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn population(&self) -> u32 {
+        self.city.population
+    }
+
+    /// Residents beyond the housing capacity
+    #[wasm_bindgen(getter)]
+    pub fn homeless(&self) -> u32 {
+        self.city.homeless
+    }
+
+    /// Jobs offered by connected workplaces at the last step
+    #[wasm_bindgen(getter)]
+    pub fn jobs(&self) -> u32 {
+        self.city.jobs
+    }
+
+    /// Residents of working age at the last step
+    #[wasm_bindgen(getter)]
+    pub fn workforce(&self) -> u32 {
+        self.city.workforce
+    }
+
+    /// Current season: 0 spring, 1 summer, 2 autumn, 3 winter
+    #[wasm_bindgen(getter)]
+    pub fn season(&self) -> u8 {
+        self.city.season as u8
+    }
+
+    /// True at night, see the program's `day_night` module
+    #[wasm_bindgen(getter)]
+    pub fn night(&self) -> bool {
+        self.city.day_phase == DayPhase::Night
+    }
+
+    /// Garbage piled up uncollected
+    #[wasm_bindgen(getter)]
+    pub fn garbage(&self) -> u32 {
+        self.city.garbage
+    }
+
+    /// City-wide crime level, 0-100
+    #[wasm_bindgen(getter)]
+    pub fn crime(&self) -> u8 {
+        self.city.crime
+    }
+
+    /// Citizen health, 0-100
+    #[wasm_bindgen(getter)]
+    pub fn health(&self) -> u8 {
+        self.city.health
+    }
+
+    /// Education level, 0-100; advanced buildings need a minimum
+    #[wasm_bindgen(getter)]
+    pub fn education(&self) -> u8 {
+        self.city.education
+    }
+
+    /// Residential, commercial and industrial demand, each -100 to 100, for
+    /// the demand bars
+    #[wasm_bindgen(getter)]
+    pub fn demand(&self) -> Vec<i8> {
+        let demand = &self.city.demand;
+        vec![demand.residential, demand.commercial, demand.industrial]
+    }
+
+    /// Treasury balance (u64 surfaces as a JS BigInt)
+    #[wasm_bindgen(getter)]
+    pub fn money(&self) -> u64 {
+        self.city.money
+    }
+
+    /// Unpaid bills carried as a negative balance
+    #[wasm_bindgen(getter)]
+    pub fn overdraft(&self) -> u64 {
+        self.city.overdraft
+    }
+
+    /// Construction is blocked until the debt is restructured
+    #[wasm_bindgen(getter)]
+    pub fn bankrupt(&self) -> bool {
+        self.city.bankrupt
+    }
+
+    /// 0-100
+    #[wasm_bindgen(getter)]
+    pub fn happiness(&self) -> u8 {
+        self.city.happiness
+    }
+
+    /// Mayor's approval rating, 0-100
+    #[wasm_bindgen(getter)]
+    pub fn approval(&self) -> u8 {
+        self.city.approval
+    }
+
+    /// Lost the last election and collects less income until approval
+    /// recovers
+    #[wasm_bindgen(getter)]
+    pub fn caretaker(&self) -> bool {
+        self.city.caretaker
+    }
+
+    #[wasm_bindgen(getter, js_name = lastUpdated)]
+    pub fn last_updated(&self) -> i64 {
+        self.city.last_updated
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn authority(&self) -> String {
+        self.city.authority.to_string()
+    }
+
+    #[wasm_bindgen(getter, js_name = gridSize)]
+    pub fn grid_size(&self) -> usize {
+        GRID_SIZE
+    }
+
+    /// Tile codes in row-major order (`tiles[y * gridSize + x]`). Large
+    /// buildings store their code on the top-left tile and 255 on the rest
+    /// of their footprint.
+    #[wasm_bindgen(getter)]
+    pub fn tiles(&self) -> Vec<u8> {
+        self.city.tiles.iter().flatten().copied().collect()
+    }
+
+    /// Terrain codes under the buildings, in row-major order: 0 grass,
+    /// 1 water, 2 forest, 3 mountain
+    #[wasm_bindgen(getter)]
+    pub fn terrain(&self) -> Vec<u8> {
+        self.city.terrain.iter().flatten().copied().collect()
+    }
+
+    /// Tile heights in row-major order, 0 for water; see `generateElevation`
+    #[wasm_bindgen(getter)]
+    pub fn elevation(&self) -> Vec<u8> {
+        self.city.elevation.iter().flatten().copied().collect()
+    }
+
+    /// Seed the terrain was generated from, see `generateTerrain`
+    #[wasm_bindgen(getter, js_name = terrainSeed)]
+    pub fn terrain_seed(&self) -> u64 {
+        self.city.terrain_seed
+    }
+
+    /// Road component ids in row-major order (0 = not connected)
+    #[wasm_bindgen(getter, js_name = roadNetwork)]
+    pub fn road_network(&self) -> Vec<u8> {
+        self.city.road_network.iter().flatten().copied().collect()
+    }
+
+    /// Density codes in row-major order (0 low, 1 medium, 2 high)
+    #[wasm_bindgen(getter)]
+    pub fn density(&self) -> Vec<u8> {
+        self.city.density.iter().flatten().copied().collect()
+    }
+
+    /// Zone codes in row-major order (0 = unzoned)
+    #[wasm_bindgen(getter)]
+    pub fn zoning(&self) -> Vec<u8> {
+        self.city.zoning.iter().flatten().copied().collect()
+    }
+
+    /// Building condition in row-major order (100 = new, 0 = ruins)
+    #[wasm_bindgen(getter)]
+    pub fn condition(&self) -> Vec<u8> {
+        self.city.condition.iter().flatten().copied().collect()
+    }
+
+    /// Steps until each construction site opens in row-major order
+    /// (0 = finished)
+    #[wasm_bindgen(getter)]
+    pub fn construction(&self) -> Vec<u8> {
+        self.city.construction.iter().flatten().copied().collect()
+    }
+
+    /// Steps until each tile burns down in row-major order (0 = not burning)
+    #[wasm_bindgen(getter)]
+    pub fn fires(&self) -> Vec<u8> {
+        self.city.fires.iter().flatten().copied().collect()
+    }
+
+    /// 1 for each tile power reached at the last step, in row-major order
+    #[wasm_bindgen(getter)]
+    pub fn powered(&self) -> Vec<u8> {
+        (0..GRID_SIZE)
+            .flat_map(|y| (0..GRID_SIZE).map(move |x| (y, x)))
+            .map(|(y, x)| power::is_powered(&self.city.powered, x, y) as u8)
+            .collect()
+    }
+
+    /// 1 for each residential or commercial tile water didn't reach at the
+    /// last step, in row-major order
+    #[wasm_bindgen(getter)]
+    pub fn dry(&self) -> Vec<u8> {
+        (0..GRID_SIZE)
+            .flat_map(|y| (0..GRID_SIZE).map(move |x| (y, x)))
+            .map(|(y, x)| water::is_dry(&self.city.dry, x, y) as u8)
+            .collect()
+    }
+
+    /// Trips per road tile at the last step in row-major order, for the
+    /// traffic overlay
+    #[wasm_bindgen(getter)]
+    pub fn traffic(&self) -> Vec<u8> {
+        self.city.traffic.iter().flatten().copied().collect()
+    }
+
+    /// Road tier codes in row-major order (0 street, 1 avenue, 2 highway)
+    #[wasm_bindgen(getter, js_name = roadTiers)]
+    pub fn road_tiers(&self) -> Vec<u8> {
+        self.city.road_tiers.iter().flatten().copied().collect()
+    }
+
+    /// Land value per tile at the last step in row-major order, in percent
+    /// of a baseline plot (0 = not assessed yet), for the land value overlay
+    #[wasm_bindgen(getter, js_name = landValue)]
+    pub fn land_value(&self) -> Vec<u8> {
+        self.city.land_value.iter().flatten().copied().collect()
+    }
+
+    /// Air pollution levels at the last step in row-major order, for the
+    /// pollution overlay
+    #[wasm_bindgen(getter)]
+    pub fn pollution(&self) -> Vec<u8> {
+        self.city.pollution.iter().flatten().copied().collect()
+    }
+
+    /// Park greenery levels in row-major order, for the green space overlay
+    #[wasm_bindgen(getter)]
+    pub fn greenery(&self) -> Vec<u8> {
+        parks::compute_greenery(&self.city.tiles)
+            .iter()
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// Noise levels in row-major order, for the noise map overlay
+    #[wasm_bindgen(getter)]
+    pub fn noise(&self) -> Vec<u8> {
+        noise::compute_noise(&self.city.tiles, &self.city.road_tiers)
+            .iter()
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// Run `steps` simulation ticks on a copy of this city using the given
+    /// raw `GameConfig` account data. Random events are not previewed.
+    pub fn preview(&self, config_data: &[u8], steps: u16, now: i64) -> Result<CityView, JsError> {
+        let config: GameConfig = decode(config_data)?;
+        let mut city = self.city.clone();
+        for _ in 0..steps {
+            simulation::run_step(&mut city, &config.params, now, None, None);
+        }
+        Ok(CityView { city })
+    }
+}
@@ -0,0 +1,82 @@
+//! Player achievements.
+//!
+//! Each player has one `Achievements` account, seeded off their key, that
+//! records what they have accomplished as a bitfield, bit `achievement as
+//! u8` each. Unlike milestones they carry no reward and are never lost:
+//! the account is updated whenever it is passed to an instruction that
+//! changes the city, and the frontend reads it directly.
+
+use anchor_lang::prelude::*;
+
+use crate::density::Density;
+use crate::City;
+
+pub const ACHIEVEMENTS_SEED: &[u8] = b"achievements";
+
+/// Steps a city must run for `Achievement::Survivor`
+pub const SURVIVOR_STEPS: u64 = 100;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Achievement {
+    /// A zone raised to high density
+    FirstSkyscraper,
+    /// A populated city without a single polluted tile
+    ZeroPollution,
+    /// `SURVIVOR_STEPS` simulation steps survived
+    Survivor,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 3] = [
+        Achievement::FirstSkyscraper,
+        Achievement::ZeroPollution,
+        Achievement::Survivor,
+    ];
+
+    pub fn bit(self) -> u32 {
+        1 << self as u8
+    }
+
+    /// Whether `city` qualifies for this right now
+    pub fn earned_by(self, city: &City) -> bool {
+        match self {
+            Achievement::FirstSkyscraper => city
+                .density
+                .iter()
+                .flatten()
+                .any(|&density| density == Density::High as u8),
+            Achievement::ZeroPollution => {
+                city.population > 0 && city.pollution.iter().flatten().all(|&level| level == 0)
+            }
+            Achievement::Survivor => city.step_count >= SURVIVOR_STEPS,
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Achievements {
+    pub authority: Pubkey,
+    /// Bit `achievement as u8` per achievement unlocked
+    pub unlocked: u32,
+}
+
+impl Achievements {
+    pub fn has(&self, achievement: Achievement) -> bool {
+        self.unlocked & achievement.bit() != 0
+    }
+
+    /// Unlock whatever `city` has newly earned and return those bits.
+    /// Sandbox cities earn nothing.
+    pub fn record(&mut self, city: &City) -> u32 {
+        if city.sandbox {
+            return 0;
+        }
+        let earned = Achievement::ALL
+            .iter()
+            .filter(|achievement| !self.has(**achievement) && achievement.earned_by(city))
+            .fold(0, |bits, achievement| bits | achievement.bit());
+        self.unlocked |= earned;
+        earned
+    }
+}
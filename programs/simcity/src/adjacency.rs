@@ -0,0 +1,14 @@
+//! Adjacency effects between neighbouring buildings.
+//!
+//! Shops next to homes get more customers; factories next to homes make
+//! their residents unhappy. Only orthogonal neighbours count.
+
+use crate::buildings::{self, BuildingType};
+use crate::network::{self, Grid};
+
+/// Orthogonal neighbours of (x, y) of type `building_type`
+pub fn count_neighbors(tiles: &Grid, x: usize, y: usize, building_type: BuildingType) -> u32 {
+    network::neighbors(x, y)
+        .filter(|&(nx, ny)| buildings::tile_type(tiles[ny][nx]) == building_type)
+        .count() as u32
+}
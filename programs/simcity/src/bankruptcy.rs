@@ -0,0 +1,38 @@
+//! Bankruptcy.
+//!
+//! Welfare and upkeep the treasury can't cover don't vanish: the shortfall
+//! is carried as `City::overdraft`, the city's negative balance, and income
+//! pays it back first. Once the overdraft passes `bankruptcy_limit` the
+//! city goes bankrupt. A bankrupt city can't build, zone, upgrade or borrow
+//! until the mayor calls `restructure_debt`, which turns the overdraft into
+//! loan principal with a `restructure_penalty_pct` surcharge, at no less
+//! than `restructure_rate_bps` interest. Sandbox cities never go bankrupt.
+
+use crate::config::GameParams;
+use crate::City;
+
+/// Charge `amount` to the treasury, carrying what it can't cover as
+/// overdraft; true when paid in full
+pub fn charge(city: &mut City, amount: u64) -> bool {
+    let paid = city.money.min(amount);
+    city.money -= paid;
+    city.overdraft = city.overdraft.saturating_add(amount - paid);
+    paid == amount
+}
+
+/// Pay back as much of the overdraft as the treasury holds
+pub fn settle(city: &mut City) {
+    let repaid = city.money.min(city.overdraft);
+    city.money -= repaid;
+    city.overdraft -= repaid;
+}
+
+/// Whether `city` should go bankrupt now
+pub fn is_insolvent(city: &City, params: &GameParams) -> bool {
+    !city.sandbox && city.overdraft > params.bankruptcy_limit
+}
+
+/// Loan principal `overdraft` turns into when restructured
+pub fn restructured_principal(overdraft: u64, params: &GameParams) -> u64 {
+    overdraft.saturating_mul(100 + params.restructure_penalty_pct as u64) / 100
+}
@@ -0,0 +1,186 @@
+//! Department budgets.
+//!
+//! The mayor sets a funding level per department, in percent of nominal.
+//! A department's buildings cost their upkeep scaled by that level, and the
+//! level scales how well the department works:
+//!
+//! - utilities (including roads, transit and garbage): income of commercial and
+//!   industrial tiles (up to 100%)
+//! - parks: the happiness parks, stadiums and the monument bring (up to
+//!   100%)
+//!
+//! Services are funded one by one instead: `ServiceFunding` sets a level
+//! for police, fire, health and education, scaling the upkeep and the reach
+//! of that service's buildings. Police and fire stations still report as
+//! safety spending, but `Budget` has no level of its own for them, so each
+//! service is scaled exactly once.
+
+use anchor_lang::prelude::*;
+
+use crate::buildings::BuildingType;
+use crate::config::GameParams;
+use crate::coverage::MAX_SERVICE_RADIUS;
+use crate::CityError;
+
+/// Highest funding level a department can be set to, in percent
+pub const MAX_FUNDING_PCT: u8 = 150;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Department {
+    Safety,
+    Utilities,
+    Parks,
+}
+
+impl Department {
+    /// Department paying for a building, if any
+    pub fn of(building_type: BuildingType) -> Option<Department> {
+        match building_type {
+            BuildingType::PoliceStation | BuildingType::FireStation => Some(Department::Safety),
+            BuildingType::Road
+            | BuildingType::PowerPlant
+            | BuildingType::PowerLine
+            | BuildingType::Highway
+            | BuildingType::Bridge
+            | BuildingType::WaterPump
+            | BuildingType::SolarPlant
+            | BuildingType::WaterTower
+            | BuildingType::BusStop
+            | BuildingType::TrainStation
+            | BuildingType::Landfill
+            | BuildingType::RecyclingCenter
+            | BuildingType::Port => Some(Department::Utilities),
+            BuildingType::Stadium
+            | BuildingType::Monument
+            | BuildingType::Park
+            | BuildingType::LargePark
+            | BuildingType::Marina
+            | BuildingType::Beach => Some(Department::Parks),
+            _ => None,
+        }
+    }
+}
+
+/// Funding level per department, in percent of nominal. Safety has none;
+/// police and fire are funded through `ServiceFunding`.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Budget {
+    pub utilities_pct: u8,
+    pub parks_pct: u8,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self {
+            utilities_pct: 100,
+            parks_pct: 100,
+        }
+    }
+}
+
+impl Budget {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.utilities_pct <= MAX_FUNDING_PCT && self.parks_pct <= MAX_FUNDING_PCT,
+            CityError::InvalidBudget
+        );
+        Ok(())
+    }
+
+    /// Funding level of a department; safety is funded per service through
+    /// `ServiceFunding`, so it stands at 100 here
+    pub fn funding_pct(&self, department: Department) -> u8 {
+        match department {
+            Department::Safety => 100,
+            Department::Utilities => self.utilities_pct,
+            Department::Parks => self.parks_pct,
+        }
+    }
+
+    /// Upkeep of one building after its department's funding level
+    pub fn upkeep(&self, building_type: BuildingType) -> u64 {
+        let upkeep = building_type.upkeep();
+        match Department::of(building_type) {
+            Some(department) => upkeep * self.funding_pct(department) as u64 / 100,
+            None => upkeep,
+        }
+    }
+}
+
+/// Funding level per service, in percent of nominal
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ServiceFunding {
+    pub police_pct: u8,
+    pub fire_pct: u8,
+    pub health_pct: u8,
+    pub education_pct: u8,
+}
+
+impl Default for ServiceFunding {
+    fn default() -> Self {
+        Self {
+            police_pct: 100,
+            fire_pct: 100,
+            health_pct: 100,
+            education_pct: 100,
+        }
+    }
+}
+
+impl ServiceFunding {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.police_pct <= MAX_FUNDING_PCT
+                && self.fire_pct <= MAX_FUNDING_PCT
+                && self.health_pct <= MAX_FUNDING_PCT
+                && self.education_pct <= MAX_FUNDING_PCT,
+            CityError::InvalidBudget
+        );
+        Ok(())
+    }
+
+    /// Funding level of the service a building provides; 100 for buildings
+    /// that provide none
+    pub fn funding_pct(&self, building_type: BuildingType) -> u8 {
+        match building_type {
+            BuildingType::PoliceStation => self.police_pct,
+            BuildingType::FireStation => self.fire_pct,
+            BuildingType::Hospital => self.health_pct,
+            BuildingType::School | BuildingType::University => self.education_pct,
+            _ => 100,
+        }
+    }
+
+    /// `params` with each service's reach scaled by its funding
+    pub fn apply(&self, params: &GameParams) -> GameParams {
+        let scale = |radius: u8, pct: u8| {
+            (radius as u16 * pct as u16 / 100).min(MAX_SERVICE_RADIUS as u16) as u8
+        };
+        GameParams {
+            police_radius: scale(params.police_radius, self.police_pct),
+            fire_radius: scale(params.fire_radius, self.fire_pct),
+            health_radius: scale(params.health_radius, self.health_pct),
+            education_radius: scale(params.education_radius, self.education_pct),
+            ..*params
+        }
+    }
+}
+
+/// Return data of `collect_budget_report`: what the next step would collect
+/// and spend
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BudgetReport {
+    pub residential_tax: u64,
+    pub commercial_income: u64,
+    pub industrial_income: u64,
+    pub welfare: u64,
+    pub safety_spending: u64,
+    pub utilities_spending: u64,
+    pub parks_spending: u64,
+    pub other_upkeep: u64,
+    pub loan_interest: u64,
+    pub insurance_premium: u64,
+    pub ordinances: u64,
+    /// Income minus spending
+    pub net: i64,
+}
@@ -0,0 +1,307 @@
+//! Building types, their purchase prices and upkeep.
+//!
+//! Tiles are stored as raw `u8` codes; `BuildingType` is the typed view used
+//! in instruction arguments and simulation code. The variant order is the
+//! tile code, so the Borsh encoding of a `BuildingType` argument is the same
+//! single byte older clients already send.
+//!
+//! Large buildings cover a square footprint. The top-left tile is the
+//! building's anchor and holds its code; the rest hold `FOOTPRINT_TILE`, so
+//! simulation code that scans tiles counts each building once.
+
+use anchor_lang::prelude::*;
+
+use crate::CityError;
+
+/// Code of the non-anchor tiles of a multi-tile building
+pub const FOOTPRINT_TILE: u8 = u8::MAX;
+
+/// Side of the largest footprint, in tiles
+pub const MAX_FOOTPRINT: u8 = 3;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildingType {
+    Empty,
+    Road,
+    Residential,
+    Commercial,
+    Industrial,
+    PowerPlant,
+    PowerLine,
+    PoliceStation,
+    FireStation,
+    Hospital,
+    School,
+    Airport,
+    /// Placed as a road at `RoadTier::Highway`, see `roads`
+    Highway,
+    WaterPump,
+    SolarPlant,
+    WaterTower,
+    BusStop,
+    TrainStation,
+    Stadium,
+    Ruins,
+    CityHall,
+    Monument,
+    Park,
+    LargePark,
+    Landfill,
+    RecyclingCenter,
+    University,
+    /// Road over water, see `terrain`
+    Bridge,
+    /// Coastal buildings, see `coastal`
+    Port,
+    Marina,
+    Beach,
+}
+
+impl BuildingType {
+    /// Base price before demand drift, or `None` for `Empty` and `Ruins`,
+    /// which can't be placed (that's what bulldozing and decay are for)
+    pub fn base_cost(self) -> Option<u64> {
+        let cost = match self {
+            BuildingType::Empty | BuildingType::Ruins => return None,
+            BuildingType::Road => 10,
+            BuildingType::Residential => 100,
+            BuildingType::Commercial => 150,
+            BuildingType::Industrial => 200,
+            BuildingType::PowerPlant => 500,
+            BuildingType::PowerLine => 5,
+            BuildingType::PoliceStation | BuildingType::FireStation => 400,
+            BuildingType::Hospital | BuildingType::School => 600,
+            BuildingType::Airport => 2000,
+            BuildingType::Highway => 50,
+            BuildingType::WaterPump => 300,
+            BuildingType::SolarPlant => 800,
+            BuildingType::WaterTower => 150,
+            BuildingType::BusStop => 100,
+            BuildingType::TrainStation => 1000,
+            BuildingType::Stadium => 3000,
+            BuildingType::CityHall => 5000,
+            BuildingType::Monument => 2500,
+            BuildingType::Park => 50,
+            BuildingType::LargePark => 250,
+            BuildingType::Landfill => 300,
+            BuildingType::RecyclingCenter => 700,
+            BuildingType::University => 2000,
+            BuildingType::Bridge => 200,
+            BuildingType::Port => 1500,
+            BuildingType::Marina => 600,
+            BuildingType::Beach => 100,
+        };
+        Some(cost)
+    }
+
+    /// Nominal maintenance charged every simulation step, before department
+    /// funding levels, see `budget`
+    pub fn upkeep(self) -> u64 {
+        match self {
+            BuildingType::Empty | BuildingType::Ruins => 0,
+            BuildingType::Road
+            | BuildingType::Residential
+            | BuildingType::Commercial
+            | BuildingType::Industrial
+            | BuildingType::PowerLine => 1,
+            BuildingType::BusStop | BuildingType::Park | BuildingType::Beach => 2,
+            BuildingType::Marina => 8,
+            BuildingType::Port => 20,
+            BuildingType::LargePark | BuildingType::Landfill => 5,
+            BuildingType::SolarPlant => 5,
+            BuildingType::Highway | BuildingType::WaterTower | BuildingType::Bridge => 3,
+            BuildingType::PoliceStation | BuildingType::FireStation | BuildingType::WaterPump => 10,
+            BuildingType::Hospital | BuildingType::School => 15,
+            BuildingType::PowerPlant | BuildingType::TrainStation => 20,
+            BuildingType::RecyclingCenter => 15,
+            BuildingType::Airport => 50,
+            BuildingType::University => 25,
+            BuildingType::Stadium => 40,
+            BuildingType::CityHall => 30,
+            BuildingType::Monument => 10,
+        }
+    }
+
+    /// Roads, highways, bridges and power lines: they need no road access
+    /// and are finished the moment they are placed
+    pub fn is_infrastructure(self) -> bool {
+        matches!(
+            self,
+            BuildingType::Road
+                | BuildingType::Highway
+                | BuildingType::PowerLine
+                | BuildingType::Bridge
+        )
+    }
+
+    /// Whether the building must be placed next to a road
+    pub fn needs_road_access(self) -> bool {
+        !self.is_infrastructure()
+    }
+
+    /// Side of the square footprint, in tiles, anchored at the top-left
+    pub fn footprint(self) -> u8 {
+        match self {
+            BuildingType::Airport | BuildingType::Stadium => 3,
+            BuildingType::PowerPlant
+            | BuildingType::CityHall
+            | BuildingType::LargePark
+            | BuildingType::Landfill
+            | BuildingType::University
+            | BuildingType::Port => 2,
+            _ => 1,
+        }
+    }
+
+    /// Landmarks can only be built once per city
+    pub fn is_landmark(self) -> bool {
+        matches!(self, BuildingType::CityHall | BuildingType::Monument)
+    }
+
+    /// Education level, 0-100, the city needs before it can build this;
+    /// see `City::education`
+    pub fn required_education(self) -> u8 {
+        match self {
+            BuildingType::RecyclingCenter | BuildingType::TrainStation => 20,
+            BuildingType::SolarPlant | BuildingType::University => 40,
+            BuildingType::Airport => 60,
+            _ => 0,
+        }
+    }
+
+    /// Residential, commercial and industrial zones
+    pub fn is_zone(self) -> bool {
+        matches!(
+            self,
+            BuildingType::Residential | BuildingType::Commercial | BuildingType::Industrial
+        )
+    }
+}
+
+impl TryFrom<u8> for BuildingType {
+    type Error = Error;
+
+    fn try_from(code: u8) -> Result<Self> {
+        let building_type = match code {
+            0 => BuildingType::Empty,
+            1 => BuildingType::Road,
+            2 => BuildingType::Residential,
+            3 => BuildingType::Commercial,
+            4 => BuildingType::Industrial,
+            5 => BuildingType::PowerPlant,
+            6 => BuildingType::PowerLine,
+            7 => BuildingType::PoliceStation,
+            8 => BuildingType::FireStation,
+            9 => BuildingType::Hospital,
+            10 => BuildingType::School,
+            11 => BuildingType::Airport,
+            12 => BuildingType::Highway,
+            13 => BuildingType::WaterPump,
+            14 => BuildingType::SolarPlant,
+            15 => BuildingType::WaterTower,
+            16 => BuildingType::BusStop,
+            17 => BuildingType::TrainStation,
+            18 => BuildingType::Stadium,
+            19 => BuildingType::Ruins,
+            20 => BuildingType::CityHall,
+            21 => BuildingType::Monument,
+            22 => BuildingType::Park,
+            23 => BuildingType::LargePark,
+            24 => BuildingType::Landfill,
+            25 => BuildingType::RecyclingCenter,
+            26 => BuildingType::University,
+            27 => BuildingType::Bridge,
+            28 => BuildingType::Port,
+            29 => BuildingType::Marina,
+            30 => BuildingType::Beach,
+            _ => return err!(CityError::InvalidBuildingType),
+        };
+        Ok(building_type)
+    }
+}
+
+impl From<BuildingType> for u8 {
+    fn from(building_type: BuildingType) -> u8 {
+        building_type as u8
+    }
+}
+
+/// Tiles of a `size` x `size` footprint anchored at (x, y)
+pub fn footprint_tiles(x: usize, y: usize, size: usize) -> impl Iterator<Item = (usize, usize)> {
+    (y..y + size).flat_map(move |ty| (x..x + size).map(move |tx| (tx, ty)))
+}
+
+/// Anchor tile of the building covering (x, y) of a square grid, if any
+pub fn anchor_of<const N: usize>(
+    tiles: &[[u8; N]; N],
+    x: usize,
+    y: usize,
+) -> Option<(usize, usize)> {
+    match tiles[y][x] {
+        0 => None,
+        FOOTPRINT_TILE => {
+            let reach = MAX_FOOTPRINT as usize - 1;
+            (y.saturating_sub(reach)..=y)
+                .flat_map(|ay| (x.saturating_sub(reach)..=x).map(move |ax| (ax, ay)))
+                .find(|&(ax, ay)| {
+                    let size = tile_type(tiles[ay][ax]).footprint() as usize;
+                    tiles[ay][ax] != FOOTPRINT_TILE && ax + size > x && ay + size > y
+                })
+        }
+        _ => Some((x, y)),
+    }
+}
+
+/// Typed view of a stored tile; codes no version of the program writes
+/// read as `Empty`
+pub fn tile_type(tile: u8) -> BuildingType {
+    BuildingType::try_from(tile).unwrap_or(BuildingType::Empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::Grid;
+    use crate::GRID_SIZE;
+
+    fn build(tiles: &mut Grid, x: usize, y: usize, building_type: BuildingType) {
+        let size = building_type.footprint() as usize;
+        for (tx, ty) in footprint_tiles(x, y, size) {
+            tiles[ty][tx] = FOOTPRINT_TILE;
+        }
+        tiles[y][x] = building_type.into();
+    }
+
+    #[test]
+    fn footprints_on_the_far_edge_stay_in_bounds() {
+        let edge = GRID_SIZE - 3;
+        let covered: Vec<_> = footprint_tiles(edge, edge, 3).collect();
+        assert_eq!(covered.len(), 9);
+        assert_eq!(covered[0], (edge, edge));
+        assert_eq!(covered[8], (GRID_SIZE - 1, GRID_SIZE - 1));
+        assert!(covered.iter().all(|&(x, y)| x < GRID_SIZE && y < GRID_SIZE));
+    }
+
+    #[test]
+    fn every_footprint_tile_leads_back_to_its_anchor() {
+        let mut tiles = [[0u8; GRID_SIZE]; GRID_SIZE];
+        let edge = GRID_SIZE - 3;
+        build(&mut tiles, 0, 0, BuildingType::PowerPlant);
+        build(&mut tiles, 2, 0, BuildingType::Stadium);
+        build(&mut tiles, edge, edge, BuildingType::Airport);
+        tiles[2][0] = BuildingType::Residential.into();
+
+        for (x, y) in footprint_tiles(0, 0, 2) {
+            assert_eq!(anchor_of(&tiles, x, y), Some((0, 0)));
+        }
+        for (x, y) in footprint_tiles(2, 0, 3) {
+            assert_eq!(anchor_of(&tiles, x, y), Some((2, 0)));
+        }
+        for (x, y) in footprint_tiles(edge, edge, 3) {
+            assert_eq!(anchor_of(&tiles, x, y), Some((edge, edge)));
+        }
+        assert_eq!(anchor_of(&tiles, 0, 2), Some((0, 2)));
+        assert_eq!(anchor_of(&tiles, 1, 2), None);
+        assert_eq!(anchor_of(&tiles, edge - 1, GRID_SIZE - 1), None);
+    }
+}
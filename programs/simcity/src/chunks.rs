@@ -0,0 +1,209 @@
+//! Chunked world.
+//!
+//! Beyond its own grid, a city can own chunks: `GRID_SIZE` square patches of
+//! land, each its own PDA seeded by the city and the chunk's coordinates.
+//! The city's own grid is chunk (0, 0), and chunk (x, y) lies x grids east
+//! and y grids south of it, so the map grows in every direction without any
+//! one account growing with it. Each chunk is delegated to, and committed
+//! from, the ER on its own, so a client only moves the chunks it is
+//! editing. Building on a chunk works as on the `large_map`: the same tile
+//! encoding and placement routine, paid from the city's treasury.
+//!
+//! Chunks are bought with `expand_map`, each bordering land the city already
+//! owns. The first costs `chunk_price`, and every one after it
+//! `chunk_price_growth_pct` more than the last.
+//!
+//! Chunks are land to build out, not simulated districts: `run_step` only
+//! reads the city's own grid, so buildings on a chunk add no residents,
+//! jobs or income. Prices drift with the buildings of the same type on the
+//! chunk, as they do on the city grid.
+
+use anchor_lang::prelude::*;
+
+use crate::buildings::BuildingType;
+use crate::config::GameParams;
+use crate::large_map;
+use crate::{City, CityError, GRID_SIZE};
+
+pub const CHUNK_SEED: &[u8] = b"chunk";
+
+/// Price of the next chunk for a city that bought `owned` already
+pub fn expansion_price(owned: u16, params: &GameParams) -> u64 {
+    (0..owned).fold(params.chunk_price, |price, _| {
+        price.saturating_mul(100 + params.chunk_price_growth_pct as u64) / 100
+    })
+}
+
+/// Whether chunks `a` and `b` share an edge
+pub fn borders(a: (i8, i8), b: (i8, i8)) -> bool {
+    (a.0 as i16 - b.0 as i16).abs() + (a.1 as i16 - b.1 as i16).abs() == 1
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Chunk {
+    pub city: Pubkey,
+    /// Position in grids east and south of the city's own grid
+    pub x: i8,
+    pub y: i8,
+    /// Tile codes, encoded like `City::tiles`
+    pub tiles: [[u8; GRID_SIZE]; GRID_SIZE],
+    /// Bumped on every tile change
+    pub layout_version: u32,
+    /// Price paid for each building, in percent of its base cost, on its
+    /// anchor tile, see `large_map::place_on`
+    pub paid_pct: [[u8; GRID_SIZE]; GRID_SIZE],
+}
+
+impl Chunk {
+    /// Tile code at (x, y) within the chunk
+    pub fn tile(&self, x: u8, y: u8) -> Result<u8> {
+        require!(
+            (x as usize) < GRID_SIZE && (y as usize) < GRID_SIZE,
+            CityError::OutOfBounds
+        );
+        Ok(self.tiles[y as usize][x as usize])
+    }
+
+    /// Place a building with its anchor at (x, y) within the chunk, paid
+    /// from `city`'s treasury. Footprints can't straddle chunks.
+    pub fn place(
+        &mut self,
+        city: &mut City,
+        x: u8,
+        y: u8,
+        building_type: BuildingType,
+        params: &GameParams,
+    ) -> Result<()> {
+        let code = u8::from(building_type);
+        let existing = self
+            .tiles
+            .iter()
+            .flatten()
+            .filter(|&&tile| tile == code)
+            .count() as u64;
+        let cost = large_map::check_placement(city, building_type, existing, params)?;
+        large_map::place_on(
+            &mut self.tiles,
+            &mut self.paid_pct,
+            city,
+            x as usize,
+            y as usize,
+            building_type,
+            cost,
+        )?;
+        self.layout_version = self.layout_version.wrapping_add(1);
+
+        msg!(
+            "Placed {:?} at ({}, {}) of chunk ({}, {})",
+            building_type,
+            x,
+            y,
+            self.x,
+            self.y
+        );
+        Ok(())
+    }
+
+    /// Clear the building covering (x, y) within the chunk, refunding part
+    /// of its price to `city`
+    pub fn clear(&mut self, city: &mut City, x: u8, y: u8, params: &GameParams) -> Result<()> {
+        self.tile(x, y)?;
+        let (_, refund) = large_map::clear_on(
+            &mut self.tiles,
+            &mut self.paid_pct,
+            city,
+            x as usize,
+            y as usize,
+            params,
+        )?;
+        self.layout_version = self.layout_version.wrapping_add(1);
+
+        msg!(
+            "Bulldozed ({}, {}) of chunk ({}, {}), refunded {}",
+            x,
+            y,
+            self.x,
+            self.y,
+            refund
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk_at(x: i8, y: i8) -> Chunk {
+        Chunk {
+            city: Pubkey::default(),
+            x,
+            y,
+            tiles: [[0; GRID_SIZE]; GRID_SIZE],
+            layout_version: 0,
+            paid_pct: [[0; GRID_SIZE]; GRID_SIZE],
+        }
+    }
+
+    #[test]
+    fn only_edge_neighbours_border() {
+        assert!(borders((0, 0), (1, 0)));
+        assert!(borders((0, 0), (0, -1)));
+        assert!(borders((-3, 2), (-3, 3)));
+        assert!(!borders((0, 0), (1, 1)));
+        assert!(!borders((0, 0), (2, 0)));
+        assert!(!borders((0, 0), (0, 0)));
+        assert!(!borders((i8::MIN, 0), (i8::MAX, 0)));
+    }
+
+    #[test]
+    fn each_chunk_costs_more_than_the_last() {
+        let params = GameParams::default();
+        let growth = 100 + params.chunk_price_growth_pct as u64;
+        assert_eq!(expansion_price(0, &params), params.chunk_price);
+        assert_eq!(
+            expansion_price(1, &params),
+            params.chunk_price * growth / 100
+        );
+        assert_eq!(
+            expansion_price(2, &params),
+            params.chunk_price * growth / 100 * growth / 100
+        );
+        for owned in 0..20 {
+            assert!(expansion_price(owned + 1, &params) > expansion_price(owned, &params));
+        }
+        // Prices saturate rather than wrap for cities that own a lot
+        assert_eq!(expansion_price(u16::MAX, &params), u64::MAX / 100);
+    }
+
+    #[test]
+    fn chunks_build_from_the_city_treasury_within_their_own_bounds() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(100_000);
+        let mut chunk = chunk_at(1, 0);
+
+        chunk
+            .place(&mut city, 0, 0, BuildingType::Park, &params)
+            .unwrap();
+        assert!(city.money < 100_000);
+        assert_eq!(chunk.tile(0, 0).unwrap(), u8::from(BuildingType::Park));
+        assert_eq!(chunk.layout_version, 1);
+
+        // Footprints can't straddle into the next chunk
+        let edge = GRID_SIZE as u8 - 1;
+        assert!(chunk
+            .place(&mut city, edge, 0, BuildingType::PowerPlant, &params)
+            .is_err());
+        chunk
+            .place(&mut city, edge - 1, 0, BuildingType::PowerPlant, &params)
+            .unwrap();
+        assert!(chunk.tile(edge + 1, 0).is_err());
+
+        let money = city.money;
+        chunk.clear(&mut city, 0, 0, &params).unwrap();
+        assert!(city.money > money);
+        assert_eq!(chunk.tile(0, 0).unwrap(), 0);
+        assert_eq!(chunk.layout_version, 3);
+    }
+}
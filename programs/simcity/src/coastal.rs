@@ -0,0 +1,33 @@
+//! Coastal buildings.
+//!
+//! Ports, marinas and beaches need the water: at least one tile of their
+//! footprint must border water terrain. Ports ship goods in for commerce,
+//! and marinas and beaches give off greenery the way parks do.
+
+use crate::buildings::{footprint_tiles, BuildingType};
+use crate::network;
+use crate::terrain::{self, Terrain};
+use crate::GRID_SIZE;
+
+impl BuildingType {
+    /// Whether the building must border water
+    pub fn is_coastal(self) -> bool {
+        matches!(
+            self,
+            BuildingType::Port | BuildingType::Marina | BuildingType::Beach
+        )
+    }
+}
+
+/// Whether a `size` x `size` footprint at (x, y) borders water terrain
+pub fn borders_water(
+    terrain: &[[u8; GRID_SIZE]; GRID_SIZE],
+    x: usize,
+    y: usize,
+    size: usize,
+) -> bool {
+    footprint_tiles(x, y, size).any(|(tx, ty)| {
+        network::neighbors(tx, ty)
+            .any(|(nx, ny)| terrain::terrain_of(terrain[ny][nx]) == Terrain::Water)
+    })
+}
@@ -0,0 +1,315 @@
+//! Global, admin-tunable game balance parameters.
+//!
+//! Formula coefficients live here rather than as literals in the simulation,
+//! so balancing patches are `update_config` calls instead of deploys.
+
+use anchor_lang::prelude::*;
+
+use crate::coverage::MAX_SERVICE_RADIUS;
+use crate::randomness::RandomnessMode;
+use crate::CityError;
+
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Cap on the commercial income bonus of a fully educated city, in percent
+pub const MAX_EDUCATION_INCOME_BONUS_PCT: u8 = 100;
+
+#[account]
+#[derive(InitSpace)]
+pub struct GameConfig {
+    pub admin: Pubkey,
+    pub params: GameParams,
+    /// Signer the randomness oracle answers requests with; the default key
+    /// means no oracle is set up, see `randomness`
+    pub randomness_oracle: Pubkey,
+    /// Source every city's steps draw from, see `randomness`
+    pub randomness_source: RandomnessMode,
+    /// Feature flags newly initialized cities start with, see `features`
+    pub default_features: u32,
+}
+
+/// Tunable coefficients used by the simulation
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameParams {
+    /// Seconds of game time per simulation step; `step_simulation` runs one
+    /// step per interval elapsed
+    pub step_interval_seconds: u32,
+    /// Steps per season, see `season`; 0 keeps it spring forever
+    pub season_length_steps: u32,
+    /// Seconds from one dawn to the next, see `day_night`; 0 is endless day
+    pub day_length_seconds: u32,
+    /// Share of vacant housing, in percent, filled each step by homes of
+    /// ordinary appeal
+    pub growth_rate_pct: u8,
+    /// Growth lost by a residential tile, in percent, per level of noise
+    pub noise_growth_penalty_pct: u8,
+    /// Growth gained by a residential tile, in percent, per level of park
+    /// greenery
+    pub park_growth_bonus_pct: u8,
+    /// Growth lost by a residential tile, in percent, per level of
+    /// pollution
+    pub pollution_growth_penalty_pct: u8,
+    /// Happiness lost per level of pollution averaged over residential
+    /// tiles
+    pub pollution_happiness_penalty: u8,
+    /// Garbage thrown out per 100 residents each step
+    pub garbage_per_100_residents: u32,
+    /// Health lost while garbage is left uncollected
+    pub garbage_health_penalty: u8,
+    /// Health lost per level of pollution averaged over residential tiles
+    pub pollution_health_penalty: u8,
+    /// Happiness lost while garbage is left uncollected
+    pub garbage_happiness_penalty: u8,
+    /// Happiness lost per unit of per-capita residential tax
+    pub tax_happiness_penalty: u8,
+    /// Happiness lost, in percent of the crime level
+    pub crime_happiness_penalty_pct: u8,
+    /// Happiness below which residents start moving away
+    pub unrest_happiness: u8,
+    /// Share of residents, in percent, moving away each step of unrest
+    pub unrest_decline_pct: u8,
+    /// Residents housed by each low-density residential tile before
+    /// services and shortages
+    pub housing_per_residential: u32,
+    /// Share of residents, in percent, who work
+    pub workforce_pct: u8,
+    /// Growth lost, in percent, with the whole workforce unemployed;
+    /// scales linearly with the unemployment rate
+    pub unemployment_growth_penalty_pct: u8,
+    /// Jobs offered by each road-connected commercial or industrial tile
+    pub jobs_per_workplace: u32,
+    /// Treasury cost per unemployed resident each step
+    pub welfare_per_unemployed: u64,
+    /// Tax collected per resident each step
+    pub residential_tax_per_capita: u64,
+    /// Base treasury income per worker employed by a commercial tile
+    pub commercial_income_per_worker: u64,
+    /// Treasury income per worker employed by an industrial tile
+    pub industrial_income_per_worker: u64,
+    /// Extra commercial income, in percent, when every residential tile is
+    /// in school reach; scales linearly with the education level
+    pub education_income_bonus_pct: u8,
+    /// Extra income, in percent, of a commercial tile bordering at least
+    /// one residential tile
+    pub commercial_near_homes_bonus_pct: u8,
+    /// Happiness lost for every industrial tile bordering a residential tile
+    pub industry_near_homes_penalty: u8,
+    /// Commercial income lost, in percent, by a shop whose road is fully
+    /// congested; scales linearly with congestion
+    pub traffic_income_penalty_pct: u8,
+    /// Chance per step, in percent, of a crime on each residential or
+    /// commercial tile outside police reach
+    pub crime_chance_pct: u8,
+    /// Money taken from the treasury by each crime
+    pub crime_theft: u64,
+    /// Residents per point of crime level, before poverty and policing
+    pub residents_per_crime_point: u32,
+    /// Commercial income lost, in percent, at the highest crime level;
+    /// scales linearly with crime
+    pub crime_income_penalty_pct: u8,
+    /// Approval lost per unit of per-capita residential tax, see
+    /// `elections`
+    pub tax_approval_penalty: u8,
+    /// Steps between elections; 0 holds none
+    pub election_interval_steps: u32,
+    /// Approval a mayor needs to win an election and to end caretaker mode
+    pub approval_threshold: u8,
+    /// Income lost in caretaker mode, in percent
+    pub caretaker_income_penalty_pct: u8,
+    /// Chance per step, in percent, of a fire breaking out somewhere
+    /// outside fire-station reach
+    pub fire_chance_pct: u8,
+    /// Steps a building burns before it is destroyed, at least 1
+    pub fire_burn_steps: u8,
+    /// Chance per step, in percent, of a disaster striking a city with
+    /// `features::DISASTERS`, see `disaster`
+    pub disaster_chance_pct: u8,
+    /// Chance per step, in percent, of low land by the water flooding in a
+    /// city with `features::DISASTERS`, see `elevation`
+    pub flood_chance_pct: u8,
+    /// Price per step of disaster insurance
+    pub insurance_premium: u64,
+    /// Share of the rebuild cost of disaster damage, in percent, paid out
+    /// to insured cities
+    pub insurance_payout_pct: u8,
+    /// Offset into the daily quest rotation, see `quests`; changing it
+    /// reshuffles which quest falls on which day
+    pub quest_seed: u32,
+    /// Price of `dispatch_emergency`
+    pub dispatch_cost: u64,
+    /// Placement price increase, in percent, per existing building of the
+    /// same type in the city
+    pub price_drift_pct: u16,
+    /// Price of zoning one tile
+    pub zoning_fee: u64,
+    /// Price of clearing one forest tile to build on it, and of reclaiming
+    /// one water tile as land, see `terrain`
+    pub forest_clearing_cost: u64,
+    pub reclamation_cost: u64,
+    /// Placement surcharge per level of slope past the gentle, see
+    /// `elevation`
+    pub slope_cost_per_level: u64,
+    /// Price per level a tile is raised or lowered with `terraform`
+    pub terraform_cost_per_level: u64,
+    /// Steps a newly placed building spends under construction before it
+    /// starts working; infrastructure is built at once
+    pub construction_steps: u8,
+    /// Condition, out of 100, every working building loses each step
+    pub decay_per_step: u8,
+    /// Price of a full repair from zero condition, in percent of the
+    /// building's base cost; partial repairs cost proportionally less
+    pub repair_cost_pct: u8,
+    /// Share of the price paid for a building returned when it is bulldozed
+    pub refund_pct: u8,
+    /// Interest per step on new loans, in basis points of the principal
+    pub loan_rate_bps: u16,
+    /// Most a city may owe
+    pub debt_ceiling: u64,
+    /// Consecutive unpaid interest charges before a city defaults
+    pub max_missed_payments: u8,
+    /// Overdraft past which a city goes bankrupt, see `bankruptcy`
+    pub bankruptcy_limit: u64,
+    /// Surcharge on the overdraft when debt is restructured, in percent
+    pub restructure_penalty_pct: u8,
+    /// Lowest interest per step on restructured debt, in basis points
+    pub restructure_rate_bps: u16,
+    /// Price of the first chunk bought with `expand_map`, and how much
+    /// dearer each next one gets, in percent; see `chunks`
+    pub chunk_price: u64,
+    pub chunk_price_growth_pct: u16,
+    /// Steps between reseeds from the randomness oracle, when one is set
+    /// up, and how long a city waits for an answer before stepping on
+    /// without it; see `randomness`. 0 steps never reseeds.
+    pub reseed_interval_steps: u32,
+    pub randomness_timeout_seconds: u32,
+    /// Manhattan reach of each service building, in tiles
+    pub police_radius: u8,
+    pub fire_radius: u8,
+    pub health_radius: u8,
+    pub education_radius: u8,
+}
+
+impl GameParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.police_radius <= MAX_SERVICE_RADIUS
+                && self.fire_radius <= MAX_SERVICE_RADIUS
+                && self.health_radius <= MAX_SERVICE_RADIUS
+                && self.education_radius <= MAX_SERVICE_RADIUS,
+            CityError::InvalidConfig
+        );
+        require!(
+            self.education_income_bonus_pct <= MAX_EDUCATION_INCOME_BONUS_PCT,
+            CityError::InvalidConfig
+        );
+        require!(
+            self.traffic_income_penalty_pct <= 100,
+            CityError::InvalidConfig
+        );
+        require!(
+            self.crime_chance_pct <= 100 && self.crime_income_penalty_pct <= 100,
+            CityError::InvalidConfig
+        );
+        require!(
+            self.crime_happiness_penalty_pct <= 100 && self.unrest_decline_pct <= 100,
+            CityError::InvalidConfig
+        );
+        require!(
+            self.workforce_pct <= 100 && self.unemployment_growth_penalty_pct <= 100,
+            CityError::InvalidConfig
+        );
+        require!(
+            self.step_interval_seconds >= 1 && self.growth_rate_pct <= 100,
+            CityError::InvalidConfig
+        );
+        require!(self.refund_pct <= 100, CityError::InvalidConfig);
+        require!(
+            self.approval_threshold <= 100 && self.caretaker_income_penalty_pct <= 100,
+            CityError::InvalidConfig
+        );
+        require!(
+            self.fire_chance_pct <= 100
+                && self.fire_burn_steps >= 1
+                && self.disaster_chance_pct <= 100
+                && self.flood_chance_pct <= 100
+                && self.insurance_payout_pct <= 100,
+            CityError::InvalidConfig
+        );
+        Ok(())
+    }
+}
+
+impl Default for GameParams {
+    fn default() -> Self {
+        Self {
+            step_interval_seconds: 10,
+            season_length_steps: 30,
+            day_length_seconds: 600,
+            growth_rate_pct: 20,
+            noise_growth_penalty_pct: 20,
+            park_growth_bonus_pct: 10,
+            pollution_growth_penalty_pct: 10,
+            pollution_happiness_penalty: 3,
+            garbage_per_100_residents: 10,
+            garbage_health_penalty: 30,
+            pollution_health_penalty: 5,
+            garbage_happiness_penalty: 10,
+            tax_happiness_penalty: 2,
+            crime_happiness_penalty_pct: 20,
+            unrest_happiness: 30,
+            unrest_decline_pct: 5,
+            housing_per_residential: 50,
+            workforce_pct: 80,
+            unemployment_growth_penalty_pct: 50,
+            jobs_per_workplace: 20,
+            welfare_per_unemployed: 1,
+            residential_tax_per_capita: 1,
+            commercial_income_per_worker: 1,
+            industrial_income_per_worker: 1,
+            education_income_bonus_pct: 50,
+            commercial_near_homes_bonus_pct: 10,
+            industry_near_homes_penalty: 2,
+            traffic_income_penalty_pct: 50,
+            crime_chance_pct: 2,
+            crime_theft: 50,
+            residents_per_crime_point: 100,
+            crime_income_penalty_pct: 50,
+            tax_approval_penalty: 5,
+            election_interval_steps: 100,
+            approval_threshold: 40,
+            caretaker_income_penalty_pct: 25,
+            fire_chance_pct: 5,
+            fire_burn_steps: 3,
+            disaster_chance_pct: 1,
+            flood_chance_pct: 2,
+            insurance_premium: 10,
+            insurance_payout_pct: 80,
+            quest_seed: 0,
+            dispatch_cost: 200,
+            price_drift_pct: 5,
+            zoning_fee: 5,
+            forest_clearing_cost: 20,
+            reclamation_cost: 2_000,
+            slope_cost_per_level: 30,
+            terraform_cost_per_level: 100,
+            construction_steps: 2,
+            decay_per_step: 1,
+            repair_cost_pct: 50,
+            refund_pct: 50,
+            loan_rate_bps: 50,
+            debt_ceiling: 20_000,
+            max_missed_payments: 3,
+            bankruptcy_limit: 5_000,
+            restructure_penalty_pct: 25,
+            restructure_rate_bps: 100,
+            chunk_price: 5_000,
+            chunk_price_growth_pct: 50,
+            reseed_interval_steps: 100,
+            randomness_timeout_seconds: 300,
+            police_radius: 4,
+            fire_radius: 4,
+            health_radius: 5,
+            education_radius: 5,
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Service radius coverage.
+//!
+//! One engine computes which tiles each service reaches, so police, fire,
+//! health and education mechanics all read the same per-tile bitmap instead
+//! of running their own loops. Radii come from `GameParams`.
+
+use anchor_lang::prelude::*;
+
+use crate::buildings::BuildingType;
+use crate::config::GameParams;
+use crate::network::Grid;
+use crate::{City, GRID_SIZE};
+
+/// Tile codes of service buildings
+pub const POLICE_STATION_TILE: u8 = BuildingType::PoliceStation as u8;
+pub const FIRE_STATION_TILE: u8 = BuildingType::FireStation as u8;
+pub const HOSPITAL_TILE: u8 = BuildingType::Hospital as u8;
+pub const SCHOOL_TILE: u8 = BuildingType::School as u8;
+pub const UNIVERSITY_TILE: u8 = BuildingType::University as u8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Service {
+    Police,
+    Fire,
+    Health,
+    Education,
+}
+
+impl Service {
+    pub const ALL: [Service; 4] = [
+        Service::Police,
+        Service::Fire,
+        Service::Health,
+        Service::Education,
+    ];
+
+    /// Bit of this service in a coverage bitmap entry
+    pub fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    /// The service a tile provides, if any
+    pub fn provided_by(tile: u8) -> Option<Service> {
+        match tile {
+            POLICE_STATION_TILE => Some(Service::Police),
+            FIRE_STATION_TILE => Some(Service::Fire),
+            HOSPITAL_TILE => Some(Service::Health),
+            SCHOOL_TILE | UNIVERSITY_TILE => Some(Service::Education),
+            _ => None,
+        }
+    }
+
+    pub fn radius(self, params: &GameParams) -> u8 {
+        match self {
+            Service::Police => params.police_radius,
+            Service::Fire => params.fire_radius,
+            Service::Health => params.health_radius,
+            Service::Education => params.education_radius,
+        }
+    }
+}
+
+/// Per-tile bitmap of the services reaching each tile
+pub fn compute_coverage(tiles: &Grid, params: &GameParams) -> Grid {
+    let mut coverage = [[0u8; GRID_SIZE]; GRID_SIZE];
+    for (y, row) in tiles.iter().enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            if let Some(service) = Service::provided_by(tile) {
+                apply_coverage(&mut coverage, x, y, service.radius(params), service.bit());
+            }
+        }
+    }
+    coverage
+}
+
+/// OR `bit` into every tile within Manhattan distance `radius` of (x, y)
+pub fn apply_coverage(coverage: &mut Grid, x: usize, y: usize, radius: u8, bit: u8) {
+    let r = radius as i16;
+    for dy in -r..=r {
+        let ty = y as i16 + dy;
+        if ty < 0 || ty >= GRID_SIZE as i16 {
+            continue;
+        }
+        let span = r - dy.abs();
+        for dx in -span..=span {
+            let tx = x as i16 + dx;
+            if tx < 0 || tx >= GRID_SIZE as i16 {
+                continue;
+            }
+            coverage[ty as usize][tx as usize] |= bit;
+        }
+    }
+}
+
+/// True when `service` reaches the tile
+pub fn is_covered(coverage: &Grid, x: usize, y: usize, service: Service) -> bool {
+    coverage[y][x] & service.bit() != 0
+}
+
+// ========================================
+// Cached Overlay
+// ========================================
+
+pub const COVERAGE_SEED: &[u8] = b"coverage";
+
+/// Largest radius a service may have; keeps per-tile counts within a u8
+pub const MAX_SERVICE_RADIUS: u8 = 8;
+
+/// Coverage cached across steps and updated incrementally as tiles change.
+/// `counts` holds, per tile and service, how many buildings reach it, so a
+/// bulldozed station can be subtracted without recomputing the coverage.
+/// `sources` records the service buildings counted, so `sync` catches up
+/// with any number of layout changes, by the mayor or by the simulation,
+/// by diffing the grid against it and adjusting only the tiles that moved.
+#[account]
+#[derive(InitSpace)]
+pub struct CoverageOverlay {
+    pub city: Pubkey,
+    /// `City::layout_version` this overlay reflects
+    pub layout_version: u32,
+    /// Radii the counts were built with, indexed by `Service`
+    pub radii: [u8; 4],
+    pub counts: [[[u8; 4]; GRID_SIZE]; GRID_SIZE],
+    /// Tile code of each service building counted, 0 elsewhere
+    pub sources: [[u8; GRID_SIZE]; GRID_SIZE],
+}
+
+impl CoverageOverlay {
+    /// True when the overlay matches the city layout and the configured radii
+    pub fn is_synced(&self, city: &City, params: &GameParams) -> bool {
+        self.layout_version == city.layout_version && self.radii == Self::radii_of(params)
+    }
+
+    /// Coverage bitmap derived from the counts
+    pub fn bitmap(&self) -> Grid {
+        let mut coverage = [[0u8; GRID_SIZE]; GRID_SIZE];
+        for (y, row) in self.counts.iter().enumerate() {
+            for (x, counts) in row.iter().enumerate() {
+                for service in Service::ALL {
+                    if counts[service as usize] > 0 {
+                        coverage[y][x] |= service.bit();
+                    }
+                }
+            }
+        }
+        coverage
+    }
+
+    /// Recompute every count from scratch
+    pub fn rebuild(&mut self, city: &City, params: &GameParams) {
+        self.counts = [[[0; 4]; GRID_SIZE]; GRID_SIZE];
+        self.sources = [[0; GRID_SIZE]; GRID_SIZE];
+        self.radii = Self::radii_of(params);
+        self.sync(city, params);
+    }
+
+    /// Bring the overlay up to the city's current layout. Only service
+    /// buildings that appeared or went since the last sync are counted in
+    /// or out; new radii mean a rebuild.
+    pub fn sync(&mut self, city: &City, params: &GameParams) {
+        if self.radii != Self::radii_of(params) {
+            self.rebuild(city, params);
+            return;
+        }
+        if self.layout_version == city.layout_version {
+            return;
+        }
+        for (y, row) in city.tiles.iter().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                let source = if Service::provided_by(tile).is_some() {
+                    tile
+                } else {
+                    0
+                };
+                let previous = self.sources[y][x];
+                if source == previous {
+                    continue;
+                }
+                if let Some(service) = Service::provided_by(previous) {
+                    self.adjust(x, y, service, false);
+                }
+                if let Some(service) = Service::provided_by(source) {
+                    self.adjust(x, y, service, true);
+                }
+                self.sources[y][x] = source;
+            }
+        }
+        self.layout_version = city.layout_version;
+    }
+
+    fn adjust(&mut self, x: usize, y: usize, service: Service, add: bool) {
+        let r = self.radii[service as usize] as i16;
+        for dy in -r..=r {
+            let ty = y as i16 + dy;
+            if ty < 0 || ty >= GRID_SIZE as i16 {
+                continue;
+            }
+            let span = r - dy.abs();
+            for dx in -span..=span {
+                let tx = x as i16 + dx;
+                if tx < 0 || tx >= GRID_SIZE as i16 {
+                    continue;
+                }
+                let count = &mut self.counts[ty as usize][tx as usize][service as usize];
+                *count = if add {
+                    count.saturating_add(1)
+                } else {
+                    count.saturating_sub(1)
+                };
+            }
+        }
+    }
+
+    fn radii_of(params: &GameParams) -> [u8; 4] {
+        Service::ALL.map(|service| service.radius(params))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buildings::{footprint_tiles, FOOTPRINT_TILE};
+
+    fn overlay_of(city: &City, params: &GameParams) -> CoverageOverlay {
+        let mut overlay = CoverageOverlay {
+            city: Pubkey::default(),
+            layout_version: 0,
+            radii: [0; 4],
+            counts: [[[0; 4]; GRID_SIZE]; GRID_SIZE],
+            sources: [[0; GRID_SIZE]; GRID_SIZE],
+        };
+        overlay.rebuild(city, params);
+        overlay
+    }
+
+    #[test]
+    fn bulldozing_a_large_building_off_its_anchor_drops_its_coverage() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(0);
+        for (x, y) in footprint_tiles(4, 4, 2) {
+            city.tiles[y][x] = FOOTPRINT_TILE;
+        }
+        city.tiles[4][4] = UNIVERSITY_TILE;
+        city.on_layout_changed();
+        let mut overlay = overlay_of(&city, &params);
+        assert!(is_covered(&overlay.bitmap(), 4, 4, Service::Education));
+
+        city.clear(5, 5, &params).unwrap();
+        overlay.sync(&city, &params);
+
+        assert!(overlay.is_synced(&city, &params));
+        assert_eq!(overlay.bitmap(), compute_coverage(&city.tiles, &params));
+        assert_eq!(overlay.bitmap(), [[0; GRID_SIZE]; GRID_SIZE]);
+    }
+
+    #[test]
+    fn overlay_stays_synced_with_services_underfunded() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(0);
+        city.funding.police_pct = 50;
+        let applied = city.effective_params(&params);
+        assert_ne!(applied.police_radius, params.police_radius);
+        let mut overlay = overlay_of(&city, &applied);
+
+        city.tiles[3][3] = POLICE_STATION_TILE;
+        city.on_layout_changed();
+        overlay.sync(&city, &city.effective_params(&params));
+
+        assert!(overlay.is_synced(&city, &city.effective_params(&params)));
+        assert_eq!(overlay.bitmap(), compute_coverage(&city.tiles, &applied));
+    }
+
+    #[test]
+    fn many_layout_changes_sync_without_a_rebuild() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(0);
+        city.tiles[2][2] = POLICE_STATION_TILE;
+        city.tiles[9][9] = HOSPITAL_TILE;
+        city.on_layout_changed();
+        let mut overlay = overlay_of(&city, &params);
+
+        // Several changes, as a step's fires, decay and development make
+        city.tiles[2][2] = BuildingType::Ruins as u8;
+        city.on_layout_changed();
+        city.tiles[12][3] = FIRE_STATION_TILE;
+        city.on_layout_changed();
+        city.tiles[0][15] = BuildingType::Residential as u8;
+        city.on_layout_changed();
+        assert!(!overlay.is_synced(&city, &params));
+
+        overlay.sync(&city, &params);
+        assert!(overlay.is_synced(&city, &params));
+        assert_eq!(overlay.bitmap(), compute_coverage(&city.tiles, &params));
+        assert_eq!(overlay.sources[2][2], 0);
+        assert_eq!(overlay.sources[12][3], FIRE_STATION_TILE);
+    }
+}
@@ -0,0 +1,50 @@
+//! City-wide crime level.
+//!
+//! Besides the individual crime scenes rolled each step, the city has a
+//! crime level from 0 to 100. It rises with population and with poverty,
+//! measured as the unemployment rate, and falls with the share of homes and
+//! shops in police reach. Crime scares shoppers away, cutting commercial
+//! income, and a level of `HIGH_CRIME_LEVEL` or more raises a `HighCrime`
+//! event.
+
+use crate::config::GameParams;
+
+/// Highest crime level
+pub const MAX_CRIME: u8 = 100;
+
+/// Crime level at which the city is warned
+pub const HIGH_CRIME_LEVEL: u8 = 50;
+
+/// Most crime population alone can cause; poverty brings the rest
+pub const MAX_POPULATION_CRIME: u32 = 50;
+
+/// Census inputs of the crime level
+pub struct CrimeFactors {
+    pub population: u32,
+    pub workforce: u32,
+    pub unemployed: u32,
+    /// Residential and commercial tiles, and those in police reach
+    pub crime_prone_tiles: u32,
+    pub policed_tiles: u32,
+}
+
+/// Crime level, 0-100
+pub fn crime_level(factors: &CrimeFactors, params: &GameParams) -> u8 {
+    if factors.population == 0 || factors.workforce == 0 {
+        return 0;
+    }
+    let from_population = if params.residents_per_crime_point == 0 {
+        0
+    } else {
+        (factors.population / params.residents_per_crime_point).min(MAX_POPULATION_CRIME)
+    };
+    let unemployed_pct =
+        (factors.unemployed as u64 * 100 / factors.workforce as u64).min(100) as u32;
+    let pressure = (from_population + unemployed_pct).min(MAX_CRIME as u32);
+    let policed_pct = if factors.crime_prone_tiles == 0 {
+        0
+    } else {
+        factors.policed_tiles * 100 / factors.crime_prone_tiles
+    };
+    (pressure * (100 - policed_pct) / 100) as u8
+}
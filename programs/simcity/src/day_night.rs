@@ -0,0 +1,74 @@
+//! Day and night.
+//!
+//! Each `day_length_seconds` of clock time is a day, its first half in
+//! daylight and the second at night. The phase of a step comes from the
+//! step's own timestamp, so catch-up steps see the nights they skipped, and
+//! it is stored on the city for clients to light the map. At night:
+//!
+//! - solar plants produce nothing
+//! - homes switch the lights on and draw more power, while shops close and
+//!   draw less
+//! - commerce takes in less
+//! - crime breaks out more often
+
+use anchor_lang::prelude::*;
+
+use crate::buildings::BuildingType;
+use crate::resources::Flows;
+use crate::season::Season;
+
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Default, Clone, Copy, Debug, PartialEq, Eq,
+)]
+pub enum DayPhase {
+    #[default]
+    Day,
+    Night,
+}
+
+impl DayPhase {
+    /// Phase at unix time `now`; a zero length is always day
+    pub fn at(now: i64, day_length_seconds: u32) -> Self {
+        if day_length_seconds == 0 {
+            return DayPhase::Day;
+        }
+        let length = day_length_seconds as i64;
+        if now.rem_euclid(length) < length / 2 {
+            DayPhase::Day
+        } else {
+            DayPhase::Night
+        }
+    }
+
+    /// Commercial income, in percent of the daytime takings
+    pub fn commercial_income_pct(self) -> u64 {
+        match self {
+            DayPhase::Day => 100,
+            DayPhase::Night => 50,
+        }
+    }
+
+    /// Chance of new crime, in percent of the daytime chance
+    pub fn crime_pct(self) -> u64 {
+        match self {
+            DayPhase::Day => 100,
+            DayPhase::Night => 200,
+        }
+    }
+}
+
+impl BuildingType {
+    /// `flows` adjusted for the season and time of day
+    pub fn flows_at(self, season: Season, phase: DayPhase) -> Flows {
+        let mut flows = self.seasonal_flows(season);
+        if phase == DayPhase::Night {
+            match self {
+                BuildingType::SolarPlant => flows.power_out = 0,
+                BuildingType::Residential => flows.power_in = flows.power_in * 3 / 2,
+                BuildingType::Commercial => flows.power_in /= 2,
+                _ => {}
+            }
+        }
+        flows
+    }
+}
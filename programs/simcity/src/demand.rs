@@ -0,0 +1,97 @@
+//! Residential, commercial and industrial demand.
+//!
+//! Each zone has a demand from -100 (nobody wants it) to 100 (everybody
+//! does), worked out every step:
+//!
+//! - residential: jobs against the workforce to fill them, shifted by the
+//!   tax rate, see `taxes`; a city without residents always wants homes
+//! - commercial: shoppers, half the residents, against commercial jobs,
+//!   held back while industry can't supply the goods to sell
+//! - industrial: workers, the other half, against industrial jobs, pushed
+//!   up while shops are short of goods
+//!
+//! Zones only develop and homes only fill up while their demand is
+//! positive.
+
+use anchor_lang::prelude::*;
+
+use crate::buildings::BuildingType;
+use crate::resources::Resources;
+use crate::taxes;
+
+/// Strongest demand either way
+pub const MAX_DEMAND: i8 = 100;
+
+/// Share of residents, in percent, shopping at and working in commerce;
+/// the rest want industrial jobs
+pub const COMMERCIAL_SHARE_PCT: u32 = 50;
+
+/// Demand per zone at the last step, -100 to 100
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Default, Clone, Copy, Debug, PartialEq, Eq,
+)]
+pub struct Demand {
+    pub residential: i8,
+    pub commercial: i8,
+    pub industrial: i8,
+}
+
+impl Demand {
+    /// Demand for `zone`; anything but a zone has none
+    pub fn of(&self, zone: BuildingType) -> i8 {
+        match zone {
+            BuildingType::Residential => self.residential,
+            BuildingType::Commercial => self.commercial,
+            BuildingType::Industrial => self.industrial,
+            _ => 0,
+        }
+    }
+}
+
+/// Census inputs of the demand model
+pub struct DemandFactors {
+    pub population: u32,
+    pub workforce: u32,
+    pub commercial_jobs: u32,
+    pub industrial_jobs: u32,
+    pub resources: Resources,
+    pub tax_rate_bps: u16,
+}
+
+/// `wanted` against `offered`, scaled to -100..=100
+fn balance(wanted: u32, offered: u32) -> i8 {
+    let scale = wanted.max(offered) as i64;
+    if scale == 0 {
+        return 0;
+    }
+    ((wanted as i64 - offered as i64) * MAX_DEMAND as i64 / scale) as i8
+}
+
+pub fn compute_demand(factors: &DemandFactors) -> Demand {
+    let jobs = factors
+        .commercial_jobs
+        .saturating_add(factors.industrial_jobs);
+    let residential = if factors.population == 0 {
+        MAX_DEMAND
+    } else {
+        (balance(jobs, factors.workforce) as i32 + taxes::demand_shift(factors.tax_rate_bps))
+            .clamp(-(MAX_DEMAND as i32), MAX_DEMAND as i32) as i8
+    };
+
+    let shoppers = (factors.population as u64 * COMMERCIAL_SHARE_PCT as u64 / 100) as u32;
+    let mut commercial = balance(shoppers, factors.commercial_jobs);
+    if commercial > 0 {
+        commercial = (commercial as u64 * factors.resources.goods_supply_pct() / 100) as i8;
+    }
+
+    let workers = factors.population - shoppers;
+    let goods_shortage = 100 - factors.resources.goods_supply_pct() as i16;
+    let industrial = (balance(workers, factors.industrial_jobs) as i16 + goods_shortage / 2)
+        .min(MAX_DEMAND as i16) as i8;
+
+    Demand {
+        residential,
+        commercial,
+        industrial,
+    }
+}
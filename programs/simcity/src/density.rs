@@ -0,0 +1,59 @@
+//! Zone density.
+//!
+//! Every zoned building starts at low density and can be upgraded in place
+//! once the city is big enough. Denser buildings house more residents,
+//! grow faster and offer more jobs, by `Density::factor`. Density is stored
+//! per tile on `City::density` and reset whenever the tile is rebuilt.
+
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Density {
+    Low,
+    Medium,
+    High,
+}
+
+impl Density {
+    /// Typed view of a stored density; unknown codes read as `Low`
+    pub fn from_code(code: u8) -> Density {
+        match code {
+            1 => Density::Medium,
+            2 => Density::High,
+            _ => Density::Low,
+        }
+    }
+
+    /// Housing, growth and jobs multiplier
+    pub fn factor(self) -> u32 {
+        match self {
+            Density::Low => 1,
+            Density::Medium => 2,
+            Density::High => 4,
+        }
+    }
+
+    /// Population the city needs before buildings can reach this density
+    pub fn required_population(self) -> u32 {
+        match self {
+            Density::Low => 0,
+            Density::Medium => 500,
+            Density::High => 2_000,
+        }
+    }
+
+    /// The density an upgrade leads to and its price in percent of the
+    /// building's base cost, or `None` at the top
+    pub fn upgrade(self) -> Option<(Density, u64)> {
+        match self {
+            Density::Low => Some((Density::Medium, 150)),
+            Density::Medium => Some((Density::High, 300)),
+            Density::High => None,
+        }
+    }
+}
+
+/// Density multiplier of the tile whose stored code is `code`
+pub fn factor(code: u8) -> u32 {
+    Density::from_code(code).factor()
+}
@@ -0,0 +1,76 @@
+//! Difficulty levels.
+//!
+//! A mayor picks a difficulty when founding a city, and it stays for the
+//! city's lifetime. Normal plays by the config's parameters; easy starts
+//! richer, builds cheaper, collects more tax and sees fewer disasters, and
+//! hard the other way round.
+
+use anchor_lang::prelude::*;
+
+use crate::config::GameParams;
+
+/// Treasury of a new city on normal difficulty
+pub const BASE_STARTING_MONEY: u64 = 10_000;
+
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Default, Clone, Copy, Debug, PartialEq, Eq,
+)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn starting_money(self) -> u64 {
+        BASE_STARTING_MONEY * self.money_pct() / 100
+    }
+
+    /// Starting treasury, in percent of normal
+    pub fn money_pct(self) -> u64 {
+        match self {
+            Difficulty::Easy => 200,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 50,
+        }
+    }
+
+    /// Building prices, in percent of normal
+    pub fn cost_pct(self) -> u64 {
+        match self {
+            Difficulty::Easy => 75,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 125,
+        }
+    }
+
+    /// Taxes and business income collected, in percent of normal
+    pub fn tax_pct(self) -> u64 {
+        match self {
+            Difficulty::Easy => 125,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 75,
+        }
+    }
+
+    /// Disaster chance, in percent of normal
+    pub fn disaster_pct(self) -> u16 {
+        match self {
+            Difficulty::Easy => 50,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 200,
+        }
+    }
+
+    /// `params` with the disaster and flood chances scaled
+    pub fn apply(self, params: &GameParams) -> GameParams {
+        GameParams {
+            disaster_chance_pct: (params.disaster_chance_pct as u16 * self.disaster_pct() / 100)
+                .min(100) as u8,
+            flood_chance_pct: (params.flood_chance_pct as u16 * self.disaster_pct() / 100).min(100)
+                as u8,
+            ..*params
+        }
+    }
+}
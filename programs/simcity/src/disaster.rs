@@ -0,0 +1,108 @@
+//! Natural disasters.
+//!
+//! With `features::DISASTERS`, every step has a `disaster_chance_pct` chance
+//! of striking a random spot with a random kind and severity from 1 to
+//! `MAX_SEVERITY`. Severity widens the area hit:
+//!
+//! - earthquake: the square within `severity` tiles of the epicentre; every
+//!   building in it loses `EARTHQUAKE_DAMAGE` condition per severity, and
+//!   those shaken down to nothing become ruins
+//! - tornado: a straight track `TORNADO_LENGTH` tiles per severity long
+//!   from where it touches down, ruining every building it crosses
+//! - meteor: a crater of Manhattan radius `severity - 1`, ruining everything
+//!
+//! A building hit anywhere on its footprint is hit as a whole. Disasters
+//! ignore tile protection, which only guards against bulldozing. Insured
+//! cities, paying `insurance_premium` every step, get `insurance_payout_pct`
+//! of the rebuild cost back: the full price of ruined buildings and the
+//! repair price of the wear on damaged ones.
+//!
+//! Rolls come from the step's randomness source; request oracle randomness
+//! first to make them unpredictable, see `randomness`.
+
+use anchor_lang::prelude::*;
+
+use crate::randomness::RandomnessSource;
+use crate::GRID_SIZE;
+
+/// Strongest disaster
+pub const MAX_SEVERITY: u8 = 3;
+
+/// Condition an earthquake takes from buildings per point of severity
+pub const EARTHQUAKE_DAMAGE: u8 = 40;
+
+/// Length of a tornado's track per point of severity, in tiles
+pub const TORNADO_LENGTH: u8 = 4;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisasterKind {
+    Earthquake,
+    Tornado,
+    Meteor,
+}
+
+/// A disaster about to strike
+#[derive(Clone, Copy, Debug)]
+pub struct Disaster {
+    pub kind: DisasterKind,
+    /// Epicentre, touchdown or impact tile
+    pub x: usize,
+    pub y: usize,
+    pub severity: u8,
+    /// Tornado heading, one of the four grid directions
+    pub heading: (i8, i8),
+}
+
+impl Disaster {
+    /// Roll the kind, position and severity of a disaster
+    pub fn roll(rng: &mut dyn RandomnessSource) -> Self {
+        let kind = match rng.below(3) {
+            0 => DisasterKind::Earthquake,
+            1 => DisasterKind::Tornado,
+            _ => DisasterKind::Meteor,
+        };
+        let index = rng.below((GRID_SIZE * GRID_SIZE) as u64) as usize;
+        let severity = 1 + rng.below(MAX_SEVERITY as u64) as u8;
+        let heading = [(1, 0), (-1, 0), (0, 1), (0, -1)][rng.below(4) as usize];
+        Self {
+            kind,
+            x: index % GRID_SIZE,
+            y: index / GRID_SIZE,
+            severity,
+            heading,
+        }
+    }
+
+    /// Whether buildings hit are destroyed outright rather than damaged
+    pub fn destroys(&self) -> bool {
+        self.kind != DisasterKind::Earthquake
+    }
+
+    /// Tiles hit, within the grid
+    pub fn area(&self) -> Vec<(usize, usize)> {
+        let (x, y, reach) = (self.x as i32, self.y as i32, self.severity as i32);
+        let in_grid = |(tx, ty): (i32, i32)| {
+            (0..GRID_SIZE as i32).contains(&tx) && (0..GRID_SIZE as i32).contains(&ty)
+        };
+        let tiles: Vec<(i32, i32)> = match self.kind {
+            DisasterKind::Earthquake => (y - reach..=y + reach)
+                .flat_map(|ty| (x - reach..=x + reach).map(move |tx| (tx, ty)))
+                .collect(),
+            DisasterKind::Tornado => {
+                let (dx, dy) = (self.heading.0 as i32, self.heading.1 as i32);
+                (0..reach * TORNADO_LENGTH as i32)
+                    .map(|step| (x + dx * step, y + dy * step))
+                    .collect()
+            }
+            DisasterKind::Meteor => (y - reach + 1..y + reach)
+                .flat_map(|ty| (x - reach + 1..x + reach).map(move |tx| (tx, ty)))
+                .filter(|&(tx, ty)| (tx - x).abs() + (ty - y).abs() < reach)
+                .collect(),
+        };
+        tiles
+            .into_iter()
+            .filter(|&tile| in_grid(tile))
+            .map(|(tx, ty)| (tx as usize, ty as usize))
+            .collect()
+    }
+}
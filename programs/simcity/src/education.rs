@@ -0,0 +1,23 @@
+//! Education level.
+//!
+//! The city's education level, 0-100, is the share of homes in reach of a
+//! school or university, plus `UNIVERSITY_EDUCATION` for every working
+//! university. An educated city earns more from commerce, and advanced
+//! buildings can only be placed once it is educated enough, see
+//! `BuildingType::required_education`.
+
+/// Highest education level
+pub const MAX_EDUCATION: u8 = 100;
+
+/// Education each working university adds on top of school reach
+pub const UNIVERSITY_EDUCATION: u32 = 20;
+
+/// Education level of a city with `educated` of its `residential` tiles in
+/// school reach and `universities` working universities
+pub fn education_level(residential: u32, educated: u32, universities: u32) -> u8 {
+    if residential == 0 {
+        return 0;
+    }
+    (educated * 100 / residential + universities * UNIVERSITY_EDUCATION).min(MAX_EDUCATION as u32)
+        as u8
+}
@@ -0,0 +1,38 @@
+//! Approval rating and elections.
+//!
+//! Every step rates the mayor: approval starts from residents' happiness
+//! and drops further with the tax burden, `tax_approval_penalty` per unit
+//! of per-capita residential tax at the city's tax rate. Every
+//! `election_interval_steps` steps the city votes, and a mayor rated below
+//! `approval_threshold` loses: the city falls into caretaker mode and
+//! collects `caretaker_income_penalty_pct` less income. Caretaker mode ends
+//! at the first step the rating is back at the threshold.
+
+use crate::config::GameParams;
+use crate::taxes;
+use crate::{City, MAX_HAPPINESS};
+
+/// Approval rating, 0-100, of the mayor of `city`
+pub fn approval(city: &City, params: &GameParams) -> u8 {
+    let tax_penalty = params
+        .residential_tax_per_capita
+        .saturating_mul(params.tax_approval_penalty as u64)
+        .saturating_mul(taxes::rate_pct(city.tax_rate_bps))
+        / 100;
+    let tax_penalty = tax_penalty.min(MAX_HAPPINESS as u64) as u8;
+    city.happiness.saturating_sub(tax_penalty)
+}
+
+/// Whether the step numbered `step` ends with an election
+pub fn is_election_step(step: u64, params: &GameParams) -> bool {
+    params.election_interval_steps > 0 && (step + 1) % params.election_interval_steps as u64 == 0
+}
+
+/// Income collected, in percent of the usual
+pub fn income_pct(city: &City, params: &GameParams) -> u64 {
+    if city.caretaker {
+        100 - params.caretaker_income_penalty_pct as u64
+    } else {
+        100
+    }
+}
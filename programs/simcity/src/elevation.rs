@@ -0,0 +1,176 @@
+//! Elevation.
+//!
+//! Alongside its terrain every tile has a height, `City::elevation`, from
+//! water at 0 up to `MAX_ELEVATION`, generated with the terrain from its
+//! seed. Height shows in three places:
+//!
+//! - Hillside plots overlook the land below: a tile gains
+//!   `VIEW_VALUE` land value per level it stands above the lowest tile
+//!   within `VIEW_RADIUS`, up to `MAX_VIEW_LEVELS`.
+//! - Building on a slope costs extra: every level the ground under and
+//!   around a footprint rises past `GENTLE_SLOPE` adds
+//!   `slope_cost_per_level` to the placement.
+//! - Low land by the water floods. With `features::DISASTERS`, every step has
+//!   a `flood_chance_pct` chance of the water rising over the land at or
+//!   below `FLOOD_LEVEL` that borders it, wearing down the buildings there
+//!   by `FLOOD_DAMAGE`. Floods never ruin a building outright.
+//!
+//! Reclaimed land keeps the height of the water it was filled in from, so it
+//! is always prone to flooding.
+//!
+//! Mayors reshape the land with `terraform`: raising or lowering an empty
+//! tile by a level, or flattening it to the average height of the land
+//! around it, for `terraform_cost_per_level` per level moved. Water is left
+//! to `reclaim_land`.
+
+use anchor_lang::prelude::*;
+
+use crate::config::GameParams;
+use crate::network::{self, Grid};
+use crate::terrain::{self, Terrain};
+use crate::GRID_SIZE;
+
+/// Highest a tile can stand
+pub const MAX_ELEVATION: u8 = 9;
+
+/// Land at or below this height floods when it borders water
+pub const FLOOD_LEVEL: u8 = 1;
+
+/// Condition a flood takes off every building it reaches
+pub const FLOOD_DAMAGE: u8 = 25;
+
+/// Rise in levels, across a footprint and the tiles around it, that comes
+/// at no extra cost
+pub const GENTLE_SLOPE: u8 = 1;
+
+/// Land value added, in percent, per level of view
+pub const VIEW_VALUE: i32 = 5;
+/// Distance, in tiles, a hillside plot looks out over
+pub const VIEW_RADIUS: usize = 2;
+pub const MAX_VIEW_LEVELS: u8 = 4;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerraformAction {
+    Raise,
+    Lower,
+    Flatten,
+}
+
+impl TerraformAction {
+    /// Height (x, y) ends up at
+    pub fn target(self, elevation: &Grid, terrain: &Grid, x: usize, y: usize) -> u8 {
+        let height = elevation[y][x];
+        match self {
+            TerraformAction::Raise => (height + 1).min(MAX_ELEVATION),
+            TerraformAction::Lower => height.saturating_sub(1),
+            TerraformAction::Flatten => {
+                let land: Vec<u8> = network::neighbors(x, y)
+                    .filter(|&(nx, ny)| terrain::terrain_of(terrain[ny][nx]) != Terrain::Water)
+                    .map(|(nx, ny)| elevation[ny][nx])
+                    .collect();
+                if land.is_empty() {
+                    return height;
+                }
+                let total: u32 = land.iter().map(|&level| level as u32).sum();
+                ((total + land.len() as u32 / 2) / land.len() as u32) as u8
+            }
+        }
+    }
+}
+
+/// Levels the land rises across a `size` x `size` footprint at (x, y) and
+/// the tiles bordering it; water and tiles past the edge of the grid are
+/// left out
+pub fn slope(elevation: &Grid, terrain: &Grid, x: usize, y: usize, size: usize) -> u8 {
+    let (x0, y0) = (x.saturating_sub(1), y.saturating_sub(1));
+    let (x1, y1) = ((x + size + 1).min(GRID_SIZE), (y + size + 1).min(GRID_SIZE));
+    let heights = (y0..y1)
+        .flat_map(|ty| (x0..x1).map(move |tx| (tx, ty)))
+        .filter(|&(tx, ty)| terrain::terrain_of(terrain[ty][tx]) != Terrain::Water)
+        .map(|(tx, ty)| elevation[ty][tx]);
+    let (low, high) = heights.fold((u8::MAX, 0), |(low, high), height| {
+        (low.min(height), high.max(height))
+    });
+    high.saturating_sub(low)
+}
+
+/// Surcharge for building a `size` x `size` footprint at (x, y) on a slope
+pub fn slope_cost(
+    elevation: &Grid,
+    terrain: &Grid,
+    x: usize,
+    y: usize,
+    size: usize,
+    params: &GameParams,
+) -> u64 {
+    let steep = slope(elevation, terrain, x, y, size).saturating_sub(GENTLE_SLOPE);
+    (steep as u64).saturating_mul(params.slope_cost_per_level)
+}
+
+/// Levels of view from (x, y) over the land within `VIEW_RADIUS`
+pub fn view_levels(elevation: &Grid, x: usize, y: usize) -> u8 {
+    let lowest = (y.saturating_sub(VIEW_RADIUS)..(y + VIEW_RADIUS + 1).min(GRID_SIZE))
+        .flat_map(|ty| {
+            (x.saturating_sub(VIEW_RADIUS)..(x + VIEW_RADIUS + 1).min(GRID_SIZE))
+                .map(move |tx| elevation[ty][tx])
+        })
+        .min()
+        .unwrap_or(0);
+    elevation[y][x].saturating_sub(lowest).min(MAX_VIEW_LEVELS)
+}
+
+/// Whether (x, y) is low land the water rises over in a flood
+pub fn flood_prone(elevation: &Grid, terrain: &Grid, x: usize, y: usize) -> bool {
+    terrain::terrain_of(terrain[y][x]) != Terrain::Water
+        && elevation[y][x] <= FLOOD_LEVEL
+        && network::neighbors(x, y)
+            .any(|(nx, ny)| terrain::terrain_of(terrain[ny][nx]) == Terrain::Water)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRASS: Grid = [[0; GRID_SIZE]; GRID_SIZE];
+
+    #[test]
+    fn only_slopes_past_gentle_cost_extra() {
+        let params = GameParams::default();
+        let mut elevation = [[FLOOD_LEVEL + 1; GRID_SIZE]; GRID_SIZE];
+        assert_eq!(slope_cost(&elevation, &GRASS, 4, 4, 2, &params), 0);
+
+        elevation[3][3] += GENTLE_SLOPE;
+        assert_eq!(slope_cost(&elevation, &GRASS, 4, 4, 2, &params), 0);
+        elevation[6][6] += GENTLE_SLOPE + 2;
+        assert_eq!(
+            slope_cost(&elevation, &GRASS, 4, 4, 2, &params),
+            2 * params.slope_cost_per_level
+        );
+        // Tiles just past the border don't count
+        assert_eq!(slope_cost(&elevation, &GRASS, 8, 8, 2, &params), 0);
+    }
+
+    #[test]
+    fn flattening_levels_to_the_land_around_and_ignores_water() {
+        let mut elevation = [[2; GRID_SIZE]; GRID_SIZE];
+        let mut terrain = GRASS;
+        elevation[5][5] = 8;
+        elevation[4][5] = 4;
+        elevation[5][4] = 0;
+        terrain[5][4] = Terrain::Water.into();
+
+        // (4 + 2 + 2) / 3, rounded
+        assert_eq!(
+            TerraformAction::Flatten.target(&elevation, &terrain, 5, 5),
+            3
+        );
+        assert_eq!(TerraformAction::Raise.target(&elevation, &terrain, 5, 5), 9);
+        elevation[5][5] = MAX_ELEVATION;
+        assert_eq!(
+            TerraformAction::Raise.target(&elevation, &terrain, 5, 5),
+            MAX_ELEVATION
+        );
+        elevation[5][5] = 0;
+        assert_eq!(TerraformAction::Lower.target(&elevation, &terrain, 5, 5), 0);
+    }
+}
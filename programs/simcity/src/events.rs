@@ -0,0 +1,237 @@
+//! Events emitted during simulation steps.
+//!
+//! Clients subscribe to the program logs and turn these into in-game
+//! notifications.
+
+use anchor_lang::prelude::*;
+
+use crate::day_night::DayPhase;
+use crate::disaster::DisasterKind;
+use crate::research::Tech;
+use crate::season::Season;
+
+/// A building caught fire outside fire-station reach
+#[event]
+pub struct FireStarted {
+    pub authority: Pubkey,
+    pub x: u8,
+    pub y: u8,
+}
+
+/// A disaster hit the city; `tiles` lists every tile in its path
+#[event]
+pub struct DisasterStruck {
+    pub authority: Pubkey,
+    pub kind: DisasterKind,
+    pub x: u8,
+    pub y: u8,
+    pub severity: u8,
+    pub tiles: Vec<[u8; 2]>,
+    /// Buildings left standing but worn down, and those turned to ruins
+    pub damaged: u32,
+    pub destroyed: u32,
+    /// Credited back to an insured city's treasury
+    pub insurance_payout: u64,
+}
+
+/// Low land by the water flooded; `tiles` lists every tile under water
+#[event]
+pub struct Flooded {
+    pub authority: Pubkey,
+    pub tiles: Vec<[u8; 2]>,
+    /// Buildings worn down by the water
+    pub damaged: u32,
+    /// Credited back to an insured city's treasury
+    pub insurance_payout: u64,
+}
+
+/// The insurance premium went unpaid and the policy lapsed
+#[event]
+pub struct InsuranceLapsed {
+    pub authority: Pubkey,
+}
+
+/// Fires spread, were put out or destroyed buildings this step
+#[event]
+pub struct FireUpdate {
+    pub authority: Pubkey,
+    pub ignited: u32,
+    pub extinguished: u32,
+    pub burned_down: u32,
+}
+
+/// Construction sites finished this step and started working
+#[event]
+pub struct ConstructionCompleted {
+    pub authority: Pubkey,
+    pub completed: u32,
+}
+
+/// Buildings wore out completely and are now ruins that need bulldozing
+#[event]
+pub struct BuildingsRuined {
+    pub authority: Pubkey,
+    pub ruined: u32,
+}
+
+/// Some homes or shops had no water this step; which ones is in the city's
+/// `dry` map
+#[event]
+pub struct WaterShortage {
+    pub authority: Pubkey,
+    pub dry_tiles: u32,
+}
+
+/// Garbage collection fell short this step and `uncollected` garbage is
+/// piling up
+#[event]
+pub struct GarbageOverflow {
+    pub authority: Pubkey,
+    pub uncollected: u32,
+}
+
+/// A city's scheduled reseed asked the randomness oracle for randomness;
+/// the oracle answers with `consume_randomness` for `city`
+#[event]
+pub struct RandomnessRequested {
+    pub authority: Pubkey,
+    pub city: Pubkey,
+    /// Value the oracle derives its output from, unique per request
+    pub seed: u64,
+}
+
+/// The oracle's randomness was mixed into the city's seed
+#[event]
+pub struct RandomnessFulfilled {
+    pub authority: Pubkey,
+}
+
+/// The oracle didn't answer in time; the city steps on with its own
+/// generator
+#[event]
+pub struct RandomnessTimedOut {
+    pub authority: Pubkey,
+}
+
+/// The year turned to a new season
+#[event]
+pub struct SeasonChanged {
+    pub authority: Pubkey,
+    pub season: Season,
+}
+
+/// Day broke or night fell
+#[event]
+pub struct DayPhaseChanged {
+    pub authority: Pubkey,
+    pub phase: DayPhase,
+}
+
+/// A city won or lost its scenario
+#[event]
+pub struct ScenarioCompleted {
+    pub authority: Pubkey,
+    pub scenario: Pubkey,
+    pub won: bool,
+}
+
+/// Research finished and the city learned `tech`
+#[event]
+pub struct ResearchCompleted {
+    pub authority: Pubkey,
+    pub tech: Tech,
+}
+
+/// The city reached milestones for the first time; their rewards can now
+/// be claimed
+#[event]
+pub struct MilestonesReached {
+    pub authority: Pubkey,
+    /// Bit `index` into `milestones::MILESTONES` per milestone reached
+    pub milestones: u32,
+}
+
+/// The player unlocked achievements
+#[event]
+pub struct AchievementsUnlocked {
+    pub authority: Pubkey,
+    /// Bit `achievement as u8` per achievement unlocked
+    pub achievements: u32,
+}
+
+/// The mayor reached a new level, see `mayor`
+#[event]
+pub struct MayorLeveledUp {
+    pub authority: Pubkey,
+    pub level: u8,
+}
+
+/// The city was started over for a prestige bonus, see `prestige`
+#[event]
+pub struct CityPrestiged {
+    pub authority: Pubkey,
+    /// Final score of the run
+    pub score: u64,
+    pub prestige_count: u16,
+    /// Total bonus after this run, in percent
+    pub prestige_pct: u16,
+}
+
+/// The city voted; a lost election puts it in caretaker mode, see
+/// `elections`
+#[event]
+pub struct ElectionHeld {
+    pub authority: Pubkey,
+    pub approval: u8,
+    pub won: bool,
+}
+
+/// Approval recovered and the city left caretaker mode
+#[event]
+pub struct CaretakerEnded {
+    pub authority: Pubkey,
+    pub approval: u8,
+}
+
+/// Unpaid bills passed the bankruptcy limit; the city can't build until it
+/// restructures its debt, see `bankruptcy`
+#[event]
+pub struct CityBankrupt {
+    pub authority: Pubkey,
+    pub overdraft: u64,
+}
+
+/// The city bought another chunk of land, see `chunks`
+#[event]
+pub struct MapExpanded {
+    pub authority: Pubkey,
+    pub x: i8,
+    pub y: i8,
+    pub price: u64,
+    pub chunks_owned: u16,
+}
+
+/// Residents moved away because happiness fell below `unrest_happiness`
+#[event]
+pub struct ResidentsLeft {
+    pub authority: Pubkey,
+    pub left: u32,
+    pub happiness: u8,
+}
+
+/// The city-wide crime level reached `crime::HIGH_CRIME_LEVEL`
+#[event]
+pub struct HighCrime {
+    pub authority: Pubkey,
+    pub crime: u8,
+}
+
+/// A crime in a tile outside police reach took money from the treasury.
+/// The scene stays active, stealing again each step, until resolved.
+#[event]
+pub struct CrimeIncident {
+    pub authority: Pubkey,
+    pub x: u8,
+    pub y: u8,
+    pub stolen: u64,
+}
@@ -0,0 +1,123 @@
+//! Fire spread.
+//!
+//! `City::fires` holds, per tile, the steps left before a burning building
+//! burns down (0 = not burning). Each step is one cellular update over a
+//! snapshot of the grid: burning tiles in fire-station reach are put out,
+//! the rest tick down and ignite their flammable neighbours. Roads and
+//! empty tiles are firebreaks. A building that burns down leaves ruins
+//! behind, which have to be bulldozed before the lot can be used again.
+
+use crate::buildings::{self, BuildingType};
+use crate::coverage::{self, Service};
+use crate::network::{self, Grid};
+use crate::GRID_SIZE;
+
+/// Zoned buildings burn; roads, empty lots and service buildings don't
+pub fn is_flammable(tile: u8) -> bool {
+    buildings::tile_type(tile).is_zone()
+}
+
+/// Outcome of one spread update
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SpreadResult {
+    /// Tiles newly set alight by a burning neighbour
+    pub ignited: u32,
+    /// Fires put out by fire-station coverage
+    pub extinguished: u32,
+    /// Buildings that burned down and are now ruins
+    pub burned_down: u32,
+}
+
+/// Advance every fire by one step. Burned down tiles turn to ruins in `tiles`;
+/// the caller is responsible for refreshing derived layout state.
+pub fn spread(tiles: &mut Grid, fires: &mut Grid, coverage: &Grid, burn_steps: u8) -> SpreadResult {
+    let mut result = SpreadResult::default();
+    let before = *fires;
+
+    for y in 0..GRID_SIZE {
+        for x in 0..GRID_SIZE {
+            if before[y][x] == 0 {
+                continue;
+            }
+            if coverage::is_covered(coverage, x, y, Service::Fire) {
+                fires[y][x] = 0;
+                result.extinguished += 1;
+                continue;
+            }
+
+            for (nx, ny) in network::neighbors(x, y) {
+                if before[ny][nx] == 0
+                    && fires[ny][nx] == 0
+                    && is_flammable(tiles[ny][nx])
+                    && !coverage::is_covered(coverage, nx, ny, Service::Fire)
+                {
+                    fires[ny][nx] = burn_steps;
+                    result.ignited += 1;
+                }
+            }
+
+            fires[y][x] -= 1;
+            if fires[y][x] == 0 {
+                tiles[y][x] = BuildingType::Ruins.into();
+                result.burned_down += 1;
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOME: u8 = BuildingType::Residential as u8;
+    const NO_COVERAGE: Grid = [[0; GRID_SIZE]; GRID_SIZE];
+
+    #[test]
+    fn fire_spreads_to_neighbours_and_burns_down() {
+        let mut tiles = [[0u8; GRID_SIZE]; GRID_SIZE];
+        let mut fires = [[0u8; GRID_SIZE]; GRID_SIZE];
+        tiles[0][0] = HOME;
+        tiles[0][1] = HOME;
+        tiles[0][2] = network::ROAD_TILE;
+        tiles[0][3] = HOME;
+        fires[0][0] = 2;
+
+        let result = spread(&mut tiles, &mut fires, &NO_COVERAGE, 2);
+        assert_eq!((result.ignited, result.burned_down), (1, 0));
+        assert_eq!(fires[0][0], 1);
+        assert_eq!(fires[0][1], 2);
+
+        let result = spread(&mut tiles, &mut fires, &NO_COVERAGE, 2);
+        assert_eq!((result.ignited, result.burned_down), (0, 1));
+        assert_eq!(tiles[0][0], u8::from(BuildingType::Ruins));
+
+        // The road is a firebreak: the home past it never catches
+        spread(&mut tiles, &mut fires, &NO_COVERAGE, 2);
+        assert_eq!(tiles[0][1], u8::from(BuildingType::Ruins));
+        assert_eq!(tiles[0][3], HOME);
+        assert_eq!(fires, [[0; GRID_SIZE]; GRID_SIZE]);
+    }
+
+    #[test]
+    fn fire_coverage_puts_fires_out_and_shields_neighbours() {
+        let mut tiles = [[0u8; GRID_SIZE]; GRID_SIZE];
+        let mut fires = [[0u8; GRID_SIZE]; GRID_SIZE];
+        let mut coverage = [[0u8; GRID_SIZE]; GRID_SIZE];
+        tiles[4][4] = HOME;
+        tiles[4][5] = HOME;
+        tiles[4][8] = HOME;
+        tiles[4][9] = HOME;
+        fires[4][4] = 3;
+        fires[4][8] = 3;
+        coverage[4][5] = Service::Fire.bit();
+        coverage[4][8] = Service::Fire.bit();
+
+        let result = spread(&mut tiles, &mut fires, &coverage, 3);
+        assert_eq!(result.extinguished, 1);
+        assert_eq!(result.ignited, 0);
+        assert_eq!(fires[4][8], 0);
+        assert_eq!(fires[4][5], 0);
+        assert_eq!(fires[4][4], 2);
+    }
+}
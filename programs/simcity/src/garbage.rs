@@ -0,0 +1,23 @@
+//! Garbage.
+//!
+//! Residents throw out garbage every step. Landfills and recycling centers
+//! connected to the road network collect up to their capacity; whatever
+//! they can't take piles up on the city. While a pile is left uncollected
+//! residents are unhappier and sicker, see `health`. Landfills are cheap
+//! but pollute; recycling centers are clean but cost more to run.
+
+use crate::buildings::BuildingType;
+
+/// Garbage a building can collect per step
+pub fn capacity_of(building_type: BuildingType) -> u32 {
+    match building_type {
+        BuildingType::Landfill => 100,
+        BuildingType::RecyclingCenter => 60,
+        _ => 0,
+    }
+}
+
+/// Garbage `population` residents throw out per step
+pub fn produced_by(population: u32, per_100_residents: u32) -> u32 {
+    (population as u64 * per_100_residents as u64 / 100).min(u32::MAX as u64) as u32
+}
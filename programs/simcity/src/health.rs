@@ -0,0 +1,48 @@
+//! Citizen health.
+//!
+//! Health runs from 0 to 100. A city starts out fairly healthy; hospitals
+//! reaching its homes make it healthier, while pollution at those homes and
+//! uncollected garbage make it sicker. Below `HEALTHY_LEVEL` population
+//! growth slows in proportion.
+
+use crate::config::GameParams;
+
+/// Highest health
+pub const MAX_HEALTH: u8 = 100;
+
+/// Health of a city without hospitals, pollution or garbage
+pub const BASE_HEALTH: u32 = 60;
+
+/// Health at and above which growth is unaffected
+pub const HEALTHY_LEVEL: u8 = 50;
+
+/// Census inputs of the health score
+pub struct HealthFactors {
+    pub residential_tiles: u32,
+    /// Residential tiles in hospital reach
+    pub treated_tiles: u32,
+    /// Pollution level summed over residential tiles
+    pub residential_pollution: u32,
+    /// Garbage piled up uncollected
+    pub garbage: u32,
+}
+
+/// Health score, 0-100
+pub fn health_level(factors: &HealthFactors, params: &GameParams) -> u8 {
+    let (treated, pollution) = if factors.residential_tiles == 0 {
+        (0, 0)
+    } else {
+        (
+            factors.treated_tiles * (MAX_HEALTH as u32 - BASE_HEALTH) / factors.residential_tiles,
+            factors.residential_pollution * params.pollution_health_penalty as u32
+                / factors.residential_tiles,
+        )
+    };
+    let garbage = (factors.garbage > 0) as u32 * params.garbage_health_penalty as u32;
+    (BASE_HEALTH + treated).saturating_sub(pollution + garbage) as u8
+}
+
+/// Share of growth, in percent, a city of `health` keeps
+pub fn growth_pct(health: u8) -> u32 {
+    health.min(HEALTHY_LEVEL) as u32 * 100 / HEALTHY_LEVEL as u32
+}
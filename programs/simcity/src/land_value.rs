@@ -0,0 +1,90 @@
+//! Land value.
+//!
+//! Every step each tile is assessed, in percent of a baseline plot:
+//! greenery, service reach, nearby water sources and hillside views raise
+//! it, noise, pollution and a homeless population lower it. Placing a
+//! building costs its price scaled by the land value of its anchor tile, so
+//! prime plots are expensive.
+//! Roads, highways and power lines are priced the same everywhere.
+
+use crate::buildings::{self, BuildingType};
+use crate::coverage::Service;
+use crate::elevation::{self, VIEW_VALUE};
+use crate::network::Grid;
+use crate::noise;
+use crate::GRID_SIZE;
+
+/// Value of a plot with nothing around it, in percent
+pub const BASE_LAND_VALUE: u8 = 100;
+/// Bounds of an assessed land value; 0 is never assessed
+pub const MIN_LAND_VALUE: u8 = 25;
+pub const MAX_LAND_VALUE: u8 = 250;
+
+/// Value added or taken, in percent, per level or service
+const GREENERY_VALUE: i32 = 10;
+const SERVICE_VALUE: i32 = 5;
+const WATER_VALUE: i32 = 5;
+const NOISE_VALUE: i32 = 10;
+const POLLUTION_VALUE: i32 = 10;
+
+/// Proximity level water sources give off at their own position
+fn water_proximity_of(tile: u8) -> u8 {
+    match buildings::tile_type(tile) {
+        BuildingType::WaterPump | BuildingType::WaterTower => 3,
+        _ => 0,
+    }
+}
+
+/// Inputs of the assessment, all computed earlier in the step
+pub struct Assessment<'a> {
+    pub tiles: &'a Grid,
+    pub coverage: &'a Grid,
+    pub noise: &'a Grid,
+    pub greenery: &'a Grid,
+    pub pollution: &'a Grid,
+    pub elevation: &'a Grid,
+    /// Share of residents without housing, 0-100
+    pub homeless_pct: u8,
+}
+
+/// Per-tile land value in percent of `BASE_LAND_VALUE`
+pub fn compute_land_value(assessment: &Assessment) -> Grid {
+    let mut water = [[0u8; GRID_SIZE]; GRID_SIZE];
+    for (y, row) in assessment.tiles.iter().enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            let level = water_proximity_of(tile);
+            if level > 0 {
+                noise::apply_falloff(&mut water, x, y, level);
+            }
+        }
+    }
+
+    let mut value = [[0u8; GRID_SIZE]; GRID_SIZE];
+    for (y, row) in value.iter_mut().enumerate() {
+        for (x, cell) in row.iter_mut().enumerate() {
+            let services = Service::ALL
+                .iter()
+                .filter(|service| assessment.coverage[y][x] & service.bit() != 0)
+                .count() as i32;
+            let assessed = BASE_LAND_VALUE as i32
+                + assessment.greenery[y][x] as i32 * GREENERY_VALUE
+                + services * SERVICE_VALUE
+                + water[y][x] as i32 * WATER_VALUE
+                + elevation::view_levels(assessment.elevation, x, y) as i32 * VIEW_VALUE
+                - assessment.noise[y][x] as i32 * NOISE_VALUE
+                - assessment.pollution[y][x] as i32 * POLLUTION_VALUE
+                - assessment.homeless_pct as i32 / 2;
+            *cell = assessed.clamp(MIN_LAND_VALUE as i32, MAX_LAND_VALUE as i32) as u8;
+        }
+    }
+    value
+}
+
+/// Land value of a stored assessment; unassessed tiles count as baseline
+pub fn value_pct(stored: u8) -> u64 {
+    if stored == 0 {
+        BASE_LAND_VALUE as u64
+    } else {
+        stored as u64
+    }
+}
@@ -0,0 +1,283 @@
+//! Large maps.
+//!
+//! A city's own grid is `GRID_SIZE` tiles square, small enough to live on
+//! `City` with the rest of its state. A mayor who wants more room opens a
+//! `LargeMap`: a zero-copy account holding a `LARGE_MAP_SIZE` square grid,
+//! read and written in place through `AccountLoader`, so the grid is never
+//! deserialized or copied onto the stack. Buildings on the large map follow
+//! the tile encoding of `City::tiles`, footprints included, and are paid
+//! from and refunded to the city's treasury.
+//!
+//! The large map keeps a count of its buildings by tile code, updated on
+//! every placement and demolition, so nothing has to scan its grid.
+//!
+//! The large map is room to build, not a second simulation. Placing and
+//! bulldozing are the only instructions that run on the loader; zoning,
+//! upgrades, repairs and the rest stay on the city grid, and `run_step`
+//! reads the city grid alone, so buildings on the large map add no
+//! residents, jobs or income and cost no upkeep.
+
+use anchor_lang::prelude::*;
+
+use crate::buildings::{self, footprint_tiles, BuildingType, FOOTPRINT_TILE};
+use crate::config::GameParams;
+use crate::{unlocks, City, CityError};
+
+pub const LARGE_MAP_SEED: &[u8] = b"large_map";
+
+/// Width and height of a large map
+pub const LARGE_MAP_SIZE: usize = 64;
+
+/// Building tile codes counted by `LargeMap::buildings`
+pub const BUILDING_CODES: usize = 32;
+
+#[account(zero_copy)]
+#[repr(C)]
+pub struct LargeMap {
+    pub city: Pubkey,
+    /// Tile codes, encoded like `City::tiles`
+    pub tiles: [[u8; LARGE_MAP_SIZE]; LARGE_MAP_SIZE],
+    /// Buildings standing, by tile code
+    pub buildings: [u32; BUILDING_CODES],
+    /// Bumped on every tile change
+    pub layout_version: u32,
+    /// Price paid for each building, in percent of its base cost, on its
+    /// anchor tile, see `place_on`
+    pub paid_pct: [[u8; LARGE_MAP_SIZE]; LARGE_MAP_SIZE],
+}
+
+impl LargeMap {
+    /// Tile code at (x, y)
+    pub fn tile(&self, x: u16, y: u16) -> Result<u8> {
+        require!(
+            (x as usize) < LARGE_MAP_SIZE && (y as usize) < LARGE_MAP_SIZE,
+            CityError::OutOfBounds
+        );
+        Ok(self.tiles[y as usize][x as usize])
+    }
+
+    /// Buildings of `building_type` standing on the map
+    pub fn count(&self, building_type: BuildingType) -> u32 {
+        self.buildings
+            .get(u8::from(building_type) as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Anchor tile of the building covering (x, y), if any
+    pub fn building_anchor(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        buildings::anchor_of(&self.tiles, x, y)
+    }
+
+    /// Place a building with its anchor at (x, y), paid from `city`'s
+    /// treasury, see `place_on`
+    pub fn place(
+        &mut self,
+        city: &mut City,
+        x: u16,
+        y: u16,
+        building_type: BuildingType,
+        params: &GameParams,
+    ) -> Result<()> {
+        let cost = check_placement(
+            city,
+            building_type,
+            self.count(building_type) as u64,
+            params,
+        )?;
+        place_on(
+            &mut self.tiles,
+            &mut self.paid_pct,
+            city,
+            x as usize,
+            y as usize,
+            building_type,
+            cost,
+        )?;
+        self.buildings[u8::from(building_type) as usize] += 1;
+        self.layout_version = self.layout_version.wrapping_add(1);
+
+        msg!(
+            "Placed {:?} at ({}, {}) on the large map",
+            building_type,
+            x,
+            y
+        );
+        Ok(())
+    }
+
+    /// Clear the building covering (x, y), refunding part of its price to
+    /// `city`
+    pub fn clear(&mut self, city: &mut City, x: u16, y: u16, params: &GameParams) -> Result<()> {
+        self.tile(x, y)?;
+        let (building_type, refund) = clear_on(
+            &mut self.tiles,
+            &mut self.paid_pct,
+            city,
+            x as usize,
+            y as usize,
+            params,
+        )?;
+        let code = u8::from(building_type) as usize;
+        self.buildings[code] = self.buildings[code].saturating_sub(1);
+        self.layout_version = self.layout_version.wrapping_add(1);
+
+        msg!(
+            "Bulldozed large map tile at ({}, {}), refunded {}",
+            x,
+            y,
+            refund
+        );
+        Ok(())
+    }
+}
+
+/// Check `city` may build `building_type` off its own grid, and return the
+/// price: the city grid's, drifting with the `existing` buildings of the
+/// type on the same map, but without land value, which only the city grid
+/// assesses. Landmarks stay on the city grid, as do bridges and coastal
+/// buildings, which need the water only the city grid has.
+pub fn check_placement(
+    city: &City,
+    building_type: BuildingType,
+    existing: u64,
+    params: &GameParams,
+) -> Result<u64> {
+    require!(!city.in_default, CityError::LoanInDefault);
+    require!(!city.bankrupt, CityError::Bankrupt);
+    require!(!building_type.is_landmark(), CityError::InvalidBuildingType);
+    require!(
+        building_type != BuildingType::Bridge && !building_type.is_coastal(),
+        CityError::TerrainBlocked
+    );
+    require!(
+        city.education >= building_type.required_education(),
+        CityError::EducationTooLow
+    );
+    require!(
+        city.unlocked(building_type.required_tech()),
+        CityError::TechRequired
+    );
+    require!(
+        unlocks::tier_of(city.population) >= building_type.required_tier(),
+        CityError::BuildingLocked
+    );
+    let base_cost = building_type
+        .base_cost()
+        .ok_or(CityError::InvalidBuildingType)?;
+    let drift_pct = if building_type == BuildingType::Road {
+        100
+    } else {
+        100 + existing.saturating_mul(params.price_drift_pct as u64)
+    };
+    Ok(base_cost
+        .saturating_mul(drift_pct)
+        .saturating_mul(city.difficulty.cost_pct())
+        .saturating_mul(city.specialization.cost_pct(building_type))
+        / 1_000_000)
+}
+
+/// Place a building with its anchor at (x, y) of an off-grid map of any
+/// size for `cost`, as quoted by `check_placement`, paid from `city`'s
+/// treasury. Footprints can't run past the edge of the map. The price is
+/// kept in `paid_pct`, in percent of the base cost and rounded down, so the
+/// refund never exceeds it.
+pub fn place_on<const N: usize>(
+    tiles: &mut [[u8; N]; N],
+    paid_pct: &mut [[u8; N]; N],
+    city: &mut City,
+    x: usize,
+    y: usize,
+    building_type: BuildingType,
+    cost: u64,
+) -> Result<()> {
+    let size = building_type.footprint() as usize;
+    require!(x + size <= N && y + size <= N, CityError::OutOfBounds);
+    require!(
+        footprint_tiles(x, y, size).all(|(tx, ty)| tiles[ty][tx] == 0),
+        CityError::TileOccupied
+    );
+    city.spend(cost)?;
+
+    for (tx, ty) in footprint_tiles(x, y, size) {
+        tiles[ty][tx] = FOOTPRINT_TILE;
+    }
+    tiles[y][x] = building_type.into();
+    let base_cost = building_type.base_cost().unwrap_or(0).max(1);
+    paid_pct[y][x] = if city.sandbox {
+        0
+    } else {
+        (cost * 100 / base_cost).min(u8::MAX as u64) as u8
+    };
+    Ok(())
+}
+
+/// Clear the building covering (x, y) of an off-grid map of any size,
+/// refunding `refund_pct` of the price paid for it to `city`; returns the
+/// building and the refund
+pub fn clear_on<const N: usize>(
+    tiles: &mut [[u8; N]; N],
+    paid_pct: &mut [[u8; N]; N],
+    city: &mut City,
+    x: usize,
+    y: usize,
+    params: &GameParams,
+) -> Result<(BuildingType, u64)> {
+    let (ax, ay) = buildings::anchor_of(tiles, x, y).ok_or(CityError::TileEmpty)?;
+    let building_type = buildings::tile_type(tiles[ay][ax]);
+    let price = building_type.base_cost().unwrap_or(0) * paid_pct[ay][ax] as u64 / 100;
+    let refund = price * params.refund_pct as u64 / 100;
+    city.money = city.money.saturating_add(refund);
+
+    for (tx, ty) in footprint_tiles(ax, ay, building_type.footprint() as usize) {
+        tiles[ty][tx] = 0;
+        paid_pct[ty][tx] = 0;
+    }
+    Ok((building_type, refund))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::Difficulty;
+
+    #[test]
+    fn off_grid_refund_never_exceeds_the_price_paid() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(10_000);
+        city.difficulty = Difficulty::Easy;
+        let mut tiles = [[0u8; 16]; 16];
+        let mut paid_pct = [[0u8; 16]; 16];
+
+        let cost = check_placement(&city, BuildingType::Park, 0, &params).unwrap();
+        place_on(
+            &mut tiles,
+            &mut paid_pct,
+            &mut city,
+            2,
+            3,
+            BuildingType::Park,
+            cost,
+        )
+        .unwrap();
+        assert_eq!(city.money, 10_000 - cost);
+        let (building_type, refund) =
+            clear_on(&mut tiles, &mut paid_pct, &mut city, 2, 3, &params).unwrap();
+        assert_eq!(building_type, BuildingType::Park);
+        assert_eq!(refund, cost * params.refund_pct as u64 / 100);
+        assert_eq!(tiles, [[0u8; 16]; 16]);
+        assert_eq!(paid_pct, [[0u8; 16]; 16]);
+    }
+
+    #[test]
+    fn off_grid_prices_drift_with_existing_buildings() {
+        let params = GameParams::default();
+        let city = City::for_tests(10_000);
+        let first = check_placement(&city, BuildingType::Park, 0, &params).unwrap();
+        let third = check_placement(&city, BuildingType::Park, 2, &params).unwrap();
+        assert_eq!(
+            third,
+            first * (100 + 2 * params.price_drift_pct as u64) / 100
+        );
+    }
+}
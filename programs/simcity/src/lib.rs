@@ -4,21 +4,171 @@ use ephemeral_rollups_sdk::cpi::DelegateConfig;
 use ephemeral_rollups_sdk::ephem::{commit_accounts, commit_and_undelegate_accounts};
 use session_keys::{session_auth_or, Session, SessionError, SessionToken};
 
+pub mod achievements;
+pub mod adjacency;
+pub mod bankruptcy;
+pub mod budget;
+pub mod buildings;
+pub mod chunks;
+pub mod coastal;
+pub mod config;
+pub mod coverage;
+pub mod crime;
+pub mod day_night;
+pub mod demand;
+pub mod density;
+pub mod difficulty;
+pub mod disaster;
+pub mod education;
+pub mod elections;
+pub mod elevation;
+pub mod events;
+pub mod fire;
+pub mod garbage;
+pub mod health;
+pub mod land_value;
+pub mod large_map;
+pub mod mayor;
+pub mod milestones;
+pub mod network;
+pub mod noise;
+pub mod ordinances;
+pub mod parks;
+pub mod pollution;
+pub mod power;
+pub mod prestige;
+pub mod procgen;
+pub mod quests;
+pub mod randomness;
+pub mod research;
+pub mod resources;
+pub mod roads;
+pub mod scenario;
+pub mod season;
+pub mod simulation;
+pub mod specialization;
+pub mod spectator;
+pub mod taxes;
+pub mod terrain;
+pub mod traffic;
+pub mod unlocks;
+pub mod water;
+pub mod zoning;
+
+use achievements::{Achievements, ACHIEVEMENTS_SEED};
+use budget::{Budget, BudgetReport, ServiceFunding};
+use buildings::{footprint_tiles, BuildingType, FOOTPRINT_TILE};
+use chunks::{Chunk, CHUNK_SEED};
+use config::{GameConfig, GameParams, CONFIG_SEED};
+use coverage::{CoverageOverlay, COVERAGE_SEED, FIRE_STATION_TILE, POLICE_STATION_TILE};
+
+use day_night::DayPhase;
+use demand::Demand;
+use density::Density;
+use difficulty::Difficulty;
+use elevation::TerraformAction;
+use events::{
+    AchievementsUnlocked, CityPrestiged, MapExpanded, MayorLeveledUp, RandomnessFulfilled,
+    RandomnessRequested, RandomnessTimedOut, ScenarioCompleted,
+};
+use large_map::{LargeMap, LARGE_MAP_SEED};
+use mayor::{MayorProfile, PROFILE_SEED, XP_PER_MILESTONE, XP_PER_PLACEMENT, XP_PER_STEP};
+use ordinances::Ordinance;
+use quests::QuestStatus;
+use randomness::{RandomnessMode, RandomnessSource};
+use research::Tech;
+use resources::Resources;
+use roads::RoadTier;
+use scenario::{Scenario, ScenarioParams, ScenarioStatus, SCENARIO_SEED};
+use season::Season;
+use specialization::Specialization;
+use spectator::{SpectatorPass, SPECTATOR_SEED};
+use terrain::Terrain;
+use unlocks::BuildingUnlock;
+
 declare_id!("6U4BoX8jTdsJca3N6B1H42x4NkCeMVV667QkDBV8bdKq");
 
+pub const SCORE_SEED: &[u8] = b"score";
+
+/// Width and height of the city grid
+pub const GRID_SIZE: usize = 16;
+
+/// Anti-cheat: at most this many mutating actions per city per window
+pub const MAX_ACTIONS_PER_WINDOW: u16 = 20;
+pub const ACTION_WINDOW_SECONDS: i64 = 1;
+
+/// Number of recently processed idempotency keys remembered per city
+pub const IDEMPOTENCY_KEY_SLOTS: usize = 8;
+
+/// Upper bound on the number of actions in a single `do_actions` batch
+pub const MAX_BATCH_ACTIONS: usize = 16;
+
+/// Upper bound on ticks simulated by a single `project_steps` call
+pub const MAX_PROJECTION_STEPS: u16 = 32;
+
+/// Upper bound on missed steps a single `step_simulation` call catches up
+/// on; time away beyond that is forfeited
+pub const MAX_CATCH_UP_STEPS: i64 = 4;
+
+/// Ceiling of `City::happiness`
+pub const MAX_HAPPINESS: u8 = 100;
+
+/// Condition of a new or fully repaired building
+pub const MAX_CONDITION: u8 = 100;
+
+/// Per-city simulation feature flags, stored as a bitmask on `City::features`.
+/// Heavy mechanics only run when their flag is set.
+pub mod features {
+    pub const TRAFFIC: u32 = 1 << 0;
+    pub const POLLUTION: u32 = 1 << 1;
+    /// Working-age residents, unemployment and homelessness. Without it
+    /// every resident counts towards the workforce, and nobody is out of
+    /// work or homeless.
+    pub const DEMOGRAPHICS: u32 = 1 << 2;
+    /// Disasters and floods. Set from the config when the city is created
+    /// and out of the mayor's hands afterwards, so nobody plays risk-free.
+    pub const DISASTERS: u32 = 1 << 3;
+
+    pub const ALL: u32 = TRAFFIC | POLLUTION | DEMOGRAPHICS | DISASTERS;
+    /// Flags a new config hands to newly initialized cities, see
+    /// `GameConfig::default_features`
+    pub const DEFAULT: u32 = ALL;
+}
+
 #[ephemeral]
 #[program]
 pub mod simcity_build {
     use super::*;
 
-    /// Initialize a new city account
-    pub fn initialize_city(ctx: Context<InitializeCity>) -> Result<()> {
+    /// Initialize a new city account at `difficulty` with a permanent
+    /// `specialization`; a sandbox city builds for free and never sees
+    /// disasters, but stays off the leaderboard
+    pub fn initialize_city(
+        ctx: Context<InitializeCity>,
+        sandbox: bool,
+        difficulty: Difficulty,
+        specialization: Specialization,
+    ) -> Result<()> {
         let city = &mut ctx.accounts.city;
         city.tiles = [[0; 16]; 16];
         city.population = 0;
-        city.money = 10000; // Starting money
+        city.money = difficulty.starting_money();
         city.last_updated = Clock::get()?.unix_timestamp;
         city.authority = ctx.accounts.authority.key();
+        city.action_window_start = city.last_updated;
+        city.actions_in_window = 0;
+        city.features = ctx.accounts.config.default_features;
+        city.happiness = MAX_HAPPINESS;
+        city.budget = Budget::default();
+        city.funding = ServiceFunding::default();
+        city.tax_rate_bps = taxes::NOMINAL_TAX_RATE_BPS;
+        city.rng_seed = randomness::city_seed(&city.authority);
+        city.terrain_seed = city.rng_seed;
+        city.terrain = procgen::generate(city.terrain_seed);
+        city.elevation = procgen::generate_elevation(city.terrain_seed, &city.terrain);
+        city.sandbox = sandbox;
+        city.difficulty = difficulty;
+        city.specialization = specialization;
 
         msg!("City initialized for authority: {}", city.authority);
         Ok(())
@@ -29,25 +179,27 @@ pub mod simcity_build {
         ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
         CityError::InvalidAuth
     )]
-    pub fn place_building(ctx: Context<UpdateCity>, x: u8, y: u8, building_type: u8) -> Result<()> {
-        require!(x < 16 && y < 16, CityError::OutOfBounds);
-        // Basic validation: 0=Empty, 1=Global, 2=Residential, 3=Commercial, 4=Industrial
-        // Assuming > 0 is a building. 0 is bulldozing (use bulldoze instruction for clarity or allow here)
-        require!(building_type > 0, CityError::InvalidBuildingType);
+    pub fn place_building(ctx: Context<UpdateCity>, args: PlaceBuildingArgs) -> Result<()> {
+        let PlaceBuilding {
+            x,
+            y,
+            building_type,
+            overwrite,
+            idempotency_key,
+        } = args.into_latest();
 
         let city = &mut ctx.accounts.city;
-        city.tiles[y as usize][x as usize] = building_type;
-
-        // Simple mechanic: Spend money
-        // TODO: Make costs dynamic based on building type
-        let cost = 100;
-        if city.money >= cost {
-            city.money -= cost;
-        } else {
-            return err!(CityError::NotEnoughMoney);
+        if city.already_processed(idempotency_key) {
+            msg!("Duplicate place_building request ignored");
+            return Ok(());
         }
-
-        msg!("Placed building type {} at ({}, {})", building_type, x, y);
+        let now = Clock::get()?.unix_timestamp;
+        city.record_action(now)?;
+        ctx.accounts.catch_up(now)?;
+        let city = &mut ctx.accounts.city;
+        city.place(x, y, building_type, overwrite, &ctx.accounts.config.params)?;
+        ctx.accounts.sync_coverage();
+        ctx.accounts.award_xp(XP_PER_PLACEMENT);
         Ok(())
     }
 
@@ -56,164 +208,3260 @@ pub mod simcity_build {
         ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
         CityError::InvalidAuth
     )]
-    pub fn bulldoze(ctx: Context<UpdateCity>, x: u8, y: u8) -> Result<()> {
-        require!(x < 16 && y < 16, CityError::OutOfBounds);
+    pub fn bulldoze(ctx: Context<UpdateCity>, args: BulldozeArgs) -> Result<()> {
+        let Bulldoze {
+            x,
+            y,
+            idempotency_key,
+        } = args.into_latest();
+
+        let city = &mut ctx.accounts.city;
+        if city.already_processed(idempotency_key) {
+            msg!("Duplicate bulldoze request ignored");
+            return Ok(());
+        }
+        let now = Clock::get()?.unix_timestamp;
+        city.record_action(now)?;
+        ctx.accounts.catch_up(now)?;
+        let city = &mut ctx.accounts.city;
+        city.clear(x, y, &ctx.accounts.config.params)?;
+        ctx.accounts.sync_coverage();
+        Ok(())
+    }
 
+    /// Paint a `width` x `height` rectangle with a zone, or clear its zoning
+    /// with `BuildingType::Empty`; see `zoning`
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn zone_tiles(
+        ctx: Context<UpdateCity>,
+        x: u8,
+        y: u8,
+        width: u8,
+        height: u8,
+        zone: BuildingType,
+    ) -> Result<()> {
         let city = &mut ctx.accounts.city;
-        city.tiles[y as usize][x as usize] = 0; // 0 = Empty
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        city.zone(x, y, width, height, zone, &ctx.accounts.config.params)
+    }
 
-        msg!("Bulldozed tile at ({}, {})", x, y);
+    /// Lock a tile so bulldozing or overwriting it fails until it is
+    /// unprotected
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn protect_tile(ctx: Context<UpdateCity>, x: u8, y: u8) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        city.set_protected(x, y, true)?;
+        msg!("Protected tile at ({}, {})", x, y);
         Ok(())
     }
 
-    /// Simulate one step (can be called periodically)
+    /// Remove the protection added by `protect_tile`
     #[session_auth_or(
         ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
         CityError::InvalidAuth
     )]
-    pub fn step_simulation(ctx: Context<UpdateCity>) -> Result<()> {
+    pub fn unprotect_tile(ctx: Context<UpdateCity>, x: u8, y: u8) -> Result<()> {
         let city = &mut ctx.accounts.city;
-        let now = Clock::get()?.unix_timestamp;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        city.set_protected(x, y, false)?;
+        msg!("Unprotected tile at ({}, {})", x, y);
+        Ok(())
+    }
 
-        // Example logic: Grow population if there are residential tiles
-        // In a real game, this would be more complex
-        let mut residential_count = 0;
-        for row in city.tiles.iter() {
-            for &tile in row.iter() {
-                if tile == 2 {
-                    // Residential
-                    residential_count += 1;
-                }
-            }
+    /// Apply a mixed list of actions atomically under a single auth check.
+    /// Any failing action reverts the whole batch. A retried batch carrying
+    /// the `idempotency_key` of one already applied is ignored.
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn do_actions(
+        ctx: Context<UpdateCity>,
+        actions: Vec<Action>,
+        idempotency_key: Option<u64>,
+    ) -> Result<()> {
+        require!(
+            actions.len() <= MAX_BATCH_ACTIONS,
+            CityError::TooManyActions
+        );
+        if ctx.accounts.city.already_processed(idempotency_key) {
+            msg!("Duplicate do_actions request ignored");
+            return Ok(());
         }
 
-        if residential_count > 0 {
-            city.population += residential_count * 10;
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.catch_up(now)?;
+        let params = ctx.accounts.config.params;
+        let placed = ctx.accounts.city.apply_actions(&actions, now, &params)?;
+        ctx.accounts.sync_coverage();
+        for _ in 0..placed {
+            ctx.accounts.award_xp(XP_PER_PLACEMENT);
         }
 
-        city.last_updated = now;
-        msg!("Simulation step complete. Population: {}", city.population);
+        msg!("Applied {} actions", actions.len());
         Ok(())
     }
 
-    // ========================================
-    // MagicBlock Ephemeral Rollups Functions
-    // ========================================
+    /// Simulate every step due since the last one, one per
+    /// `step_interval_seconds` and at most `MAX_CATCH_UP_STEPS`. Fails when
+    /// no step is due yet. Placing, bulldozing and batches catch up the
+    /// same way first, so idle cities progress without a cranker.
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn step_simulation(ctx: Context<UpdateCity>) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        let now = Clock::get()?.unix_timestamp;
+        city.record_action(now)?;
 
-    pub fn delegate(ctx: Context<DelegateInput>) -> Result<()> {
-        ctx.accounts.delegate_pda(
-            &ctx.accounts.payer,
-            &[ctx.accounts.payer.key().as_ref()],
-            DelegateConfig {
-                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
-                ..Default::default()
-            },
-        )?;
+        require!(
+            !city.awaiting_randomness(&ctx.accounts.config.params, now),
+            CityError::RandomnessPending
+        );
+        require!(
+            city.steps_due(&ctx.accounts.config.params, now) > 0,
+            CityError::StepTooSoon
+        );
+        let steps = ctx.accounts.catch_up(now)?;
+        msg!(
+            "Simulated {} steps. Population: {}",
+            steps,
+            ctx.accounts.city.population
+        );
         Ok(())
     }
 
-    pub fn commit(ctx: Context<CommitInput>) -> Result<()> {
-        commit_accounts(
-            &ctx.accounts.payer,
-            vec![&ctx.accounts.city.to_account_info()],
-            &ctx.accounts.magic_context,
-            &ctx.accounts.magic_program,
-        )?;
+    /// Spend money to put out a fire or end a crime on a tile immediately
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn dispatch_emergency(ctx: Context<UpdateCity>, x: u8, y: u8) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        city.dispatch_emergency(x, y, ctx.accounts.config.params.dispatch_cost)
+    }
+
+    /// Restore a worn building to full condition
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn repair_building(ctx: Context<UpdateCity>, x: u8, y: u8) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        city.repair(x, y, &ctx.accounts.config.params)
+    }
+
+    /// Raise a zone building to the next density once the city has grown
+    /// enough
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn upgrade_density(ctx: Context<UpdateCity>, x: u8, y: u8) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        city.upgrade_density(x, y)?;
+        ctx.accounts.track_achievements();
         Ok(())
     }
 
-    pub fn undelegate(ctx: Context<CommitInput>) -> Result<()> {
-        commit_and_undelegate_accounts(
-            &ctx.accounts.payer,
-            vec![&ctx.accounts.city.to_account_info()],
-            &ctx.accounts.magic_context,
-            &ctx.accounts.magic_program,
-        )?;
+    /// Upgrade a road tile to the next tier, raising its traffic capacity
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn upgrade_road(ctx: Context<UpdateCity>, x: u8, y: u8) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        city.upgrade_road(x, y)
+    }
+
+    /// Fill in a water tile bordering land, making it buildable grass, see
+    /// `terrain`
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn reclaim_land(ctx: Context<UpdateCity>, x: u8, y: u8) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        city.reclaim(x, y, &ctx.accounts.config.params)
+    }
+
+    /// Raise, lower or flatten an empty tile, see `elevation`
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn terraform(
+        ctx: Context<UpdateCity>,
+        x: u8,
+        y: u8,
+        action: TerraformAction,
+    ) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        city.terraform(x, y, action, &ctx.accounts.config.params)
+    }
+
+    /// Collect the reward of a reached milestone, see `milestones`
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn claim_milestone(ctx: Context<UpdateCity>, milestone: u8) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        let reward = city.claim_milestone(milestone)?;
+        ctx.accounts.pay_level_bonus(reward);
+        ctx.accounts.award_xp(XP_PER_MILESTONE);
         Ok(())
     }
-}
 
-// ========================================
-// Account Structs
-// ========================================
+    /// Collect the reward of today's quest, see `quests`
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn claim_quest_reward(ctx: Context<UpdateCity>) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        let now = Clock::get()?.unix_timestamp;
+        city.record_action(now)?;
+        let reward = city.claim_quest(now, ctx.accounts.config.params.quest_seed)?;
+        ctx.accounts.pay_level_bonus(reward);
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct InitializeCity<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + City::INIT_SPACE,
-        seeds = [authority.key().as_ref()],
-        bump
+    /// Pay for `tech` and start researching it, see `research`
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
     )]
-    pub city: Account<'info, City>,
+    pub fn research(ctx: Context<UpdateCity>, tech: Tech) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        city.start_research(tech)
+    }
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    /// Run the simulation forward `steps` ticks on a copy of the city and
+    /// return the projected stats without mutating the account
+    pub fn project_steps(ctx: Context<ViewCity>, steps: u16) -> Result<Projection> {
+        require!(
+            steps <= MAX_PROJECTION_STEPS,
+            CityError::TooManyProjectionSteps
+        );
 
-    pub system_program: Program<'info, System>,
-}
+        // `ViewCity` doesn't take the city `mut`, so the steps run on the boxed
+        // copy deserialized onto the heap and are never written back
+        let city = &mut ctx.accounts.city;
+        let now = Clock::get()?.unix_timestamp;
+        for _ in 0..steps {
+            simulation::run_step(city, &ctx.accounts.config.params, now, None, None);
+        }
 
-#[derive(Accounts, Session)]
-pub struct UpdateCity<'info> {
-    #[account(
-        mut,
-        seeds = [city.authority.key().as_ref()],
-        bump
+        Ok(Projection {
+            steps,
+            population: city.population,
+            money: city.money,
+        })
+    }
+
+    /// Dry-run the next step on a copy of the city and report its income and
+    /// spending by department
+    pub fn collect_budget_report(ctx: Context<ViewCity>) -> Result<BudgetReport> {
+        // Like `project_steps`, on the heap copy, never written back
+        let city = &mut ctx.accounts.city;
+        let now = Clock::get()?.unix_timestamp;
+        let state = simulation::run_step(city, &ctx.accounts.config.params, now, None, None);
+
+        let income = state.residential_tax + state.commercial_income + state.industrial_income;
+        let spending = state.welfare_cost
+            + state.upkeep
+            + state.loan_interest
+            + state.insurance_premium
+            + state.ordinance_cost;
+        Ok(BudgetReport {
+            residential_tax: state.residential_tax,
+            commercial_income: state.commercial_income,
+            industrial_income: state.industrial_income,
+            welfare: state.welfare_cost,
+            safety_spending: state.safety_upkeep,
+            utilities_spending: state.utilities_upkeep,
+            parks_spending: state.parks_upkeep,
+            other_upkeep: state.other_upkeep,
+            loan_interest: state.loan_interest,
+            insurance_premium: state.insurance_premium,
+            ordinances: state.ordinance_cost,
+            net: income as i64 - spending as i64,
+        })
+    }
+
+    /// Return the exact amount `place_building` would charge for this placement
+    pub fn quote_placement(
+        ctx: Context<ViewCity>,
+        x: u8,
+        y: u8,
+        building_type: BuildingType,
+    ) -> Result<u64> {
+        ctx.accounts
+            .city
+            .placement_cost(x, y, building_type, &ctx.accounts.config.params)
+    }
+
+    /// Return every placeable building with the population tier that
+    /// unlocks it, see `unlocks`
+    pub fn building_unlocks(_ctx: Context<ViewUnlocks>) -> Result<Vec<BuildingUnlock>> {
+        Ok(unlocks::table())
+    }
+
+    /// Return today's quest and the city's progress on it
+    pub fn daily_quest(ctx: Context<ViewCity>) -> Result<QuestStatus> {
+        Ok(quests::status(
+            &ctx.accounts.city,
+            Clock::get()?.unix_timestamp,
+            ctx.accounts.config.params.quest_seed,
+        ))
+    }
+
+    /// Borrow `amount` into the treasury, up to the debt ceiling. The rate
+    /// is fixed when a loan is opened and kept for later top-ups.
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
     )]
-    pub city: Account<'info, City>,
+    pub fn take_loan(ctx: Context<UpdateCity>, amount: u64) -> Result<()> {
+        let params = &ctx.accounts.config.params;
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        require!(!city.in_default, CityError::LoanInDefault);
+        require!(!city.bankrupt, CityError::Bankrupt);
+        let principal = city
+            .loan_principal
+            .checked_add(amount)
+            .filter(|&principal| principal <= params.debt_ceiling)
+            .ok_or(CityError::DebtCeilingExceeded)?;
 
-    #[account(mut)]
-    pub signer: Signer<'info>,
+        if city.loan_principal == 0 {
+            city.loan_rate_bps = params.loan_rate_bps;
+        }
+        city.loan_principal = principal;
+        city.money = city.money.saturating_add(amount);
 
-    #[session(signer = signer, authority = city.authority.key())]
-    pub session_token: Option<Account<'info, SessionToken>>,
-}
+        msg!("Borrowed {}, now owing {}", amount, principal);
+        Ok(())
+    }
 
-#[delegate]
-#[derive(Accounts)]
-pub struct DelegateInput<'info> {
-    pub payer: Signer<'info>,
-    /// CHECK: The PDA to delegate - validated by seeds constraint
-    #[account(mut, del, seeds = [payer.key().as_ref()], bump)]
-    pub pda: AccountInfo<'info>,
-}
+    /// Pay back up to `amount` of the outstanding loan from the treasury
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn repay_loan(ctx: Context<UpdateCity>, amount: u64) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        require!(city.loan_principal > 0, CityError::NoOutstandingLoan);
+        let payment = amount.min(city.loan_principal);
+        require!(city.money >= payment, CityError::NotEnoughMoney);
 
-#[commit]
-#[derive(Accounts)]
-pub struct CommitInput<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(mut, seeds = [payer.key().as_ref()], bump)]
-    pub city: Account<'info, City>,
-}
+        city.money -= payment;
+        city.loan_principal -= payment;
+        if city.loan_principal == 0 {
+            city.missed_loan_payments = 0;
+            city.in_default = false;
+        }
 
-// ========================================
-// Account Data
-// ========================================
+        msg!("Repaid {}, now owing {}", payment, city.loan_principal);
+        Ok(())
+    }
 
-#[account]
-#[derive(InitSpace)]
-pub struct City {
-    pub tiles: [[u8; 16]; 16], // 16x16 grid = 256 bytes
-    pub population: u32,
-    pub money: u64,
-    pub last_updated: i64,
-    pub authority: Pubkey,
-}
+    /// Bring a bankrupt city out of bankruptcy by rolling its overdraft,
+    /// plus a surcharge, into the loan; see `bankruptcy`
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn restructure_debt(ctx: Context<UpdateCity>) -> Result<()> {
+        let params = &ctx.accounts.config.params;
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        require!(city.bankrupt, CityError::NotBankrupt);
 
-// ========================================
-// Errors
-// ========================================
+        let added = bankruptcy::restructured_principal(city.overdraft, params);
+        city.loan_principal = city.loan_principal.saturating_add(added);
+        city.loan_rate_bps = city.loan_rate_bps.max(params.restructure_rate_bps);
+        city.overdraft = 0;
+        city.bankrupt = false;
+        city.missed_loan_payments = 0;
+        city.in_default = false;
 
-#[error_code]
-pub enum CityError {
-    #[msg("Coordinates out of bounds")]
-    OutOfBounds,
-    #[msg("Invalid building type")]
-    InvalidBuildingType,
-    #[msg("Invalid authentication")]
-    InvalidAuth,
-    #[msg("Not enough money")]
-    NotEnoughMoney,
+        msg!(
+            "Debt restructured, now owing {} at {} bps",
+            city.loan_principal,
+            city.loan_rate_bps
+        );
+        Ok(())
+    }
+
+    /// Enact or repeal an ordinance, see `ordinances`
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn set_ordinance(
+        ctx: Context<UpdateCity>,
+        ordinance: Ordinance,
+        enabled: bool,
+    ) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        if enabled {
+            city.ordinances |= ordinance.bit();
+        } else {
+            city.ordinances &= !ordinance.bit();
+        }
+
+        msg!(
+            "{:?} {}",
+            ordinance,
+            if enabled { "enacted" } else { "repealed" }
+        );
+        Ok(())
+    }
+
+    /// Insure the city against disasters for `insurance_premium` a step
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn buy_insurance(ctx: Context<UpdateCity>) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        require!(!city.insured, CityError::AlreadyInsured);
+        city.insured = true;
+        msg!("City insured against disasters");
+        Ok(())
+    }
+
+    /// Stop paying the insurance premium
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn cancel_insurance(ctx: Context<UpdateCity>) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        require!(city.insured, CityError::NotInsured);
+        city.insured = false;
+        msg!("City insurance cancelled");
+        Ok(())
+    }
+
+    /// Set the utilities and parks funding levels. Police and fire have no
+    /// level here: they are funded per service with `set_funding`.
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn set_budget(ctx: Context<UpdateCity>, budget: Budget) -> Result<()> {
+        budget.validate()?;
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        city.budget = budget;
+
+        msg!("Budget set to {:?}", budget);
+        Ok(())
+    }
+
+    /// Set the residential tax rate in basis points, see `taxes`
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn set_tax_rate(ctx: Context<UpdateCity>, bps: u16) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        city.set_tax_rate(bps)?;
+
+        msg!("Tax rate set to {} bps", bps);
+        Ok(())
+    }
+
+    /// Set per-service funding levels, see `budget`
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn set_funding(ctx: Context<UpdateCity>, funding: ServiceFunding) -> Result<()> {
+        funding.validate()?;
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        city.funding = funding;
+
+        msg!("Funding set to {:?}", funding);
+        Ok(())
+    }
+
+    /// Enable or disable optional simulation mechanics for this city;
+    /// `features::DISASTERS` must stay as it is
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn set_features(ctx: Context<UpdateCity>, features: u32) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        city.set_features(features)?;
+
+        msg!("City features set to {:#x}", features);
+        Ok(())
+    }
+
+    // ========================================
+    // Game Config
+    // ========================================
+
+    /// Create the global config with default balance parameters.
+    /// Only the program's upgrade authority may do this.
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.params = GameParams::default();
+        config.randomness_source = RandomnessMode::default();
+        config.default_features = features::DEFAULT;
+
+        msg!("Game config initialized, admin: {}", config.admin);
+        Ok(())
+    }
+
+    /// Replace the balance parameters
+    pub fn update_config(ctx: Context<UpdateConfig>, params: GameParams) -> Result<()> {
+        params.validate()?;
+        ctx.accounts.config.params = params;
+
+        msg!("Game config updated");
+        Ok(())
+    }
+
+    /// Set the feature flags newly initialized cities start with
+    pub fn set_default_features(ctx: Context<UpdateConfig>, features: u32) -> Result<()> {
+        require!(
+            features & !features::ALL == 0,
+            CityError::InvalidFeatureFlags
+        );
+        ctx.accounts.config.default_features = features;
+        msg!("Default features set to {:#x}", features);
+        Ok(())
+    }
+
+    /// Set the signer randomness oracle answers come from; the default key
+    /// disables `consume_randomness`
+    pub fn set_randomness_oracle(ctx: Context<UpdateConfig>, oracle: Pubkey) -> Result<()> {
+        ctx.accounts.config.randomness_oracle = oracle;
+        msg!("Randomness oracle set to {}", oracle);
+        Ok(())
+    }
+
+    /// Set the source simulation steps draw from; see `randomness`
+    pub fn set_randomness_source(ctx: Context<UpdateConfig>, source: RandomnessMode) -> Result<()> {
+        ctx.accounts.config.randomness_source = source;
+        msg!("Randomness source set to {:?}", source);
+        Ok(())
+    }
+
+    /// Oracle callback: mix the oracle's randomness into the city's seed
+    /// for the steps after its scheduled reseed; see `randomness`
+    pub fn consume_randomness(ctx: Context<ConsumeRandomness>, randomness: [u8; 32]) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        require!(city.randomness_pending, CityError::NoRandomnessRequested);
+        city.reseed(&randomness, &ctx.accounts.config.params);
+        emit!(RandomnessFulfilled {
+            authority: city.authority,
+        });
+        Ok(())
+    }
+
+    // ========================================
+    // MagicBlock Ephemeral Rollups Functions
+    // ========================================
+
+    pub fn delegate(ctx: Context<DelegateInput>) -> Result<()> {
+        ctx.accounts.delegate_pda(
+            &ctx.accounts.payer,
+            &[ctx.accounts.payer.key().as_ref()],
+            DelegateConfig {
+                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+                ..Default::default()
+            },
+        )?;
+        Ok(())
+    }
+
+    pub fn commit(ctx: Context<CommitInput>) -> Result<()> {
+        commit_accounts(
+            &ctx.accounts.payer,
+            vec![&ctx.accounts.city.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        Ok(())
+    }
+
+    pub fn undelegate(ctx: Context<CommitInput>) -> Result<()> {
+        commit_and_undelegate_accounts(
+            &ctx.accounts.payer,
+            vec![&ctx.accounts.city.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        Ok(())
+    }
+
+    // ========================================
+    // Score-only Commits (competitive mode)
+    // ========================================
+
+    /// Create the compact score account that mirrors a city's headline stats
+    pub fn initialize_score(ctx: Context<InitializeScore>) -> Result<()> {
+        let score = &mut ctx.accounts.score;
+        score.authority = ctx.accounts.authority.key();
+        score.refresh(&ctx.accounts.city);
+
+        msg!(
+            "Score account initialized for authority: {}",
+            score.authority
+        );
+        Ok(())
+    }
+
+    pub fn delegate_score(ctx: Context<DelegateScoreInput>) -> Result<()> {
+        ctx.accounts.delegate_pda(
+            &ctx.accounts.payer,
+            &[SCORE_SEED, ctx.accounts.payer.key().as_ref()],
+            DelegateConfig {
+                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+                ..Default::default()
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Refresh the score from the city and commit only the score account,
+    /// leaving the full tile grid in the ER until the city is undelegated
+    pub fn commit_score(ctx: Context<CommitScoreInput>) -> Result<()> {
+        let score = &mut ctx.accounts.score;
+        score.refresh(&ctx.accounts.city);
+        score.exit(&crate::ID)?;
+
+        commit_accounts(
+            &ctx.accounts.payer,
+            vec![&ctx.accounts.score.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        Ok(())
+    }
+
+    // ========================================
+    // Coverage Overlay
+    // ========================================
+
+    /// Create the cached coverage overlay for the signer's city
+    pub fn initialize_coverage(ctx: Context<InitializeCoverage>) -> Result<()> {
+        let overlay = &mut ctx.accounts.coverage;
+        overlay.city = ctx.accounts.city.key();
+        let city = &ctx.accounts.city;
+        overlay.rebuild(city, &city.effective_params(&ctx.accounts.config.params));
+
+        msg!("Coverage overlay initialized for city: {}", overlay.city);
+        Ok(())
+    }
+
+    pub fn delegate_coverage(ctx: Context<DelegateCoverageInput>) -> Result<()> {
+        ctx.accounts.delegate_pda(
+            &ctx.accounts.payer,
+            &[COVERAGE_SEED, ctx.accounts.payer.key().as_ref()],
+            DelegateConfig {
+                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+                ..Default::default()
+            },
+        )?;
+        Ok(())
+    }
+
+    pub fn undelegate_coverage(ctx: Context<UndelegateCoverageInput>) -> Result<()> {
+        commit_and_undelegate_accounts(
+            &ctx.accounts.payer,
+            vec![&ctx.accounts.coverage.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        Ok(())
+    }
+
+    // ========================================
+    // Large Map
+    // ========================================
+
+    /// Open a large map for the signer's city, see `large_map`
+    pub fn initialize_large_map(ctx: Context<InitializeLargeMap>) -> Result<()> {
+        let mut large_map = ctx.accounts.large_map.load_init()?;
+        large_map.city = ctx.accounts.city.key();
+
+        msg!("Large map initialized for city: {}", large_map.city);
+        Ok(())
+    }
+
+    /// Place a building on the city's large map
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn place_on_large_map(
+        ctx: Context<UpdateLargeMap>,
+        x: u16,
+        y: u16,
+        building_type: BuildingType,
+    ) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        let mut large_map = ctx.accounts.large_map.load_mut()?;
+        large_map.place(city, x, y, building_type, &ctx.accounts.config.params)
+    }
+
+    /// Clear a tile of the city's large map
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn bulldoze_large_map(ctx: Context<UpdateLargeMap>, x: u16, y: u16) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        let mut large_map = ctx.accounts.large_map.load_mut()?;
+        large_map.clear(city, x, y, &ctx.accounts.config.params)
+    }
+
+    pub fn delegate_large_map(ctx: Context<DelegateLargeMapInput>) -> Result<()> {
+        ctx.accounts.delegate_pda(
+            &ctx.accounts.payer,
+            &[LARGE_MAP_SEED, ctx.accounts.payer.key().as_ref()],
+            DelegateConfig {
+                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+                ..Default::default()
+            },
+        )?;
+        Ok(())
+    }
+
+    pub fn undelegate_large_map(ctx: Context<UndelegateLargeMapInput>) -> Result<()> {
+        commit_and_undelegate_accounts(
+            &ctx.accounts.payer,
+            vec![&ctx.accounts.large_map.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        Ok(())
+    }
+
+    // ========================================
+    // Chunks
+    // ========================================
+
+    /// Buy chunk (x, y) for the signer's city, see `chunks`. The chunk must
+    /// border the city's own grid or `neighbor`, a chunk the city owns.
+    pub fn expand_map(ctx: Context<ExpandMap>, x: i8, y: i8) -> Result<()> {
+        require!((x, y) != (0, 0), CityError::InvalidChunk);
+        let city_key = ctx.accounts.city.key();
+        let from = match &ctx.accounts.neighbor {
+            Some(neighbor) => {
+                require_keys_eq!(neighbor.city, city_key, CityError::ChunkNotAdjacent);
+                (neighbor.x, neighbor.y)
+            }
+            None => (0, 0),
+        };
+        require!(chunks::borders(from, (x, y)), CityError::ChunkNotAdjacent);
+
+        let city = &mut ctx.accounts.city;
+        require!(!city.bankrupt, CityError::Bankrupt);
+        let price = chunks::expansion_price(city.chunks_owned, &ctx.accounts.config.params);
+        city.spend(price)?;
+        city.chunks_owned = city.chunks_owned.saturating_add(1);
+
+        let chunk = &mut ctx.accounts.chunk;
+        chunk.city = city_key;
+        chunk.x = x;
+        chunk.y = y;
+
+        emit!(MapExpanded {
+            authority: city.authority,
+            x,
+            y,
+            price,
+            chunks_owned: city.chunks_owned,
+        });
+        Ok(())
+    }
+
+    /// Place a building on one of the city's chunks
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn place_on_chunk(
+        ctx: Context<UpdateChunk>,
+        x: u8,
+        y: u8,
+        building_type: BuildingType,
+    ) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        ctx.accounts
+            .chunk
+            .place(city, x, y, building_type, &ctx.accounts.config.params)
+    }
+
+    /// Clear a tile of one of the city's chunks
+    #[session_auth_or(
+        ctx.accounts.city.authority.key() == ctx.accounts.signer.key(),
+        CityError::InvalidAuth
+    )]
+    pub fn bulldoze_chunk(ctx: Context<UpdateChunk>, x: u8, y: u8) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.record_action(Clock::get()?.unix_timestamp)?;
+        ctx.accounts
+            .chunk
+            .clear(city, x, y, &ctx.accounts.config.params)
+    }
+
+    pub fn delegate_chunk(ctx: Context<DelegateChunkInput>, x: i8, y: i8) -> Result<()> {
+        ctx.accounts.delegate_pda(
+            &ctx.accounts.payer,
+            &[
+                CHUNK_SEED,
+                ctx.accounts.city.key().as_ref(),
+                &x.to_le_bytes(),
+                &y.to_le_bytes(),
+            ],
+            DelegateConfig {
+                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+                ..Default::default()
+            },
+        )?;
+        Ok(())
+    }
+
+    pub fn undelegate_chunk(ctx: Context<UndelegateChunkInput>) -> Result<()> {
+        commit_and_undelegate_accounts(
+            &ctx.accounts.payer,
+            vec![&ctx.accounts.chunk.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        Ok(())
+    }
+
+    // ========================================
+    // Achievements
+    // ========================================
+
+    /// Create the signer's achievements account, unlocking anything their
+    /// city already qualifies for
+    pub fn initialize_achievements(ctx: Context<InitializeAchievements>) -> Result<()> {
+        let achievements = &mut ctx.accounts.achievements;
+        achievements.authority = ctx.accounts.authority.key();
+        achievements.record(&ctx.accounts.city);
+
+        msg!(
+            "Achievements initialized for authority: {}",
+            achievements.authority
+        );
+        Ok(())
+    }
+
+    pub fn delegate_achievements(ctx: Context<DelegateAchievementsInput>) -> Result<()> {
+        ctx.accounts.delegate_pda(
+            &ctx.accounts.payer,
+            &[ACHIEVEMENTS_SEED, ctx.accounts.payer.key().as_ref()],
+            DelegateConfig {
+                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+                ..Default::default()
+            },
+        )?;
+        Ok(())
+    }
+
+    pub fn undelegate_achievements(ctx: Context<UndelegateAchievementsInput>) -> Result<()> {
+        commit_and_undelegate_accounts(
+            &ctx.accounts.payer,
+            vec![&ctx.accounts.achievements.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        Ok(())
+    }
+
+    // ========================================
+    // Mayor Profile
+    // ========================================
+
+    /// Create the signer's mayor profile at level 1, see `mayor`
+    pub fn initialize_profile(ctx: Context<InitializeProfile>) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
+        profile.authority = ctx.accounts.authority.key();
+        profile.level = 1;
+
+        msg!(
+            "Mayor profile initialized for authority: {}",
+            profile.authority
+        );
+        Ok(())
+    }
+
+    pub fn delegate_profile(ctx: Context<DelegateProfileInput>) -> Result<()> {
+        ctx.accounts.delegate_pda(
+            &ctx.accounts.payer,
+            &[PROFILE_SEED, ctx.accounts.payer.key().as_ref()],
+            DelegateConfig {
+                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+                ..Default::default()
+            },
+        )?;
+        Ok(())
+    }
+
+    pub fn undelegate_profile(ctx: Context<UndelegateProfileInput>) -> Result<()> {
+        commit_and_undelegate_accounts(
+            &ctx.accounts.payer,
+            vec![&ctx.accounts.profile.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        Ok(())
+    }
+
+    /// Start the city over for a permanent bonus earned by the final score,
+    /// see `prestige`
+    pub fn prestige_city(ctx: Context<PrestigeCity>) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        require!(
+            !city.sandbox && city.scenario_status != ScenarioStatus::InProgress,
+            CityError::PrestigeUnavailable
+        );
+        let score = prestige::score(city);
+        require!(
+            score >= prestige::MIN_PRESTIGE_SCORE,
+            CityError::ScoreTooLow
+        );
+
+        let profile = &mut ctx.accounts.profile;
+        profile.prestige_count = profile.prestige_count.saturating_add(1);
+        profile.prestige_pct = (profile.prestige_pct + prestige::bonus_pct(score))
+            .min(prestige::MAX_PRESTIGE_BONUS_PCT);
+        city.prestige_pct = profile.prestige_pct;
+        city.start_over(Clock::get()?.unix_timestamp);
+
+        emit!(CityPrestiged {
+            authority: city.authority,
+            score,
+            prestige_count: profile.prestige_count,
+            prestige_pct: profile.prestige_pct,
+        });
+        Ok(())
+    }
+
+    pub fn undelegate_score(ctx: Context<CommitScoreInput>) -> Result<()> {
+        let score = &mut ctx.accounts.score;
+        score.refresh(&ctx.accounts.city);
+        score.exit(&crate::ID)?;
+
+        commit_and_undelegate_accounts(
+            &ctx.accounts.payer,
+            vec![&ctx.accounts.score.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        Ok(())
+    }
+
+    // ========================================
+    // Scenarios
+    // ========================================
+
+    /// Publish scenario `id` (config admin only)
+    pub fn create_scenario(
+        ctx: Context<CreateScenario>,
+        id: u32,
+        params: ScenarioParams,
+    ) -> Result<()> {
+        let scenario = &mut ctx.accounts.scenario;
+        scenario.id = id;
+        scenario.params = params;
+
+        msg!("Scenario {} created", id);
+        Ok(())
+    }
+
+    /// Play a scenario on a city that hasn't run a step yet, starting it
+    /// with the scenario's treasury
+    pub fn start_scenario(ctx: Context<StartScenario>) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        require!(
+            city.step_count == 0 && city.scenario_status == ScenarioStatus::None && !city.sandbox,
+            CityError::ScenarioUnavailable
+        );
+        city.money = ctx.accounts.scenario.params.starting_money;
+        city.scenario = ctx.accounts.scenario.key();
+        city.scenario_status = ScenarioStatus::InProgress;
+
+        msg!("Scenario {} started", ctx.accounts.scenario.id);
+        Ok(())
+    }
+
+    /// Mark the city's scenario won or lost once it is decided
+    pub fn check_scenario_completion(ctx: Context<CheckScenario>) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        require!(
+            city.scenario_status == ScenarioStatus::InProgress,
+            CityError::NoScenarioInProgress
+        );
+        let peak_pollution = city.pollution.iter().flatten().copied().max().unwrap_or(0);
+        city.scenario_status = scenario::outcome(
+            &ctx.accounts.scenario.params,
+            city.population,
+            peak_pollution,
+            city.step_count,
+        );
+        if city.scenario_status != ScenarioStatus::InProgress {
+            emit!(ScenarioCompleted {
+                authority: city.authority,
+                scenario: city.scenario,
+                won: city.scenario_status == ScenarioStatus::Won,
+            });
+        }
+        Ok(())
+    }
+
+    // ========================================
+    // Spectators
+    // ========================================
+
+    /// Let `viewer` watch this city. Created on the base layer; the city
+    /// itself may stay delegated.
+    pub fn issue_spectator_pass(ctx: Context<IssueSpectatorPass>, viewer: Pubkey) -> Result<()> {
+        let pass = &mut ctx.accounts.pass;
+        pass.authority = ctx.accounts.authority.key();
+        pass.viewer = viewer;
+        pass.issued_at = Clock::get()?.unix_timestamp;
+
+        msg!("Spectator pass issued to {}", viewer);
+        Ok(())
+    }
+
+    /// Close a pass and refund its rent to the authority
+    pub fn revoke_spectator_pass(_ctx: Context<RevokeSpectatorPass>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Record a view of the city by a pass holder
+    pub fn spectate(ctx: Context<Spectate>) -> Result<()> {
+        let city = &mut ctx.accounts.city;
+        city.spectator_views = city.spectator_views.saturating_add(1);
+        Ok(())
+    }
+}
+
+// ========================================
+// Instruction Args
+// ========================================
+
+/// Versioned `place_building` arguments. New optional parameters are added
+/// as a new variant so older clients keep encoding `V1` unchanged.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaceBuildingArgs {
+    V1 {
+        x: u8,
+        y: u8,
+        building_type: BuildingType,
+    },
+    V2 {
+        x: u8,
+        y: u8,
+        building_type: BuildingType,
+        overwrite: bool,
+    },
+    V3 {
+        x: u8,
+        y: u8,
+        building_type: BuildingType,
+        overwrite: bool,
+        idempotency_key: Option<u64>,
+    },
+}
+
+/// Latest-version view of `PlaceBuildingArgs` that the handler works with
+pub struct PlaceBuilding {
+    pub x: u8,
+    pub y: u8,
+    pub building_type: BuildingType,
+    pub overwrite: bool,
+    pub idempotency_key: Option<u64>,
+}
+
+impl PlaceBuildingArgs {
+    pub fn into_latest(self) -> PlaceBuilding {
+        match self {
+            // V1 always replaced whatever was on the tile
+            PlaceBuildingArgs::V1 {
+                x,
+                y,
+                building_type,
+            } => PlaceBuilding {
+                x,
+                y,
+                building_type,
+                overwrite: true,
+                idempotency_key: None,
+            },
+            PlaceBuildingArgs::V2 {
+                x,
+                y,
+                building_type,
+                overwrite,
+            } => PlaceBuilding {
+                x,
+                y,
+                building_type,
+                overwrite,
+                idempotency_key: None,
+            },
+            PlaceBuildingArgs::V3 {
+                x,
+                y,
+                building_type,
+                overwrite,
+                idempotency_key,
+            } => PlaceBuilding {
+                x,
+                y,
+                building_type,
+                overwrite,
+                idempotency_key,
+            },
+        }
+    }
+}
+
+/// Versioned `bulldoze` arguments
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BulldozeArgs {
+    V1 {
+        x: u8,
+        y: u8,
+    },
+    V2 {
+        x: u8,
+        y: u8,
+        idempotency_key: Option<u64>,
+    },
+}
+
+/// Latest-version view of `BulldozeArgs`
+pub struct Bulldoze {
+    pub x: u8,
+    pub y: u8,
+    pub idempotency_key: Option<u64>,
+}
+
+impl BulldozeArgs {
+    pub fn into_latest(self) -> Bulldoze {
+        match self {
+            BulldozeArgs::V1 { x, y } => Bulldoze {
+                x,
+                y,
+                idempotency_key: None,
+            },
+            BulldozeArgs::V2 {
+                x,
+                y,
+                idempotency_key,
+            } => Bulldoze {
+                x,
+                y,
+                idempotency_key,
+            },
+        }
+    }
+}
+
+/// A single step of a `do_actions` batch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Place {
+        x: u8,
+        y: u8,
+        building_type: BuildingType,
+        overwrite: bool,
+    },
+    Bulldoze {
+        x: u8,
+        y: u8,
+    },
+    /// Residential tax rate in basis points, see `taxes`
+    SetTax {
+        bps: u16,
+    },
+}
+
+/// Return data of `project_steps`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Projection {
+    pub steps: u16,
+    pub population: u32,
+    pub money: u64,
+}
+
+// ========================================
+// Account Structs
+// ========================================
+
+#[derive(Accounts)]
+pub struct InitializeCity<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + City::INIT_SPACE,
+        seeds = [authority.key().as_ref()],
+        bump
+    )]
+    pub city: Box<Account<'info, City>>,
+
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts, Session)]
+pub struct UpdateCity<'info> {
+    #[account(
+        mut,
+        seeds = [city.authority.key().as_ref()],
+        bump
+    )]
+    pub city: Box<Account<'info, City>>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[session(signer = signer, authority = city.authority.key())]
+    pub session_token: Option<Account<'info, SessionToken>>,
+
+    /// CHECK: SlotHashes sysvar, validated when the config draws from it
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
+
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, GameConfig>,
+
+    /// Cached coverage, maintained incrementally when supplied
+    #[account(mut, seeds = [COVERAGE_SEED, city.authority.key().as_ref()], bump)]
+    pub coverage: Option<Account<'info, CoverageOverlay>>,
+
+    /// The player's achievements, updated when supplied
+    #[account(mut, seeds = [ACHIEVEMENTS_SEED, city.authority.key().as_ref()], bump)]
+    pub achievements: Option<Account<'info, Achievements>>,
+
+    /// The player's mayor profile, credited with experience when supplied
+    #[account(mut, seeds = [PROFILE_SEED, city.authority.key().as_ref()], bump)]
+    pub profile: Option<Account<'info, MayorProfile>>,
+}
+
+impl UpdateCity<'_> {
+    /// Run the simulation steps due by `now`, at most `MAX_CATCH_UP_STEPS`,
+    /// and return how many ran. Random events draw from the config's
+    /// `randomness_source`; a deployment on SlotHashes needs the sysvar
+    /// supplied.
+    pub fn catch_up(&mut self, now: i64) -> Result<i64> {
+        let params = &self.config.params;
+        let city = &mut self.city;
+        if city.expire_randomness(params, now) {
+            emit!(RandomnessTimedOut {
+                authority: city.authority,
+            });
+        }
+        let due = city.steps_due(params, now);
+        if due == 0 {
+            return Ok(0);
+        }
+        let steps = due.min(MAX_CATCH_UP_STEPS);
+        let interval = params.step_interval_seconds.max(1) as i64;
+        let start = city.last_updated;
+        let oracle = self.config.randomness_oracle != Pubkey::default();
+
+        let mut slot_rng = randomness::slot_source(
+            self.config.randomness_source,
+            self.slot_hashes.as_ref().map(|account| account.as_ref()),
+            city,
+        )?;
+        let mut ran = 0;
+        for step in 1..=steps {
+            // The rest of the steps wait for the scheduled reseed
+            if oracle && !city.randomness_pending && city.reseed_due(params) {
+                city.randomness_pending = true;
+                city.randomness_requested_at = now;
+                emit!(RandomnessRequested {
+                    authority: city.authority,
+                    city: city.key(),
+                    seed: city.rng_seed ^ city.step_count,
+                });
+                break;
+            }
+            let rng = slot_rng.as_mut().map(|r| r as &mut dyn RandomnessSource);
+            // Steps can change the layout, so the overlay catches up each time
+            let cached_coverage = self.coverage.as_mut().map(|overlay| {
+                overlay.sync(city, &city.effective_params(params));
+                overlay.bitmap()
+            });
+            simulation::run_step(city, params, start + step * interval, rng, cached_coverage);
+            ran = step;
+        }
+        // Time away beyond the cap is forfeited
+        if due > steps && !city.randomness_pending {
+            city.last_updated = now;
+        }
+        // Leave the overlay current for the next placement
+        self.sync_coverage();
+        self.track_achievements();
+        self.award_xp(ran as u64 * XP_PER_STEP);
+        Ok(ran)
+    }
+
+    /// Credit the mayor profile (if supplied) with `xp`. Sandbox cities
+    /// earn none.
+    pub fn award_xp(&mut self, xp: u64) {
+        let city = &self.city;
+        if city.sandbox {
+            return;
+        }
+        if let Some(profile) = self.profile.as_mut() {
+            if let Some(level) = profile.award(xp) {
+                emit!(MayorLeveledUp {
+                    authority: city.authority,
+                    level,
+                });
+            }
+        }
+    }
+
+    /// Add the mayor level's share on top of a `reward` just paid
+    pub fn pay_level_bonus(&mut self, reward: u64) {
+        if let Some(profile) = self.profile.as_ref() {
+            let bonus = reward * (profile.reward_pct() - 100) / 100;
+            self.city.money = self.city.money.saturating_add(bonus);
+        }
+    }
+
+    /// Unlock achievements (if the account is supplied) the city now
+    /// qualifies for
+    pub fn track_achievements(&mut self) {
+        let city = &self.city;
+        if let Some(achievements) = self.achievements.as_mut() {
+            let unlocked = achievements.record(city);
+            if unlocked != 0 {
+                emit!(AchievementsUnlocked {
+                    authority: city.authority,
+                    achievements: unlocked,
+                });
+            }
+        }
+    }
+
+    /// Bring the coverage overlay (if supplied) up to the city's layout
+    pub fn sync_coverage(&mut self) {
+        if let Some(overlay) = self.coverage.as_mut() {
+            overlay.sync(&self.city, &self.city.effective_params(&self.config.params));
+        }
+    }
+}
+
+/// Read-only access to a city for view instructions
+#[derive(Accounts)]
+pub struct ViewCity<'info> {
+    #[account(seeds = [city.authority.key().as_ref()], bump)]
+    pub city: Box<Account<'info, City>>,
+
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, GameConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ViewUnlocks {}
+
+#[derive(Accounts)]
+pub struct InitializeCoverage<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + CoverageOverlay::INIT_SPACE,
+        seeds = [COVERAGE_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub coverage: Box<Account<'info, CoverageOverlay>>,
+
+    #[account(seeds = [authority.key().as_ref()], bump)]
+    pub city: Box<Account<'info, City>>,
+
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[delegate]
+#[derive(Accounts)]
+pub struct DelegateCoverageInput<'info> {
+    pub payer: Signer<'info>,
+    /// CHECK: The coverage PDA to delegate - validated by seeds constraint
+    #[account(mut, del, seeds = [COVERAGE_SEED, payer.key().as_ref()], bump)]
+    pub pda: AccountInfo<'info>,
+}
+
+#[commit]
+#[derive(Accounts)]
+pub struct UndelegateCoverageInput<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, seeds = [COVERAGE_SEED, payer.key().as_ref()], bump)]
+    pub coverage: Box<Account<'info, CoverageOverlay>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeLargeMap<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<LargeMap>(),
+        seeds = [LARGE_MAP_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub large_map: AccountLoader<'info, LargeMap>,
+
+    #[account(seeds = [authority.key().as_ref()], bump)]
+    pub city: Box<Account<'info, City>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts, Session)]
+pub struct UpdateLargeMap<'info> {
+    #[account(mut, seeds = [city.authority.key().as_ref()], bump)]
+    pub city: Box<Account<'info, City>>,
+
+    #[account(mut, seeds = [LARGE_MAP_SEED, city.authority.key().as_ref()], bump)]
+    pub large_map: AccountLoader<'info, LargeMap>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[session(signer = signer, authority = city.authority.key())]
+    pub session_token: Option<Account<'info, SessionToken>>,
+
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, GameConfig>,
+}
+
+#[delegate]
+#[derive(Accounts)]
+pub struct DelegateLargeMapInput<'info> {
+    pub payer: Signer<'info>,
+    /// CHECK: The large map PDA to delegate - validated by seeds constraint
+    #[account(mut, del, seeds = [LARGE_MAP_SEED, payer.key().as_ref()], bump)]
+    pub pda: AccountInfo<'info>,
+}
+
+#[commit]
+#[derive(Accounts)]
+pub struct UndelegateLargeMapInput<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, seeds = [LARGE_MAP_SEED, payer.key().as_ref()], bump)]
+    pub large_map: AccountLoader<'info, LargeMap>,
+}
+
+#[derive(Accounts)]
+#[instruction(x: i8, y: i8)]
+pub struct ExpandMap<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Chunk::INIT_SPACE,
+        seeds = [CHUNK_SEED, city.key().as_ref(), &x.to_le_bytes(), &y.to_le_bytes()],
+        bump
+    )]
+    pub chunk: Account<'info, Chunk>,
+
+    #[account(mut, seeds = [authority.key().as_ref()], bump)]
+    pub city: Box<Account<'info, City>>,
+
+    /// Owned chunk the new one borders, when it doesn't border the city's
+    /// own grid
+    pub neighbor: Option<Account<'info, Chunk>>,
+
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts, Session)]
+pub struct UpdateChunk<'info> {
+    #[account(mut, seeds = [city.authority.key().as_ref()], bump)]
+    pub city: Box<Account<'info, City>>,
+
+    #[account(
+        mut,
+        seeds = [
+            CHUNK_SEED,
+            city.key().as_ref(),
+            &chunk.x.to_le_bytes(),
+            &chunk.y.to_le_bytes()
+        ],
+        bump
+    )]
+    pub chunk: Account<'info, Chunk>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[session(signer = signer, authority = city.authority.key())]
+    pub session_token: Option<Account<'info, SessionToken>>,
+
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, GameConfig>,
+}
+
+#[delegate]
+#[derive(Accounts)]
+#[instruction(x: i8, y: i8)]
+pub struct DelegateChunkInput<'info> {
+    pub payer: Signer<'info>,
+    /// CHECK: The payer's city, only its address seeds the chunk
+    #[account(seeds = [payer.key().as_ref()], bump)]
+    pub city: UncheckedAccount<'info>,
+    /// CHECK: The chunk PDA to delegate - validated by seeds constraint
+    #[account(
+        mut,
+        del,
+        seeds = [CHUNK_SEED, city.key().as_ref(), &x.to_le_bytes(), &y.to_le_bytes()],
+        bump
+    )]
+    pub pda: AccountInfo<'info>,
+}
+
+#[commit]
+#[derive(Accounts)]
+pub struct UndelegateChunkInput<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: The payer's city, only its address seeds the chunk
+    #[account(seeds = [payer.key().as_ref()], bump)]
+    pub city: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            CHUNK_SEED,
+            city.key().as_ref(),
+            &chunk.x.to_le_bytes(),
+            &chunk.y.to_le_bytes()
+        ],
+        bump
+    )]
+    pub chunk: Account<'info, Chunk>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAchievements<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Achievements::INIT_SPACE,
+        seeds = [ACHIEVEMENTS_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub achievements: Account<'info, Achievements>,
+
+    #[account(seeds = [authority.key().as_ref()], bump)]
+    pub city: Box<Account<'info, City>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[delegate]
+#[derive(Accounts)]
+pub struct DelegateAchievementsInput<'info> {
+    pub payer: Signer<'info>,
+    /// CHECK: The achievements PDA to delegate - validated by seeds constraint
+    #[account(mut, del, seeds = [ACHIEVEMENTS_SEED, payer.key().as_ref()], bump)]
+    pub pda: AccountInfo<'info>,
+}
+
+#[commit]
+#[derive(Accounts)]
+pub struct UndelegateAchievementsInput<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, seeds = [ACHIEVEMENTS_SEED, payer.key().as_ref()], bump)]
+    pub achievements: Account<'info, Achievements>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProfile<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MayorProfile::INIT_SPACE,
+        seeds = [PROFILE_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, MayorProfile>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[delegate]
+#[derive(Accounts)]
+pub struct DelegateProfileInput<'info> {
+    pub payer: Signer<'info>,
+    /// CHECK: The profile PDA to delegate - validated by seeds constraint
+    #[account(mut, del, seeds = [PROFILE_SEED, payer.key().as_ref()], bump)]
+    pub pda: AccountInfo<'info>,
+}
+
+#[commit]
+#[derive(Accounts)]
+pub struct UndelegateProfileInput<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, seeds = [PROFILE_SEED, payer.key().as_ref()], bump)]
+    pub profile: Account<'info, MayorProfile>,
+}
+
+#[derive(Accounts)]
+pub struct PrestigeCity<'info> {
+    #[account(mut, seeds = [authority.key().as_ref()], bump)]
+    pub city: Box<Account<'info, City>>,
+
+    #[account(mut, seeds = [PROFILE_SEED, authority.key().as_ref()], bump)]
+    pub profile: Account<'info, MayorProfile>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + GameConfig::INIT_SPACE,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::SimcityBuild>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ CityError::InvalidAuth)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConsumeRandomness<'info> {
+    #[account(mut, seeds = [city.authority.key().as_ref()], bump)]
+    pub city: Box<Account<'info, City>>,
+
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(
+        constraint = oracle.key() != Pubkey::default()
+            && oracle.key() == config.randomness_oracle
+            @ CityError::InvalidRandomnessSource
+    )]
+    pub oracle: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut, seeds = [CONFIG_SEED], bump, has_one = admin @ CityError::InvalidAuth)]
+    pub config: Account<'info, GameConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+#[delegate]
+#[derive(Accounts)]
+pub struct DelegateInput<'info> {
+    pub payer: Signer<'info>,
+    /// CHECK: The PDA to delegate - validated by seeds constraint
+    #[account(mut, del, seeds = [payer.key().as_ref()], bump)]
+    pub pda: AccountInfo<'info>,
+}
+
+#[commit]
+#[derive(Accounts)]
+pub struct CommitInput<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, seeds = [payer.key().as_ref()], bump)]
+    pub city: Box<Account<'info, City>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeScore<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + CityScore::INIT_SPACE,
+        seeds = [SCORE_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub score: Account<'info, CityScore>,
+
+    #[account(
+        seeds = [authority.key().as_ref()],
+        bump,
+        constraint = !city.sandbox @ CityError::SandboxCity
+    )]
+    pub city: Box<Account<'info, City>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[delegate]
+#[derive(Accounts)]
+pub struct DelegateScoreInput<'info> {
+    pub payer: Signer<'info>,
+    /// CHECK: The score PDA to delegate - validated by seeds constraint
+    #[account(mut, del, seeds = [SCORE_SEED, payer.key().as_ref()], bump)]
+    pub pda: AccountInfo<'info>,
+}
+
+#[commit]
+#[derive(Accounts)]
+pub struct CommitScoreInput<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [payer.key().as_ref()],
+        bump,
+        constraint = !city.sandbox @ CityError::SandboxCity
+    )]
+    pub city: Box<Account<'info, City>>,
+    #[account(mut, seeds = [SCORE_SEED, payer.key().as_ref()], bump)]
+    pub score: Account<'info, CityScore>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u32)]
+pub struct CreateScenario<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Scenario::INIT_SPACE,
+        seeds = [SCENARIO_SEED, id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub scenario: Account<'info, Scenario>,
+
+    #[account(seeds = [CONFIG_SEED], bump, has_one = admin @ CityError::InvalidAuth)]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StartScenario<'info> {
+    #[account(mut, seeds = [authority.key().as_ref()], bump, has_one = authority)]
+    pub city: Box<Account<'info, City>>,
+
+    #[account(seeds = [SCENARIO_SEED, scenario.id.to_le_bytes().as_ref()], bump)]
+    pub scenario: Account<'info, Scenario>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Permissionless: the outcome only depends on the city and its scenario
+#[derive(Accounts)]
+pub struct CheckScenario<'info> {
+    #[account(mut, seeds = [city.authority.key().as_ref()], bump)]
+    pub city: Box<Account<'info, City>>,
+
+    #[account(address = city.scenario @ CityError::NoScenarioInProgress)]
+    pub scenario: Account<'info, Scenario>,
+}
+
+#[derive(Accounts)]
+#[instruction(viewer: Pubkey)]
+pub struct IssueSpectatorPass<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SpectatorPass::INIT_SPACE,
+        seeds = [SPECTATOR_SEED, authority.key().as_ref(), viewer.as_ref()],
+        bump
+    )]
+    pub pass: Account<'info, SpectatorPass>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSpectatorPass<'info> {
+    #[account(
+        mut,
+        close = authority,
+        has_one = authority,
+        seeds = [SPECTATOR_SEED, authority.key().as_ref(), pass.viewer.as_ref()],
+        bump
+    )]
+    pub pass: Account<'info, SpectatorPass>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// Pass holders only get to bump the view counter; no other city field is
+/// reachable from here
+#[derive(Accounts)]
+pub struct Spectate<'info> {
+    #[account(mut, seeds = [city.authority.key().as_ref()], bump)]
+    pub city: Box<Account<'info, City>>,
+
+    #[account(
+        seeds = [SPECTATOR_SEED, city.authority.key().as_ref(), viewer.key().as_ref()],
+        bump,
+        has_one = viewer
+    )]
+    pub pass: Account<'info, SpectatorPass>,
+
+    pub viewer: Signer<'info>,
+}
+
+// ========================================
+// Account Data
+// ========================================
+
+#[account]
+#[derive(InitSpace)]
+pub struct City {
+    pub tiles: [[u8; 16]; 16], // 16x16 grid = 256 bytes
+    pub population: u32,
+    pub money: u64,
+    pub last_updated: i64,
+    pub authority: Pubkey,
+    pub action_window_start: i64,
+    pub actions_in_window: u16,
+    pub recent_idempotency_keys: [u64; IDEMPOTENCY_KEY_SLOTS],
+    pub next_idempotency_slot: u8,
+    pub features: u32,
+    /// Road component id per tile (0 = not connected), see `network`
+    pub road_network: [[u8; 16]; 16],
+    /// Bumped on every tile change so cached overlays can detect staleness
+    pub layout_version: u32,
+    /// 0-100, recomputed each step from jobs, services, recreation,
+    /// pollution, taxes, crime, homelessness and garbage
+    pub happiness: u8,
+    /// Steps until each burning tile burns down (0 = not burning), see `fire`
+    pub fires: [[u8; 16]; 16],
+    /// Active crime scenes, bit x of row y
+    pub crime_scenes: [u16; 16],
+    /// Residents without housing, recomputed each step
+    pub homeless: u32,
+    /// Tiles locked against demolition, bit x of row y
+    pub protected: [u16; 16],
+    /// Views recorded by spectator pass holders
+    pub spectator_views: u64,
+    /// Set when the last step's building upkeep couldn't be paid in full;
+    /// service buildings stay shut down until a step pays it
+    pub upkeep_unpaid: bool,
+    /// Department funding levels set by the mayor, see `budget`
+    pub budget: Budget,
+    /// Outstanding loan, including unpaid interest, and its rate per step
+    pub loan_principal: u64,
+    pub loan_rate_bps: u16,
+    /// Consecutive steps whose loan interest went unpaid
+    pub missed_loan_payments: u8,
+    /// Set after too many missed payments; cleared by repaying the loan
+    pub in_default: bool,
+    /// Jobs offered by connected workplaces at the last step
+    pub jobs: u32,
+    /// Commercial plus industrial income of the last step
+    pub commerce_revenue: u64,
+    /// Power, water and goods totals of the last step, see `resources`
+    pub resources: Resources,
+    /// Tiles reached by power at the last step, bit x of row y, see `power`
+    pub powered: [u16; 16],
+    /// Residential and commercial tiles water didn't reach at the last
+    /// step, bit x of row y, see `water`
+    pub dry: [u16; 16],
+    /// Trips per road tile at the last step, see `traffic`
+    pub traffic: [[u8; 16]; 16],
+    /// Average road congestion at the last step, 0-100
+    pub congestion: u8,
+    /// `RoadTier` code per road tile, see `roads`
+    pub road_tiers: [[u8; 16]; 16],
+    /// Steps until each construction site starts working (0 = finished)
+    pub construction: [[u8; 16]; 16],
+    /// Wear of each building, `MAX_CONDITION` when new; homes and
+    /// workplaces produce in proportion, and at 0 the building is ruins
+    pub condition: [[u8; 16]; 16],
+    /// Zone code per tile (0 = unzoned), see `zoning`
+    pub zoning: [[u8; 16]; 16],
+    /// `Density` code per zone building, see `density`
+    pub density: [[u8; 16]; 16],
+    /// Land value per tile at the last step, in percent of a baseline plot
+    /// (0 = not assessed yet), see `land_value`
+    pub land_value: [[u8; 16]; 16],
+    /// Air pollution level per tile, see `pollution`
+    pub pollution: [[u8; 16]; 16],
+    /// Garbage piled up uncollected, see `garbage`
+    pub garbage: u32,
+    /// City-wide crime level at the last step, 0-100, see `crime`
+    pub crime: u8,
+    /// Citizen health at the last step, 0-100, see `health`
+    pub health: u8,
+    /// Education level at the last step, 0-100, see `education`
+    pub education: u8,
+    /// Residential, commercial and industrial demand at the last step, see
+    /// `demand`
+    pub demand: Demand,
+    /// Residents of working age at the last step; compare with `jobs`
+    pub workforce: u32,
+    /// Seed of the city's deterministic event generator, and the number of
+    /// steps simulated so far; see `randomness`
+    pub rng_seed: u64,
+    pub step_count: u64,
+    /// A scheduled reseed awaits the randomness oracle's answer
+    pub randomness_pending: bool,
+    /// Paying the insurance premium, see `disaster`
+    pub insured: bool,
+    /// Season of the last step, see `season`
+    pub season: Season,
+    /// Time of day of the last step, see `day_night`
+    pub day_phase: DayPhase,
+    /// Scenario being played, see `scenario`, and how it stands; the
+    /// default key in free play
+    pub scenario: Pubkey,
+    pub scenario_status: ScenarioStatus,
+    /// Creative play: nothing costs money, no disasters and no score
+    pub sandbox: bool,
+    /// Chosen at creation, see `difficulty`
+    pub difficulty: Difficulty,
+    /// Chosen at creation, see `specialization`
+    pub specialization: Specialization,
+    /// Learned techs, bit `tech as u8` each, see `research`
+    pub techs: u32,
+    /// Tech being researched and the steps until it is learned
+    pub researching: Option<Tech>,
+    pub research_steps_left: u8,
+    /// Milestones reached and those whose reward was collected, bit
+    /// `index` into `milestones::MILESTONES` each
+    pub milestones_reached: u32,
+    pub milestones_claimed: u32,
+    /// Day of the residential tax tally towards daily quests, and the
+    /// tally, see `quests`
+    pub quest_day: u32,
+    pub quest_tax: u64,
+    /// Last day a quest reward was collected
+    pub quest_claimed_day: Option<u32>,
+    /// Income and starting treasury bonus earned by earlier runs, in
+    /// percent, see `prestige`
+    pub prestige_pct: u16,
+    /// Mayor's approval rating at the last step, 0-100, and whether the
+    /// city is in caretaker mode after a lost election, see `elections`
+    pub approval: u8,
+    pub caretaker: bool,
+    /// Bills the treasury couldn't cover, and whether they pushed the city
+    /// into bankruptcy, see `bankruptcy`
+    pub overdraft: u64,
+    pub bankrupt: bool,
+    /// Ordinances in force, bit `ordinance as u8` each, see `ordinances`
+    pub ordinances: u32,
+    /// Funding level per service, see `budget`
+    pub funding: ServiceFunding,
+    /// Residential tax rate in basis points, see `taxes`
+    pub tax_rate_bps: u16,
+    /// Chunks bought with `expand_map`, see `chunks`
+    pub chunks_owned: u16,
+    /// `Terrain` code per tile, under the buildings, see `terrain`
+    pub terrain: [[u8; 16]; 16],
+    /// Seed the terrain was generated from, see `procgen`
+    pub terrain_seed: u64,
+    /// Height of each tile, see `elevation`
+    pub elevation: [[u8; 16]; 16],
+    /// Price paid for each building, on its anchor tile; bulldozing refunds
+    /// `refund_pct` of it
+    pub paid: [[u32; 16]; 16],
+    /// Reseeds done, one per `reseed_interval_steps` steps, and when the
+    /// pending one was requested; see `randomness`
+    pub reseeds: u64,
+    pub randomness_requested_at: i64,
+}
+
+impl City {
+    pub fn has_feature(&self, flag: u32) -> bool {
+        self.features & flag != 0
+    }
+
+    /// Switch the optional mechanics, keeping `features::DISASTERS` as the
+    /// config set it
+    pub fn set_features(&mut self, features: u32) -> Result<()> {
+        require!(
+            features & !features::ALL == 0,
+            CityError::InvalidFeatureFlags
+        );
+        require!(
+            (features ^ self.features) & features::DISASTERS == 0,
+            CityError::DisastersLocked
+        );
+        self.features = features;
+        Ok(())
+    }
+
+    /// Set the residential tax rate, see `taxes`
+    pub fn set_tax_rate(&mut self, bps: u16) -> Result<()> {
+        require!(bps <= taxes::MAX_TAX_RATE_BPS, CityError::InvalidTaxRate);
+        self.tax_rate_bps = bps;
+        Ok(())
+    }
+
+    /// Treasury minus what the city owes: its loan and unpaid bills
+    pub fn net_worth(&self) -> u64 {
+        self.money
+            .saturating_sub(self.loan_principal)
+            .saturating_sub(self.overdraft)
+    }
+
+    /// `params` as they apply to this city: scaled by its service funding
+    /// and difficulty. The simulation and the coverage overlay both
+    /// read these, so the overlay's radii match the step's.
+    pub fn effective_params(&self, params: &GameParams) -> GameParams {
+        self.difficulty.apply(&self.funding.apply(params))
+    }
+
+    /// Whether a scheduled reseed is waiting on the oracle and hasn't
+    /// timed out yet
+    pub fn awaiting_randomness(&self, params: &GameParams, now: i64) -> bool {
+        self.randomness_pending
+            && now < self.randomness_requested_at + params.randomness_timeout_seconds as i64
+    }
+
+    /// Whether the next step must wait for a reseed first
+    pub fn reseed_due(&self, params: &GameParams) -> bool {
+        params.reseed_interval_steps > 0
+            && self.step_count / params.reseed_interval_steps as u64 > self.reseeds
+    }
+
+    /// Mark the reseed due now as done, with or without the oracle's answer
+    fn finish_reseed(&mut self, params: &GameParams) {
+        self.randomness_pending = false;
+        self.reseeds = self
+            .step_count
+            .checked_div(params.reseed_interval_steps as u64)
+            .unwrap_or(self.reseeds);
+    }
+
+    /// Mix the oracle's answer into the seed the next steps roll from
+    pub fn reseed(&mut self, randomness: &[u8; 32], params: &GameParams) {
+        self.rng_seed = randomness::mix_seed(self.rng_seed, randomness);
+        self.finish_reseed(params);
+    }
+
+    /// Give up on a request the oracle left unanswered past the timeout;
+    /// true when one lapsed
+    pub fn expire_randomness(&mut self, params: &GameParams, now: i64) -> bool {
+        if !self.randomness_pending || self.awaiting_randomness(params, now) {
+            return false;
+        }
+        self.finish_reseed(params);
+        true
+    }
+
+    /// Simulation steps elapsed since the last one ran, see
+    /// `step_simulation`. None are due while a reseed waits on the oracle,
+    /// so the wait rolls nothing; see `randomness`.
+    pub fn steps_due(&self, params: &GameParams, now: i64) -> i64 {
+        if self.awaiting_randomness(params, now) {
+            return 0;
+        }
+        let interval = params.step_interval_seconds.max(1) as i64;
+        (now - self.last_updated).max(0) / interval
+    }
+
+    /// Count a mutating action against the per-window rate cap
+    pub fn record_action(&mut self, now: i64) -> Result<()> {
+        if now - self.action_window_start >= ACTION_WINDOW_SECONDS {
+            self.action_window_start = now;
+            self.actions_in_window = 0;
+        }
+        require!(
+            self.actions_in_window < MAX_ACTIONS_PER_WINDOW,
+            CityError::RateLimited
+        );
+        self.actions_in_window += 1;
+        Ok(())
+    }
+
+    /// Apply a `do_actions` batch all or nothing: the actions run on a copy
+    /// that replaces the city only once every one of them has succeeded.
+    /// Returns how many buildings were placed.
+    pub fn apply_actions(
+        &mut self,
+        actions: &[Action],
+        now: i64,
+        params: &GameParams,
+    ) -> Result<u32> {
+        let mut staged = self.clone();
+        let mut placed = 0;
+        for action in actions.iter() {
+            staged.record_action(now)?;
+            match *action {
+                Action::Place {
+                    x,
+                    y,
+                    building_type,
+                    overwrite,
+                } => {
+                    staged.place(x, y, building_type, overwrite, params)?;
+                    placed += 1;
+                }
+                Action::Bulldoze { x, y } => staged.clear(x, y, params)?,
+                Action::SetTax { bps } => staged.set_tax_rate(bps)?,
+            }
+        }
+        *self = staged;
+        Ok(placed)
+    }
+
+    /// Place a building on a tile and charge for it
+    pub fn place(
+        &mut self,
+        x: u8,
+        y: u8,
+        building_type: BuildingType,
+        overwrite: bool,
+        params: &GameParams,
+    ) -> Result<()> {
+        require!(!self.in_default, CityError::LoanInDefault);
+        require!(!self.bankrupt, CityError::Bankrupt);
+        let cost = self.placement_cost(x, y, building_type, params)?;
+        require!(
+            !building_type.is_landmark() || !self.has_landmark_elsewhere(x, y, building_type),
+            CityError::LandmarkAlreadyBuilt
+        );
+        require!(
+            self.education >= building_type.required_education(),
+            CityError::EducationTooLow
+        );
+        require!(
+            self.unlocked(building_type.required_tech()),
+            CityError::TechRequired
+        );
+        require!(
+            unlocks::tier_of(self.population) >= building_type.required_tier(),
+            CityError::BuildingLocked
+        );
+        let size = building_type.footprint() as usize;
+        require!(
+            x as usize + size <= GRID_SIZE && y as usize + size <= GRID_SIZE,
+            CityError::OutOfBounds
+        );
+        // Only single-tile buildings can be overwritten, and only by another
+        // single-tile building
+        for (tx, ty) in footprint_tiles(x as usize, y as usize, size) {
+            let tile = self.tiles[ty][tx];
+            let replaceable = overwrite
+                && size == 1
+                && tile != FOOTPRINT_TILE
+                && buildings::tile_type(tile).footprint() == 1;
+            require!(tile == 0 || replaceable, CityError::TileOccupied);
+            require!(!self.is_protected(tx, ty), CityError::TileProtected);
+            require!(
+                terrain::terrain_of(self.terrain[ty][tx]).allows(building_type),
+                CityError::TerrainBlocked
+            );
+        }
+        require!(
+            !building_type.is_coastal()
+                || coastal::borders_water(&self.terrain, x as usize, y as usize, size),
+            CityError::NotCoastal
+        );
+        require!(
+            !building_type.needs_road_access()
+                || footprint_tiles(x as usize, y as usize, size)
+                    .any(|(tx, ty)| self.has_road_access(tx, ty)),
+            CityError::NotConnectedToRoad
+        );
+        let construction_steps = if building_type.is_infrastructure() {
+            0
+        } else {
+            params.construction_steps
+        };
+        for (tx, ty) in footprint_tiles(x as usize, y as usize, size) {
+            if terrain::terrain_of(self.terrain[ty][tx]) == Terrain::Forest {
+                self.terrain[ty][tx] = Terrain::Grass.into();
+            }
+            self.tiles[ty][tx] = FOOTPRINT_TILE;
+            self.fires[ty][tx] = 0;
+            self.road_tiers[ty][tx] = 0;
+            self.construction[ty][tx] = construction_steps;
+            self.condition[ty][tx] = MAX_CONDITION;
+            self.density[ty][tx] = 0;
+            self.set_crime_scene(tx, ty, false);
+        }
+        // A highway is a road built at the top tier, see `roads`
+        if building_type == BuildingType::Highway {
+            self.tiles[y as usize][x as usize] = network::ROAD_TILE;
+            self.road_tiers[y as usize][x as usize] = RoadTier::Highway as u8;
+        } else {
+            self.tiles[y as usize][x as usize] = building_type.into();
+        }
+        self.on_layout_changed();
+
+        if let Err(err) = self.spend(cost) {
+            msg!("Placement costs {}, treasury has {}", cost, self.money);
+            return Err(err);
+        }
+        // Sandbox cities build for free, so there is nothing to refund
+        self.paid[y as usize][x as usize] = if self.sandbox {
+            0
+        } else {
+            u32::try_from(cost).unwrap_or(u32::MAX)
+        };
+
+        msg!("Placed {:?} at ({}, {})", building_type, x, y);
+        Ok(())
+    }
+
+    /// Tile code at (x, y)
+    pub fn tile(&self, x: u8, y: u8) -> Result<u8> {
+        require!(x < 16 && y < 16, CityError::OutOfBounds);
+        Ok(self.tiles[y as usize][x as usize])
+    }
+
+    pub fn is_protected(&self, x: usize, y: usize) -> bool {
+        self.protected[y] & (1 << x) != 0
+    }
+
+    /// Lock or unlock a tile against bulldozing and overwriting
+    pub fn set_protected(&mut self, x: u8, y: u8, protected: bool) -> Result<()> {
+        require!(x < 16 && y < 16, CityError::OutOfBounds);
+        if protected {
+            self.protected[y as usize] |= 1 << x;
+        } else {
+            self.protected[y as usize] &= !(1 << x);
+        }
+        Ok(())
+    }
+
+    pub fn is_under_construction(&self, x: usize, y: usize) -> bool {
+        self.construction[y][x] > 0
+    }
+
+    pub fn has_crime_scene(&self, x: usize, y: usize) -> bool {
+        self.crime_scenes[y] & (1 << x) != 0
+    }
+
+    pub fn set_crime_scene(&mut self, x: usize, y: usize, active: bool) {
+        if active {
+            self.crime_scenes[y] |= 1 << x;
+        } else {
+            self.crime_scenes[y] &= !(1 << x);
+        }
+    }
+
+    /// Pay to resolve the fire or crime at (x, y) right away. Needs a
+    /// station of the matching kind somewhere in the city.
+    pub fn dispatch_emergency(&mut self, x: u8, y: u8, cost: u64) -> Result<()> {
+        require!(x < 16 && y < 16, CityError::OutOfBounds);
+        let (x, y) = (x as usize, y as usize);
+        let station = if self.fires[y][x] > 0 {
+            FIRE_STATION_TILE
+        } else if self.has_crime_scene(x, y) {
+            POLICE_STATION_TILE
+        } else {
+            return err!(CityError::NoActiveIncident);
+        };
+        require!(
+            self.tiles.iter().flatten().any(|&tile| tile == station),
+            CityError::NoStationAvailable
+        );
+        self.spend(cost)?;
+
+        if station == FIRE_STATION_TILE {
+            self.fires[y][x] = 0;
+        } else {
+            self.set_crime_scene(x, y, false);
+        }
+        msg!("Emergency at ({}, {}) resolved", x, y);
+        Ok(())
+    }
+
+    /// Refresh derived layout state after a tile change
+    pub fn on_layout_changed(&mut self) {
+        self.road_network = network::compute_road_network(&self.tiles);
+        self.layout_version = self.layout_version.wrapping_add(1);
+    }
+
+    /// True when a road tile borders (x, y)
+    pub fn has_road_access(&self, x: usize, y: usize) -> bool {
+        network::neighbors(x, y).any(|(nx, ny)| network::is_road(self.tiles[ny][nx]))
+    }
+
+    /// True when the tile is a road or a building touching a road
+    pub fn is_connected(&self, x: usize, y: usize) -> bool {
+        self.road_network[y][x] != 0
+    }
+
+    /// True when both tiles sit on the same road network
+    pub fn same_network(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        let id = self.road_network[a.1][a.0];
+        id != 0 && id == self.road_network[b.1][b.0]
+    }
+
+    /// Exact price `place` charges for this placement. Every cost modifier
+    /// must go through here so `quote_placement` never disagrees with it.
+    pub fn placement_cost(
+        &self,
+        x: u8,
+        y: u8,
+        building_type: BuildingType,
+        params: &GameParams,
+    ) -> Result<u64> {
+        require!(x < 16 && y < 16, CityError::OutOfBounds);
+        let base_cost = building_type
+            .base_cost()
+            .ok_or(CityError::InvalidBuildingType)?;
+        let land_value_pct = if building_type.is_infrastructure() {
+            100
+        } else {
+            land_value::value_pct(self.land_value[y as usize][x as usize])
+        };
+        let clearing_cost = terrain::clearing_cost(
+            &self.terrain,
+            x as usize,
+            y as usize,
+            building_type.footprint() as usize,
+            params,
+        );
+        let slope_cost = elevation::slope_cost(
+            &self.elevation,
+            &self.terrain,
+            x as usize,
+            y as usize,
+            building_type.footprint() as usize,
+            params,
+        );
+        Ok((base_cost
+            .saturating_mul(self.demand_multiplier_pct(x, y, building_type, params))
+            .saturating_mul(land_value_pct)
+            .saturating_mul(self.difficulty.cost_pct())
+            .saturating_mul(self.specialization.cost_pct(building_type))
+            / 100_000_000)
+            .saturating_add(clearing_cost)
+            .saturating_add(slope_cost))
+    }
+
+    /// True when the city has a `landmark` anywhere but at (x, y)
+    pub fn has_landmark_elsewhere(&self, x: u8, y: u8, landmark: BuildingType) -> bool {
+        let code = u8::from(landmark);
+        self.tiles
+            .iter()
+            .flatten()
+            .filter(|&&tile| tile == code)
+            .count()
+            > (self.tiles[y as usize][x as usize] == code) as usize
+    }
+
+    /// Price multiplier in percent: each existing building of the same type
+    /// (other than the one being replaced) adds `price_drift_pct`, so
+    /// spamming one type gets progressively more expensive. Roads are exempt.
+    pub fn demand_multiplier_pct(
+        &self,
+        x: u8,
+        y: u8,
+        building_type: BuildingType,
+        params: &GameParams,
+    ) -> u64 {
+        if building_type == BuildingType::Road {
+            return 100;
+        }
+        let building_type = u8::from(building_type);
+        let existing = self
+            .tiles
+            .iter()
+            .flatten()
+            .filter(|&&tile| tile == building_type)
+            .count() as u64
+            - (self.tiles[y as usize][x as usize] == building_type) as u64;
+        100 + existing * params.price_drift_pct as u64
+    }
+
+    pub fn has_tech(&self, tech: Tech) -> bool {
+        research::has(self.techs, tech)
+    }
+
+    /// Whether `required` is learned, or nothing is required
+    pub fn unlocked(&self, required: Option<Tech>) -> bool {
+        match required {
+            Some(tech) => self.has_tech(tech),
+            None => true,
+        }
+    }
+
+    /// Pay out milestone `index` if reached and not yet claimed, returning
+    /// the reward
+    pub fn claim_milestone(&mut self, index: u8) -> Result<u64> {
+        let milestone = milestones::MILESTONES
+            .get(index as usize)
+            .ok_or(CityError::InvalidMilestone)?;
+        let bit = 1 << index;
+        require!(
+            self.milestones_claimed & bit == 0,
+            CityError::MilestoneClaimed
+        );
+        self.milestones_reached |= milestones::met(self);
+        require!(
+            self.milestones_reached & bit != 0,
+            CityError::MilestoneNotReached
+        );
+        self.milestones_claimed |= bit;
+        self.money = self.money.saturating_add(milestone.reward);
+
+        msg!("Milestone {} claimed for {}", index, milestone.reward);
+        Ok(milestone.reward)
+    }
+
+    /// Pay out the quest of the day `now` falls in if the city meets it,
+    /// returning the reward
+    pub fn claim_quest(&mut self, now: i64, seed: u32) -> Result<u64> {
+        let day = quests::day_index(now);
+        require!(self.quest_claimed_day != Some(day), CityError::QuestClaimed);
+        let quest = quests::of_day(day, seed);
+        let (progress, target) = quests::progress(self, quest.goal, day);
+        require!(progress >= target, CityError::QuestIncomplete);
+        self.quest_claimed_day = Some(day);
+        self.money = self.money.saturating_add(quest.reward);
+
+        msg!("Quest of day {} claimed for {}", day, quest.reward);
+        Ok(quest.reward)
+    }
+
+    /// Wipe the city back to a new one of the same difficulty and
+    /// specialization, keeping its prestige bonus, for `prestige_city`.
+    /// Budget, service funding and the tax rate go back to nominal. The
+    /// chunks bought stay the city's, being accounts of their own, so
+    /// `chunks_owned` still prices the next one; and the day a quest was
+    /// last claimed stands, so a restart can't claim the same day twice.
+    pub fn start_over(&mut self, now: i64) {
+        self.tiles = [[0; 16]; 16];
+        self.population = 0;
+        self.money = self.difficulty.starting_money() * (100 + self.prestige_pct as u64) / 100;
+        self.last_updated = now;
+        self.fires = [[0; 16]; 16];
+        self.crime_scenes = [0; 16];
+        self.homeless = 0;
+        self.protected = [0; 16];
+        self.upkeep_unpaid = false;
+        self.loan_principal = 0;
+        self.loan_rate_bps = 0;
+        self.missed_loan_payments = 0;
+        self.in_default = false;
+        self.jobs = 0;
+        self.commerce_revenue = 0;
+        self.resources = Resources::default();
+        self.powered = [0; 16];
+        self.dry = [0; 16];
+        self.traffic = [[0; 16]; 16];
+        self.congestion = 0;
+        self.road_tiers = [[0; 16]; 16];
+        self.construction = [[0; 16]; 16];
+        self.condition = [[0; 16]; 16];
+        self.zoning = [[0; 16]; 16];
+        self.density = [[0; 16]; 16];
+        self.land_value = [[0; 16]; 16];
+        self.paid = [[0; 16]; 16];
+        self.pollution = [[0; 16]; 16];
+        self.garbage = 0;
+        self.crime = 0;
+        self.health = 0;
+        self.education = 0;
+        self.demand = Demand::default();
+        self.workforce = 0;
+        self.happiness = MAX_HAPPINESS;
+        self.step_count = 0;
+        self.reseeds = 0;
+        self.insured = false;
+        self.techs = 0;
+        self.researching = None;
+        self.research_steps_left = 0;
+        self.milestones_reached = 0;
+        self.milestones_claimed = 0;
+        self.approval = 0;
+        self.caretaker = false;
+        self.overdraft = 0;
+        self.bankrupt = false;
+        self.ordinances = 0;
+        self.budget = Budget::default();
+        self.funding = ServiceFunding::default();
+        self.tax_rate_bps = taxes::NOMINAL_TAX_RATE_BPS;
+        self.quest_day = 0;
+        self.quest_tax = 0;
+        self.on_layout_changed();
+    }
+
+    /// Pay for `tech` and start researching it
+    pub fn start_research(&mut self, tech: Tech) -> Result<()> {
+        require!(!self.has_tech(tech), CityError::AlreadyResearched);
+        require!(self.researching.is_none(), CityError::ResearchInProgress);
+        require!(
+            self.techs & tech.prerequisites() == tech.prerequisites(),
+            CityError::TechRequired
+        );
+        let (cost, steps) = tech.cost();
+        self.spend(cost)?;
+        self.researching = Some(tech);
+        self.research_steps_left = steps;
+
+        msg!("Researching {:?} for {} steps", tech, steps);
+        Ok(())
+    }
+
+    /// Take `cost` from the treasury; sandbox cities build for free
+    pub fn spend(&mut self, cost: u64) -> Result<()> {
+        if self.sandbox {
+            return Ok(());
+        }
+        require!(self.money >= cost, CityError::NotEnoughMoney);
+        self.money -= cost;
+        Ok(())
+    }
+
+    /// Anchor tile of the building covering (x, y), if any
+    pub fn building_anchor(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        buildings::anchor_of(&self.tiles, x, y)
+    }
+
+    /// Clear the building covering a tile, its whole footprint, down to the
+    /// terrain underneath, refunding part of the price paid for it
+    pub fn clear(&mut self, x: u8, y: u8, params: &GameParams) -> Result<()> {
+        require!(x < 16 && y < 16, CityError::OutOfBounds);
+        let (ax, ay) = self
+            .building_anchor(x as usize, y as usize)
+            .ok_or(CityError::TileEmpty)?;
+        let building_type = buildings::tile_type(self.tiles[ay][ax]);
+        require!(building_type != BuildingType::Empty, CityError::TileEmpty);
+        let size = building_type.footprint() as usize;
+        require!(
+            !footprint_tiles(ax, ay, size).any(|(tx, ty)| self.is_protected(tx, ty)),
+            CityError::TileProtected
+        );
+        // Ruins were paid for as the building they replaced, which is gone
+        let refund = if building_type == BuildingType::Ruins {
+            0
+        } else {
+            self.paid[ay][ax] as u64 * params.refund_pct as u64 / 100
+        };
+        self.money = self.money.saturating_add(refund);
+
+        for (tx, ty) in footprint_tiles(ax, ay, size) {
+            self.tiles[ty][tx] = 0; // 0 = Empty, `terrain` shows through
+            self.paid[ty][tx] = 0;
+            self.fires[ty][tx] = 0;
+            self.road_tiers[ty][tx] = 0;
+            self.construction[ty][tx] = 0;
+            self.set_crime_scene(tx, ty, false);
+        }
+        self.on_layout_changed();
+
+        msg!("Bulldozed tile at ({}, {}), refunded {}", x, y, refund);
+        Ok(())
+    }
+
+    /// Zone a rectangle, charging `zoning_fee` per tile whose zone changes
+    /// to a non-empty one
+    pub fn zone(
+        &mut self,
+        x: u8,
+        y: u8,
+        width: u8,
+        height: u8,
+        zone: BuildingType,
+        params: &GameParams,
+    ) -> Result<()> {
+        require!(!self.bankrupt, CityError::Bankrupt);
+        require!(zoning::is_zoning(zone), CityError::InvalidZone);
+        require!(width > 0 && height > 0, CityError::OutOfBounds);
+        let (x, y, width, height) = (x as usize, y as usize, width as usize, height as usize);
+        require!(
+            x + width <= GRID_SIZE && y + height <= GRID_SIZE,
+            CityError::OutOfBounds
+        );
+        let code = u8::from(zone);
+        let mut rezoned = 0u64;
+        for ty in y..y + height {
+            for tx in x..x + width {
+                if self.zoning[ty][tx] != code {
+                    self.zoning[ty][tx] = code;
+                    rezoned += (code != 0) as u64;
+                }
+            }
+        }
+        let fee = rezoned * params.zoning_fee;
+        self.spend(fee)?;
+
+        msg!("Zoned {} tiles as {:?} for {}", width * height, zone, fee);
+        Ok(())
+    }
+
+    /// Bring the building covering a tile back to full condition. The price
+    /// is `repair_cost_pct` of its base cost, scaled by the wear repaired.
+    pub fn repair(&mut self, x: u8, y: u8, params: &GameParams) -> Result<()> {
+        require!(!self.in_default, CityError::LoanInDefault);
+        require!(x < 16 && y < 16, CityError::OutOfBounds);
+        let (ax, ay) = self
+            .building_anchor(x as usize, y as usize)
+            .ok_or(CityError::TileEmpty)?;
+        require!(
+            !self.is_under_construction(ax, ay),
+            CityError::ConstructionInProgress
+        );
+        let building_type = buildings::tile_type(self.tiles[ay][ax]);
+        let base_cost = building_type
+            .base_cost()
+            .ok_or(CityError::NothingToRepair)?;
+        let wear = MAX_CONDITION.saturating_sub(self.condition[ay][ax]);
+        require!(wear > 0, CityError::NothingToRepair);
+        let cost =
+            base_cost * params.repair_cost_pct as u64 / 100 * wear as u64 / MAX_CONDITION as u64;
+        self.spend(cost)?;
+
+        for (tx, ty) in footprint_tiles(ax, ay, building_type.footprint() as usize) {
+            self.condition[ty][tx] = MAX_CONDITION;
+        }
+        msg!(
+            "Repaired {:?} at ({}, {}) for {}",
+            building_type,
+            ax,
+            ay,
+            cost
+        );
+        Ok(())
+    }
+
+    pub fn upgrade_density(&mut self, x: u8, y: u8) -> Result<()> {
+        require!(!self.in_default, CityError::LoanInDefault);
+        require!(!self.bankrupt, CityError::Bankrupt);
+        let building_type = buildings::tile_type(self.tile(x, y)?);
+        require!(building_type.is_zone(), CityError::NotAZone);
+        let (x, y) = (x as usize, y as usize);
+        require!(
+            !self.is_under_construction(x, y),
+            CityError::ConstructionInProgress
+        );
+        let (density, cost_pct) = Density::from_code(self.density[y][x])
+            .upgrade()
+            .ok_or(CityError::MaxDensity)?;
+        require!(
+            self.population >= density.required_population(),
+            CityError::DensityLocked
+        );
+        require!(
+            self.unlocked(density.required_tech()),
+            CityError::TechRequired
+        );
+        let cost = building_type.base_cost().unwrap_or(0) * cost_pct / 100;
+        self.spend(cost)?;
+        self.density[y][x] = density as u8;
+
+        msg!(
+            "Upgraded {:?} at ({}, {}) to {:?}",
+            building_type,
+            x,
+            y,
+            density
+        );
+        Ok(())
+    }
+
+    pub fn upgrade_road(&mut self, x: u8, y: u8) -> Result<()> {
+        require!(!self.in_default, CityError::LoanInDefault);
+        require!(!self.bankrupt, CityError::Bankrupt);
+        require!(self.tile(x, y)? == network::ROAD_TILE, CityError::NotARoad);
+        let (x, y) = (x as usize, y as usize);
+        let (tier, cost) = RoadTier::from_code(self.road_tiers[y][x])
+            .upgrade()
+            .ok_or(CityError::RoadFullyUpgraded)?;
+        self.spend(cost)?;
+        self.road_tiers[y][x] = tier as u8;
+
+        msg!("Upgraded road at ({}, {}) to {:?}", x, y, tier);
+        Ok(())
+    }
+
+    /// Turn the water tile at (x, y) into grass for `reclamation_cost`
+    pub fn reclaim(&mut self, x: u8, y: u8, params: &GameParams) -> Result<()> {
+        require!(!self.in_default, CityError::LoanInDefault);
+        require!(!self.bankrupt, CityError::Bankrupt);
+        require!(self.tile(x, y)? == 0, CityError::TileOccupied);
+        let (x, y) = (x as usize, y as usize);
+        require!(
+            terrain::terrain_of(self.terrain[y][x]) == Terrain::Water,
+            CityError::NotWater
+        );
+        require!(
+            terrain::borders_land(&self.terrain, x, y),
+            CityError::NotShoreline
+        );
+        self.spend(params.reclamation_cost)?;
+        self.terrain[y][x] = Terrain::Grass.into();
+
+        msg!("Reclaimed land at ({}, {})", x, y);
+        Ok(())
+    }
+
+    /// Reshape the empty land tile at (x, y) for `terraform_cost_per_level`
+    /// per level it moves
+    pub fn terraform(
+        &mut self,
+        x: u8,
+        y: u8,
+        action: TerraformAction,
+        params: &GameParams,
+    ) -> Result<()> {
+        require!(!self.in_default, CityError::LoanInDefault);
+        require!(!self.bankrupt, CityError::Bankrupt);
+        require!(self.tile(x, y)? == 0, CityError::TileOccupied);
+        let (x, y) = (x as usize, y as usize);
+        require!(
+            terrain::terrain_of(self.terrain[y][x]) != Terrain::Water,
+            CityError::TerrainBlocked
+        );
+        let height = self.elevation[y][x];
+        let target = action.target(&self.elevation, &self.terrain, x, y);
+        require!(target != height, CityError::AlreadyLevel);
+        self.spend(
+            (target.abs_diff(height) as u64).saturating_mul(params.terraform_cost_per_level),
+        )?;
+        self.elevation[y][x] = target;
+
+        msg!("Terraformed ({}, {}) from {} to {}", x, y, height, target);
+        Ok(())
+    }
+
+    /// Returns true if `key` was already processed; otherwise remembers it.
+    /// A missing key (or zero, which marks an empty slot) is never deduplicated.
+    pub fn already_processed(&mut self, key: Option<u64>) -> bool {
+        let key = match key {
+            Some(key) if key != 0 => key,
+            _ => return false,
+        };
+        if self.recent_idempotency_keys.contains(&key) {
+            return true;
+        }
+        let slot = self.next_idempotency_slot as usize % IDEMPOTENCY_KEY_SLOTS;
+        self.recent_idempotency_keys[slot] = key;
+        self.next_idempotency_slot = ((slot + 1) % IDEMPOTENCY_KEY_SLOTS) as u8;
+        false
+    }
+}
+
+/// Compact summary of a city, committed on its own during competitive play
+#[account]
+#[derive(InitSpace)]
+pub struct CityScore {
+    pub authority: Pubkey,
+    pub population: u32,
+    pub money: u64,
+    pub last_updated: i64,
+}
+
+impl CityScore {
+    pub fn refresh(&mut self, city: &City) {
+        self.population = city.population;
+        self.money = city.money;
+        self.last_updated = city.last_updated;
+    }
+}
+
+// ========================================
+// Errors
+// ========================================
+
+#[error_code]
+pub enum CityError {
+    #[msg("Coordinates out of bounds")]
+    OutOfBounds,
+    #[msg("Invalid building type")]
+    InvalidBuildingType,
+    #[msg("Invalid authentication")]
+    InvalidAuth,
+    #[msg("Not enough money")]
+    NotEnoughMoney,
+    #[msg("Too many actions, slow down")]
+    RateLimited,
+    #[msg("Tile is already occupied")]
+    TileOccupied,
+    #[msg("Too many actions in one batch")]
+    TooManyActions,
+    #[msg("Unknown feature flags")]
+    InvalidFeatureFlags,
+    #[msg("Invalid randomness source account")]
+    InvalidRandomnessSource,
+    #[msg("Too many projection steps requested")]
+    TooManyProjectionSteps,
+    #[msg("Invalid game config")]
+    InvalidConfig,
+    #[msg("No fire or crime to respond to on this tile")]
+    NoActiveIncident,
+    #[msg("The city has no station to dispatch from")]
+    NoStationAvailable,
+    #[msg("Tile is protected, unprotect it first")]
+    TileProtected,
+    #[msg("Tile is already empty")]
+    TileEmpty,
+    #[msg("Department funding level out of range")]
+    InvalidBudget,
+    #[msg("Loan would exceed the debt ceiling")]
+    DebtCeilingExceeded,
+    #[msg("City is in default, repay its loan first")]
+    LoanInDefault,
+    #[msg("City has no outstanding loan")]
+    NoOutstandingLoan,
+    #[msg("Building must be placed next to a road")]
+    NotConnectedToRoad,
+    #[msg("Tile is not a road")]
+    NotARoad,
+    #[msg("Road is already at the highest tier")]
+    RoadFullyUpgraded,
+    #[msg("Building is still under construction")]
+    ConstructionInProgress,
+    #[msg("Tile has no building in need of repair")]
+    NothingToRepair,
+    #[msg("This landmark has already been built")]
+    LandmarkAlreadyBuilt,
+    #[msg("Only residential, commercial, industrial or empty zones exist")]
+    InvalidZone,
+    #[msg("Only residential, commercial and industrial buildings have a density")]
+    NotAZone,
+    #[msg("Building is already at the highest density")]
+    MaxDensity,
+    #[msg("City population is too small for this density")]
+    DensityLocked,
+    #[msg("City education level is too low for this building")]
+    EducationTooLow,
+    #[msg("No simulation step is due yet")]
+    StepTooSoon,
+    #[msg("A randomness request is already waiting for the oracle")]
+    RandomnessPending,
+    #[msg("No randomness was requested")]
+    NoRandomnessRequested,
+    #[msg("City is already insured")]
+    AlreadyInsured,
+    #[msg("City is not insured")]
+    NotInsured,
+    #[msg("Scenarios can only be started on a new city")]
+    ScenarioUnavailable,
+    #[msg("City is not playing a scenario")]
+    NoScenarioInProgress,
+    #[msg("Sandbox cities don't keep score")]
+    SandboxCity,
+    #[msg("A tech this needs hasn't been researched")]
+    TechRequired,
+    #[msg("Tech already researched")]
+    AlreadyResearched,
+    #[msg("Another tech is being researched")]
+    ResearchInProgress,
+    #[msg("City population is too small for this building")]
+    BuildingLocked,
+    #[msg("No such milestone")]
+    InvalidMilestone,
+    #[msg("Milestone not reached yet")]
+    MilestoneNotReached,
+    #[msg("Milestone reward already claimed")]
+    MilestoneClaimed,
+    #[msg("Today's quest isn't complete")]
+    QuestIncomplete,
+    #[msg("Today's quest reward already claimed")]
+    QuestClaimed,
+    #[msg("Sandbox and scenario cities can't prestige")]
+    PrestigeUnavailable,
+    #[msg("Score too low to prestige")]
+    ScoreTooLow,
+    #[msg("City is bankrupt, restructure its debt first")]
+    Bankrupt,
+    #[msg("City isn't bankrupt")]
+    NotBankrupt,
+    #[msg("Tax rate above the maximum")]
+    InvalidTaxRate,
+    #[msg("Chunk (0, 0) is the city's own grid")]
+    InvalidChunk,
+    #[msg("New land must border land the city owns")]
+    ChunkNotAdjacent,
+    #[msg("This building can't stand on this terrain")]
+    TerrainBlocked,
+    #[msg("Coastal buildings must border water")]
+    NotCoastal,
+    #[msg("Tile isn't water")]
+    NotWater,
+    #[msg("Reclaimed land must border existing land")]
+    NotShoreline,
+    #[msg("The tile is already at that height")]
+    AlreadyLevel,
+    #[msg("This deployment rolls from slot hashes; pass the SlotHashes sysvar")]
+    SlotHashesRequired,
+    #[msg("Disasters are set by the game config and can't be switched")]
+    DisastersLocked,
+}
+
+#[cfg(test)]
+impl City {
+    /// A fresh Normal city on flat grassland with `money` in the treasury
+    pub(crate) fn for_tests(money: u64) -> Self {
+        let zeroed = vec![0u8; 8 + City::INIT_SPACE];
+        let mut city = City::try_deserialize_unchecked(&mut &zeroed[..]).unwrap();
+        city.money = money;
+        city.difficulty = Difficulty::Normal;
+        city.happiness = MAX_HAPPINESS;
+        city.budget = Budget::default();
+        city.funding = ServiceFunding::default();
+        city.tax_rate_bps = taxes::NOMINAL_TAX_RATE_BPS;
+        city.features = features::DEFAULT;
+        city
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bulldozing_refunds_a_share_of_the_price_paid() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(10_000);
+        city.difficulty = Difficulty::Easy;
+        city.land_value[1][0] = land_value::MIN_LAND_VALUE;
+        city.place(0, 0, BuildingType::Road, false, &params)
+            .unwrap();
+
+        let before = city.money;
+        city.place(0, 1, BuildingType::Residential, false, &params)
+            .unwrap();
+        let paid = before - city.money;
+        assert!(paid < BuildingType::Residential.base_cost().unwrap() / 2);
+        city.clear(0, 1, &params).unwrap();
+        assert_eq!(
+            before - city.money,
+            paid - paid * params.refund_pct as u64 / 100
+        );
+    }
+
+    #[test]
+    fn police_funding_scales_police_reach_once() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(0);
+        city.funding.police_pct = 50;
+        let halved = GameParams {
+            police_radius: params.police_radius / 2,
+            ..params
+        };
+        assert_eq!(
+            city.effective_params(&params).police_radius,
+            city.difficulty.apply(&halved).police_radius
+        );
+    }
+
+    #[test]
+    fn no_steps_are_due_while_randomness_is_pending() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(0);
+        let now = 10 * params.step_interval_seconds as i64;
+        assert_eq!(city.steps_due(&params, now), 10);
+        city.randomness_pending = true;
+        city.randomness_requested_at = now;
+        assert_eq!(city.steps_due(&params, now), 0);
+        city.randomness_pending = false;
+        assert_eq!(city.steps_due(&params, now), 10);
+    }
+
+    #[test]
+    fn an_unanswered_request_lapses_after_the_timeout() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(0);
+        city.step_count = params.reseed_interval_steps as u64;
+        assert!(city.reseed_due(&params));
+        city.randomness_pending = true;
+        city.randomness_requested_at = 0;
+
+        let timeout = params.randomness_timeout_seconds as i64;
+        assert!(!city.expire_randomness(&params, timeout - 1));
+        assert!(city.awaiting_randomness(&params, timeout - 1));
+        assert!(city.expire_randomness(&params, timeout));
+        assert!(!city.randomness_pending);
+        assert!(!city.reseed_due(&params));
+        assert!(city.steps_due(&params, timeout) > 0);
+    }
+
+    #[test]
+    fn reseeds_come_on_schedule_and_change_the_seed() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(0);
+        let interval = params.reseed_interval_steps as u64;
+        city.step_count = interval - 1;
+        assert!(!city.reseed_due(&params));
+        city.step_count = interval;
+        assert!(city.reseed_due(&params));
+
+        let seed = city.rng_seed;
+        city.randomness_pending = true;
+        city.reseed(&[7; 32], &params);
+        assert_ne!(city.rng_seed, seed);
+        assert!(!city.randomness_pending);
+        assert!(!city.reseed_due(&params));
+        city.step_count = 2 * interval;
+        assert!(city.reseed_due(&params));
+    }
+
+    #[test]
+    fn tax_rates_past_the_cap_are_rejected() {
+        let mut city = City::for_tests(0);
+        city.set_tax_rate(taxes::MAX_TAX_RATE_BPS).unwrap();
+        assert_eq!(city.tax_rate_bps, taxes::MAX_TAX_RATE_BPS);
+        assert!(city.set_tax_rate(taxes::MAX_TAX_RATE_BPS + 1).is_err());
+        assert_eq!(city.tax_rate_bps, taxes::MAX_TAX_RATE_BPS);
+    }
+
+    #[test]
+    fn mayors_cannot_switch_disasters() {
+        let mut city = City::for_tests(0);
+        assert!(city
+            .set_features(features::DEFAULT & !features::DISASTERS)
+            .is_err());
+        city.set_features(features::DISASTERS).unwrap();
+        assert_eq!(city.features, features::DISASTERS);
+
+        city.features = 0;
+        assert!(city.set_features(features::DISASTERS).is_err());
+        city.set_features(features::TRAFFIC).unwrap();
+    }
+
+    #[test]
+    fn repeated_idempotency_keys_are_caught() {
+        let mut city = City::for_tests(0);
+        assert!(!city.already_processed(Some(7)));
+        assert!(city.already_processed(Some(7)));
+        assert!(!city.already_processed(None));
+        assert!(!city.already_processed(None));
+        assert!(!city.already_processed(Some(0)));
+        assert!(!city.already_processed(Some(0)));
+
+        // Only the last `IDEMPOTENCY_KEY_SLOTS` keys are remembered
+        for key in 100..100 + IDEMPOTENCY_KEY_SLOTS as u64 {
+            assert!(!city.already_processed(Some(key)));
+        }
+        assert!(!city.already_processed(Some(7)));
+    }
+
+    #[test]
+    fn ruins_refund_nothing() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(10_000);
+        city.place(0, 0, BuildingType::Road, false, &params)
+            .unwrap();
+        city.place(0, 1, BuildingType::Residential, false, &params)
+            .unwrap();
+        city.tiles[1][0] = BuildingType::Ruins.into();
+
+        let before = city.money;
+        city.clear(0, 1, &params).unwrap();
+        assert_eq!(city.money, before);
+        assert_eq!(city.paid[1][0], 0);
+    }
+
+    #[test]
+    fn sandbox_buildings_refund_nothing() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(10_000);
+        city.sandbox = true;
+        city.place(0, 0, BuildingType::Road, false, &params)
+            .unwrap();
+        city.clear(0, 0, &params).unwrap();
+        assert_eq!(city.money, 10_000);
+    }
+
+    #[test]
+    fn actions_past_the_rate_cap_wait_for_the_next_window() {
+        let mut city = City::for_tests(0);
+        let now = 1_000;
+        for _ in 0..MAX_ACTIONS_PER_WINDOW {
+            city.record_action(now).unwrap();
+        }
+        assert!(city.record_action(now).is_err());
+        assert!(city.record_action(now + ACTION_WINDOW_SECONDS - 1).is_err());
+
+        city.record_action(now + ACTION_WINDOW_SECONDS).unwrap();
+        assert_eq!(city.actions_in_window, 1);
+    }
+
+    #[test]
+    fn older_argument_versions_still_decode() {
+        let road = u8::from(BuildingType::Road);
+
+        // V1 as an original client encodes it: tag, x, y, building type
+        let args = PlaceBuildingArgs::try_from_slice(&[0, 3, 4, road]).unwrap();
+        assert_eq!(
+            args,
+            PlaceBuildingArgs::V1 {
+                x: 3,
+                y: 4,
+                building_type: BuildingType::Road,
+            }
+        );
+        let latest = args.into_latest();
+        assert_eq!((latest.x, latest.y), (3, 4));
+        assert!(latest.overwrite);
+        assert_eq!(latest.idempotency_key, None);
+
+        let latest = PlaceBuildingArgs::try_from_slice(&[1, 3, 4, road, 0])
+            .unwrap()
+            .into_latest();
+        assert!(!latest.overwrite);
+        assert_eq!(latest.idempotency_key, None);
+
+        let v3 = PlaceBuildingArgs::V3 {
+            x: 3,
+            y: 4,
+            building_type: BuildingType::Road,
+            overwrite: false,
+            idempotency_key: Some(9),
+        };
+        let latest = PlaceBuildingArgs::try_from_slice(&v3.try_to_vec().unwrap())
+            .unwrap()
+            .into_latest();
+        assert_eq!(latest.idempotency_key, Some(9));
+
+        let latest = BulldozeArgs::try_from_slice(&[0, 5, 6])
+            .unwrap()
+            .into_latest();
+        assert_eq!((latest.x, latest.y, latest.idempotency_key), (5, 6, None));
+        assert!(PlaceBuildingArgs::try_from_slice(&[3, 3, 4, road]).is_err());
+    }
+
+    #[test]
+    fn a_failing_action_rolls_back_the_whole_batch() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(10_000);
+        let before = city.try_to_vec().unwrap();
+
+        let actions = [
+            Action::Place {
+                x: 0,
+                y: 0,
+                building_type: BuildingType::Road,
+                overwrite: false,
+            },
+            Action::SetTax {
+                bps: taxes::MAX_TAX_RATE_BPS,
+            },
+            Action::Bulldoze {
+                x: GRID_SIZE as u8,
+                y: 0,
+            },
+        ];
+        assert!(city.apply_actions(&actions, 1_000, &params).is_err());
+        assert_eq!(city.try_to_vec().unwrap(), before);
+
+        let placed = city.apply_actions(&actions[..2], 1_000, &params).unwrap();
+        assert_eq!(placed, 1);
+        assert_eq!(city.tiles[0][0], u8::from(BuildingType::Road));
+        assert_eq!(city.tax_rate_bps, taxes::MAX_TAX_RATE_BPS);
+        assert_eq!(city.actions_in_window, 2);
+    }
+
+    #[test]
+    fn terraforming_charges_per_level_on_empty_land_only() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(10_000);
+        city.elevation = [[2; GRID_SIZE]; GRID_SIZE];
+        city.elevation[3][3] = 7;
+
+        city.terraform(3, 3, TerraformAction::Flatten, &params)
+            .unwrap();
+        assert_eq!(city.elevation[3][3], 2);
+        assert_eq!(city.money, 10_000 - 5 * params.terraform_cost_per_level);
+        assert!(city
+            .terraform(3, 3, TerraformAction::Flatten, &params)
+            .is_err());
+
+        city.elevation[3][3] = elevation::MAX_ELEVATION;
+        assert!(city
+            .terraform(3, 3, TerraformAction::Raise, &params)
+            .is_err());
+
+        city.terrain[4][4] = Terrain::Water.into();
+        assert!(city
+            .terraform(4, 4, TerraformAction::Raise, &params)
+            .is_err());
+        city.tiles[5][5] = BuildingType::Park.into();
+        assert!(city
+            .terraform(5, 5, TerraformAction::Raise, &params)
+            .is_err());
+
+        city.money = params.terraform_cost_per_level - 1;
+        assert!(city
+            .terraform(6, 6, TerraformAction::Raise, &params)
+            .is_err());
+        assert_eq!(city.elevation[6][6], 2);
+    }
 }
@@ -0,0 +1,85 @@
+//! Mayor experience and levels.
+//!
+//! Each player has a `MayorProfile`, seeded off their key, collecting
+//! experience from what they do with their city: placing buildings,
+//! simulation steps and claimed milestones. The profile is credited when it
+//! is passed to the instruction earning the experience. Experience raises
+//! the mayor's level, which comes with a title for the frontend and a
+//! `REWARD_BONUS_PCT_PER_LEVEL` bonus on milestone and quest rewards.
+//! Sandbox cities earn no experience.
+
+use anchor_lang::prelude::*;
+
+pub const PROFILE_SEED: &[u8] = b"profile";
+
+/// Experience per building placed
+pub const XP_PER_PLACEMENT: u64 = 10;
+
+/// Experience per simulation step
+pub const XP_PER_STEP: u64 = 1;
+
+/// Experience per milestone claimed
+pub const XP_PER_MILESTONE: u64 = 100;
+
+/// Experience a mayor needs for each level, from level 1 up
+pub const LEVEL_XP: [u64; 8] = [0, 100, 300, 700, 1_500, 3_000, 6_000, 12_000];
+
+/// Title of each level, from level 1 up
+pub const TITLES: [&str; 8] = [
+    "Clerk",
+    "Councillor",
+    "Alderman",
+    "Deputy Mayor",
+    "Mayor",
+    "Metropolitan Mayor",
+    "Governor",
+    "City Legend",
+];
+
+/// Extra milestone and quest reward per level above the first, in percent
+pub const REWARD_BONUS_PCT_PER_LEVEL: u64 = 2;
+
+/// Level, from 1, of a mayor with `xp` experience
+pub fn level_of(xp: u64) -> u8 {
+    LEVEL_XP
+        .iter()
+        .rposition(|&threshold| xp >= threshold)
+        .unwrap_or(0) as u8
+        + 1
+}
+
+pub fn title(level: u8) -> &'static str {
+    TITLES[(level.max(1) as usize - 1).min(TITLES.len() - 1)]
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MayorProfile {
+    pub authority: Pubkey,
+    pub xp: u64,
+    /// Cached `level_of(xp)`
+    pub level: u8,
+    /// Times the mayor's city was started over and the bonus those runs
+    /// earned, see `prestige`
+    pub prestige_count: u16,
+    pub prestige_pct: u16,
+}
+
+impl MayorProfile {
+    /// Add `xp` and return the new level if it went up
+    pub fn award(&mut self, xp: u64) -> Option<u8> {
+        self.xp = self.xp.saturating_add(xp);
+        let level = level_of(self.xp);
+        if level > self.level {
+            self.level = level;
+            Some(level)
+        } else {
+            None
+        }
+    }
+
+    /// Milestone and quest rewards, in percent of the usual
+    pub fn reward_pct(&self) -> u64 {
+        100 + self.level.saturating_sub(1) as u64 * REWARD_BONUS_PCT_PER_LEVEL
+    }
+}
@@ -0,0 +1,123 @@
+//! Milestones.
+//!
+//! `MILESTONES` lists one-time rewards for crossing population, net worth
+//! and building-count thresholds. The end of every step marks the milestones
+//! the city has reached on `City::milestones_reached`, bit `index` each; a
+//! reached milestone stays reached even if the city falls back below it.
+//! The mayor collects the reward with `claim_milestone`, which sets the same
+//! bit on `City::milestones_claimed` so it pays out only once.
+
+use crate::buildings::{self, BuildingType, FOOTPRINT_TILE};
+use crate::City;
+
+/// What a milestone asks of the city
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Goal {
+    Population(u32),
+    /// Net worth, so borrowed money doesn't count, see `City::net_worth`
+    Money(u64),
+    /// Buildings standing, counting each footprint once and leaving out
+    /// infrastructure and ruins
+    Buildings(u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Milestone {
+    pub goal: Goal,
+    /// Paid into the treasury when claimed
+    pub reward: u64,
+}
+
+pub const MILESTONES: [Milestone; 9] = [
+    Milestone {
+        goal: Goal::Population(100),
+        reward: 500,
+    },
+    Milestone {
+        goal: Goal::Population(1_000),
+        reward: 2_000,
+    },
+    Milestone {
+        goal: Goal::Population(10_000),
+        reward: 10_000,
+    },
+    Milestone {
+        goal: Goal::Population(50_000),
+        reward: 25_000,
+    },
+    Milestone {
+        goal: Goal::Money(20_000),
+        reward: 1_000,
+    },
+    Milestone {
+        goal: Goal::Money(100_000),
+        reward: 5_000,
+    },
+    Milestone {
+        goal: Goal::Buildings(10),
+        reward: 500,
+    },
+    Milestone {
+        goal: Goal::Buildings(50),
+        reward: 2_500,
+    },
+    Milestone {
+        goal: Goal::Buildings(150),
+        reward: 7_500,
+    },
+];
+
+/// Buildings standing on the grid, as `Goal::Buildings` counts them
+pub fn building_count(city: &City) -> u32 {
+    city.tiles
+        .iter()
+        .flatten()
+        .filter(|&&tile| {
+            let building_type = buildings::tile_type(tile);
+            tile != FOOTPRINT_TILE
+                && building_type != BuildingType::Empty
+                && building_type != BuildingType::Ruins
+                && !building_type.is_infrastructure()
+        })
+        .count() as u32
+}
+
+/// Bitfield of the milestones `city` meets right now
+pub fn met(city: &City) -> u32 {
+    let buildings = building_count(city);
+    MILESTONES
+        .iter()
+        .enumerate()
+        .filter(|(_, milestone)| match milestone.goal {
+            Goal::Population(population) => city.population >= population,
+            Goal::Money(money) => city.net_worth() >= money,
+            Goal::Buildings(count) => buildings >= count,
+        })
+        .fold(0, |bits, (index, _)| bits | 1 << index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money_goals(city: &City) -> usize {
+        MILESTONES
+            .iter()
+            .enumerate()
+            .filter(|(index, milestone)| {
+                matches!(milestone.goal, Goal::Money(_)) && met(city) & 1 << index != 0
+            })
+            .count()
+    }
+
+    #[test]
+    fn borrowed_money_reaches_no_treasury_milestone() {
+        let mut city = City::for_tests(25_000);
+        assert_eq!(money_goals(&city), 1);
+        city.loan_principal = 10_000;
+        assert_eq!(money_goals(&city), 0);
+        city.loan_principal = 0;
+        city.overdraft = 10_000;
+        assert_eq!(money_goals(&city), 0);
+    }
+}
@@ -0,0 +1,131 @@
+//! Road-network connectivity.
+//!
+//! Road tiles are labelled with a connected-component id by a bounded BFS.
+//! Bridges carry roads over water and count as road tiles, so they join
+//! the networks on either bank.
+//! Buildings take the id of an orthogonally adjacent road, so two tiles are
+//! "on the same network" when their ids match. Id 0 means not connected.
+//! The resulting map is cached on `City::road_network` and rebuilt whenever
+//! the tile layout changes.
+
+use crate::buildings::BuildingType;
+use crate::GRID_SIZE;
+
+pub const ROAD_TILE: u8 = BuildingType::Road as u8;
+pub const BRIDGE_TILE: u8 = BuildingType::Bridge as u8;
+
+pub type Grid = [[u8; GRID_SIZE]; GRID_SIZE];
+
+const NEIGHBORS: [(i8, i8); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Whether `tile` carries a road, bridges included
+pub fn is_road(tile: u8) -> bool {
+    tile == ROAD_TILE || tile == BRIDGE_TILE
+}
+
+/// Orthogonal in-bounds neighbours of (x, y)
+pub fn neighbors(x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+    NEIGHBORS.iter().filter_map(move |&(dx, dy)| {
+        let nx = x as i16 + dx as i16;
+        let ny = y as i16 + dy as i16;
+        if nx < 0 || ny < 0 || nx >= GRID_SIZE as i16 || ny >= GRID_SIZE as i16 {
+            None
+        } else {
+            Some((nx as usize, ny as usize))
+        }
+    })
+}
+
+/// Build the connectivity component map for `tiles`
+pub fn compute_road_network(tiles: &Grid) -> Grid {
+    let mut network = [[0u8; GRID_SIZE]; GRID_SIZE];
+    let mut queue = [(0u8, 0u8); GRID_SIZE * GRID_SIZE];
+    let mut next_id: u8 = 0;
+
+    // Label road components
+    for sy in 0..GRID_SIZE {
+        for sx in 0..GRID_SIZE {
+            if !is_road(tiles[sy][sx]) || network[sy][sx] != 0 {
+                continue;
+            }
+            next_id += 1;
+            network[sy][sx] = next_id;
+            let (mut head, mut tail) = (0, 0);
+            queue[tail] = (sx as u8, sy as u8);
+            tail += 1;
+            while head < tail {
+                let (x, y) = queue[head];
+                head += 1;
+                for (nx, ny) in neighbors(x as usize, y as usize) {
+                    if is_road(tiles[ny][nx]) && network[ny][nx] == 0 {
+                        network[ny][nx] = next_id;
+                        queue[tail] = (nx as u8, ny as u8);
+                        tail += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    // Attach buildings to the first adjacent road component
+    for y in 0..GRID_SIZE {
+        for x in 0..GRID_SIZE {
+            let tile = tiles[y][x];
+            if tile == 0 || is_road(tile) {
+                continue;
+            }
+            if let Some((nx, ny)) = neighbors(x, y).find(|&(nx, ny)| is_road(tiles[ny][nx])) {
+                network[y][x] = network[ny][nx];
+            }
+        }
+    }
+
+    network
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOME: u8 = BuildingType::Residential as u8;
+
+    #[test]
+    fn roads_split_into_components_and_buildings_join_them() {
+        let mut tiles = [[0u8; GRID_SIZE]; GRID_SIZE];
+        // An L of road in the corner, a bridge on the far edge and a road
+        // that only touches it diagonally
+        tiles[0][0] = ROAD_TILE;
+        tiles[0][1] = ROAD_TILE;
+        tiles[1][1] = ROAD_TILE;
+        tiles[0][GRID_SIZE - 1] = BRIDGE_TILE;
+        tiles[1][GRID_SIZE - 1] = ROAD_TILE;
+        tiles[2][GRID_SIZE - 2] = ROAD_TILE;
+        tiles[1][0] = HOME;
+        tiles[5][5] = HOME;
+
+        let network = compute_road_network(&tiles);
+        assert_ne!(network[0][0], 0);
+        assert_eq!(network[1][1], network[0][0]);
+        assert_eq!(network[1][0], network[0][0]);
+        assert_eq!(network[1][GRID_SIZE - 1], network[0][GRID_SIZE - 1]);
+        assert_ne!(network[0][GRID_SIZE - 1], network[0][0]);
+        assert_ne!(network[2][GRID_SIZE - 2], network[1][GRID_SIZE - 1]);
+        assert_eq!(network[5][5], 0);
+        assert_eq!(network[3][3], 0);
+    }
+
+    #[test]
+    fn a_road_across_the_grid_is_one_component() {
+        let mut tiles = [[0u8; GRID_SIZE]; GRID_SIZE];
+        for row in tiles.iter_mut() {
+            row[GRID_SIZE / 2] = ROAD_TILE;
+        }
+        tiles[GRID_SIZE / 2][GRID_SIZE / 2] = BRIDGE_TILE;
+
+        let network = compute_road_network(&tiles);
+        assert!(network
+            .iter()
+            .all(|row| row[GRID_SIZE / 2] == network[0][GRID_SIZE / 2]));
+        assert_eq!(network[0][GRID_SIZE / 2], 1);
+    }
+}
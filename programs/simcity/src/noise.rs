@@ -0,0 +1,62 @@
+//! Noise overlay.
+//!
+//! Airports, highway-tier roads, stadiums, industry, train stations and
+//! ports are loud. Each source has a level that
+//! drops by one per tile of Manhattan distance; a tile's noise is the
+//! loudest source reaching it. Noise is independent of air pollution and
+//! only makes homes less desirable.
+
+use crate::buildings::{self, BuildingType};
+use crate::network::Grid;
+use crate::roads::RoadTier;
+use crate::GRID_SIZE;
+
+/// Loudest possible noise level
+pub const MAX_NOISE: u8 = 6;
+
+/// Noise level a tile with road tier `tier_code` emits at its own position
+pub fn emitted_by(tile: u8, tier_code: u8) -> u8 {
+    match buildings::tile_type(tile) {
+        BuildingType::Airport => MAX_NOISE,
+        BuildingType::Road if RoadTier::from_code(tier_code) == RoadTier::Highway => 3,
+        BuildingType::Stadium => 3,
+        BuildingType::Industrial | BuildingType::TrainStation | BuildingType::Port => 2,
+        _ => 0,
+    }
+}
+
+/// Per-tile noise level for the whole grid
+pub fn compute_noise(tiles: &Grid, road_tiers: &Grid) -> Grid {
+    let mut noise = [[0u8; GRID_SIZE]; GRID_SIZE];
+    for (y, row) in tiles.iter().enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            let level = emitted_by(tile, road_tiers[y][x]);
+            if level > 0 {
+                apply_falloff(&mut noise, x, y, level);
+            }
+        }
+    }
+    noise
+}
+
+/// Raise every tile within reach of a source at (x, y) to the source's level
+/// at that distance. Parks spread greenery the same way.
+pub fn apply_falloff(noise: &mut Grid, x: usize, y: usize, level: u8) {
+    let r = level as i16 - 1;
+    for dy in -r..=r {
+        let ty = y as i16 + dy;
+        if ty < 0 || ty >= GRID_SIZE as i16 {
+            continue;
+        }
+        let span = r - dy.abs();
+        for dx in -span..=span {
+            let tx = x as i16 + dx;
+            if tx < 0 || tx >= GRID_SIZE as i16 {
+                continue;
+            }
+            let heard = level - (dx.abs() + dy.abs()) as u8;
+            let cell = &mut noise[ty as usize][tx as usize];
+            *cell = (*cell).max(heard);
+        }
+    }
+}
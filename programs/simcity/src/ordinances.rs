@@ -0,0 +1,80 @@
+//! City ordinances.
+//!
+//! The mayor enacts and repeals ordinances with `set_ordinance`. Enacted
+//! ones are a bitfield on `City::ordinances`, bit `ordinance as u8` each,
+//! and each costs its `cost` every step for its effect:
+//!
+//! - free public transit: more residents ride, taking
+//!   `FREE_TRANSIT_RELIEF_PCT` of the load off every road
+//! - industrial pollution controls: industry and landfills emit
+//!   `POLLUTION_CONTROLS_CUT_PCT` less pollution
+//! - neighborhood watch: residents keep an eye out, cutting the crime
+//!   level by `NEIGHBORHOOD_WATCH_CUT_PCT`
+
+use anchor_lang::prelude::*;
+
+/// Road load taken off by free public transit, in percent
+pub const FREE_TRANSIT_RELIEF_PCT: u32 = 20;
+
+/// Pollution emissions cut by pollution controls, in percent
+pub const POLLUTION_CONTROLS_CUT_PCT: u32 = 50;
+
+/// Crime cut by neighborhood watch, in percent
+pub const NEIGHBORHOOD_WATCH_CUT_PCT: u32 = 25;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ordinance {
+    FreeTransit,
+    PollutionControls,
+    NeighborhoodWatch,
+}
+
+impl Ordinance {
+    pub const ALL: [Ordinance; 3] = [
+        Ordinance::FreeTransit,
+        Ordinance::PollutionControls,
+        Ordinance::NeighborhoodWatch,
+    ];
+
+    pub fn bit(self) -> u32 {
+        1 << self as u8
+    }
+
+    /// Price per step while enacted
+    pub fn cost(self) -> u64 {
+        match self {
+            Ordinance::FreeTransit => 30,
+            Ordinance::PollutionControls => 40,
+            Ordinance::NeighborhoodWatch => 20,
+        }
+    }
+}
+
+/// Whether the `ordinances` bitfield includes `ordinance`
+pub fn has(ordinances: u32, ordinance: Ordinance) -> bool {
+    ordinances & ordinance.bit() != 0
+}
+
+/// Price per step of every enacted ordinance
+pub fn cost(ordinances: u32) -> u64 {
+    Ordinance::ALL
+        .iter()
+        .filter(|ordinance| has(ordinances, **ordinance))
+        .map(|ordinance| ordinance.cost())
+        .sum()
+}
+
+/// Road load left, in percent
+pub fn traffic_pct(ordinances: u32) -> u32 {
+    100 - has(ordinances, Ordinance::FreeTransit) as u32 * FREE_TRANSIT_RELIEF_PCT
+}
+
+/// Pollution emitted, in percent
+pub fn emission_pct(ordinances: u32) -> u32 {
+    100 - has(ordinances, Ordinance::PollutionControls) as u32 * POLLUTION_CONTROLS_CUT_PCT
+}
+
+/// Crime left, in percent
+pub fn crime_pct(ordinances: u32) -> u32 {
+    100 - has(ordinances, Ordinance::NeighborhoodWatch) as u32 * NEIGHBORHOOD_WATCH_CUT_PCT
+}
@@ -0,0 +1,36 @@
+//! Parks and green space.
+//!
+//! Parks, and the marinas and beaches of `coastal`, spread greenery the way
+//! loud buildings spread noise: a level that drops by one per tile of
+//! Manhattan distance, the greenest park winning.
+//! Greenery makes homes more desirable, and residents living near a park
+//! are happier.
+
+use crate::buildings::{self, BuildingType};
+use crate::network::Grid;
+use crate::noise;
+use crate::GRID_SIZE;
+
+/// Greenery level a tile gives off at its own position
+pub fn greenery_of(tile: u8) -> u8 {
+    match buildings::tile_type(tile) {
+        BuildingType::Park | BuildingType::Beach => 2,
+        BuildingType::Marina => 3,
+        BuildingType::LargePark => 4,
+        _ => 0,
+    }
+}
+
+/// Per-tile greenery level for the whole grid
+pub fn compute_greenery(tiles: &Grid) -> Grid {
+    let mut greenery = [[0u8; GRID_SIZE]; GRID_SIZE];
+    for (y, row) in tiles.iter().enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            let level = greenery_of(tile);
+            if level > 0 {
+                noise::apply_falloff(&mut greenery, x, y, level);
+            }
+        }
+    }
+    greenery
+}
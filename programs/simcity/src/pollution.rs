@@ -0,0 +1,58 @@
+//! Air pollution.
+//!
+//! Industry and landfills emit pollution that drifts across the grid a tile per step.
+//! Every step a tile takes the highest level around it (its own and its
+//! four neighbours') less one as the air clears, or what it emits itself if
+//! that is more. A factory raises its surroundings over a few steps, and
+//! the smog lingers a while after it closes. Unlike noise, pollution is
+//! persisted on the city, and only simulated with `features::POLLUTION`.
+
+use crate::buildings::{self, BuildingType};
+use crate::density;
+use crate::network::Grid;
+use crate::GRID_SIZE;
+
+/// Highest pollution level a tile can reach
+pub const MAX_POLLUTION: u8 = 8;
+
+/// Level a low-density industrial building emits; denser ones emit more
+pub const INDUSTRY_POLLUTION: u8 = 3;
+
+/// Level a landfill emits
+pub const LANDFILL_POLLUTION: u8 = 2;
+
+/// Pollution a tile emits at its own position
+pub fn emitted_by(tile: u8, density_code: u8) -> u8 {
+    match buildings::tile_type(tile) {
+        BuildingType::Industrial => (INDUSTRY_POLLUTION as u32 * density::factor(density_code))
+            .min(MAX_POLLUTION as u32) as u8,
+        BuildingType::Landfill => LANDFILL_POLLUTION,
+        _ => 0,
+    }
+}
+
+/// Pollution after one step of emission, at `emission_pct` percent of the
+/// usual, and drift from `previous`
+pub fn diffuse(previous: &Grid, tiles: &Grid, density: &Grid, emission_pct: u32) -> Grid {
+    let mut next = [[0u8; GRID_SIZE]; GRID_SIZE];
+    for (y, row) in next.iter_mut().enumerate() {
+        for (x, cell) in row.iter_mut().enumerate() {
+            let mut highest = previous[y][x];
+            if x > 0 {
+                highest = highest.max(previous[y][x - 1]);
+            }
+            if x + 1 < GRID_SIZE {
+                highest = highest.max(previous[y][x + 1]);
+            }
+            if y > 0 {
+                highest = highest.max(previous[y - 1][x]);
+            }
+            if y + 1 < GRID_SIZE {
+                highest = highest.max(previous[y + 1][x]);
+            }
+            let emitted = emitted_by(tiles[y][x], density[y][x]) as u32 * emission_pct / 100;
+            *cell = highest.saturating_sub(1).max(emitted as u8);
+        }
+    }
+    next
+}
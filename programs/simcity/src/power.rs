@@ -0,0 +1,152 @@
+//! Electricity propagation.
+//!
+//! Power flows from plants through any chain of orthogonally adjacent
+//! non-empty tiles (roads and power lines included). Within each such
+//! cluster, the plants' combined capacity is handed out to consumers in
+//! breadth-first order from the first plant found; consumers past the point
+//! where capacity runs out stay unpowered. Water uses the same model, see
+//! `water`.
+
+use crate::buildings::{self, BuildingType};
+use crate::day_night::DayPhase;
+use crate::network::{self, Grid};
+use crate::resources::Flows;
+use crate::season::Season;
+use crate::GRID_SIZE;
+
+/// Bit x of row y is set when the tile is powered
+pub type PowerMap = [u16; GRID_SIZE];
+
+pub fn is_powered(map: &PowerMap, x: usize, y: usize) -> bool {
+    map[y] & (1 << x) != 0
+}
+
+/// Which tiles receive power in `season` at `phase` of the day
+pub fn compute_power(tiles: &Grid, season: Season, phase: DayPhase) -> PowerMap {
+    propagate(
+        tiles,
+        |building_type| building_type.flows_at(season, phase),
+        |flows| flows.power_out,
+        |flows| flows.power_in,
+    )
+}
+
+/// Hand out what `supply` tiles produce to connected `demand` tiles, with
+/// each building's flows given by `flows_of`; bit x of row y of the result
+/// is set for every reached tile whose demand is met
+pub fn propagate(
+    tiles: &Grid,
+    flows_of: impl Fn(BuildingType) -> Flows,
+    supply: fn(&Flows) -> u32,
+    demand: fn(&Flows) -> u32,
+) -> [u16; GRID_SIZE] {
+    let flows = |x: usize, y: usize| flows_of(buildings::tile_type(tiles[y][x]));
+    let mut served = [0u16; GRID_SIZE];
+    let mut visited = [[false; GRID_SIZE]; GRID_SIZE];
+    let mut cluster = [(0u8, 0u8); GRID_SIZE * GRID_SIZE];
+
+    for sy in 0..GRID_SIZE {
+        for sx in 0..GRID_SIZE {
+            if visited[sy][sx] || supply(&flows(sx, sy)) == 0 {
+                continue;
+            }
+
+            // Collect the cluster in BFS order; `cluster` doubles as the queue
+            visited[sy][sx] = true;
+            cluster[0] = (sx as u8, sy as u8);
+            let (mut head, mut len) = (0, 1);
+            let mut capacity: u32 = 0;
+            while head < len {
+                let (x, y) = (cluster[head].0 as usize, cluster[head].1 as usize);
+                head += 1;
+                capacity += supply(&flows(x, y));
+                for (nx, ny) in network::neighbors(x, y) {
+                    if !visited[ny][nx] && tiles[ny][nx] != BuildingType::Empty as u8 {
+                        visited[ny][nx] = true;
+                        cluster[len] = (nx as u8, ny as u8);
+                        len += 1;
+                    }
+                }
+            }
+
+            for &(x, y) in cluster[..len].iter() {
+                let needed = demand(&flows(x as usize, y as usize));
+                if needed <= capacity {
+                    capacity -= needed;
+                    served[y as usize] |= 1 << x;
+                }
+            }
+        }
+    }
+    served
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLANT: u8 = BuildingType::PowerPlant as u8;
+    const SOLAR: u8 = BuildingType::SolarPlant as u8;
+    const HOME: u8 = BuildingType::Residential as u8;
+
+    #[test]
+    fn power_reaches_connected_tiles_only() {
+        let mut tiles = [[0u8; GRID_SIZE]; GRID_SIZE];
+        tiles[0][0] = PLANT;
+        tiles[0][1] = network::ROAD_TILE;
+        tiles[0][2] = HOME;
+        tiles[5][5] = HOME;
+
+        let map = compute_power(&tiles, Season::Spring, DayPhase::Day);
+        assert!(is_powered(&map, 2, 0));
+        assert!(!is_powered(&map, 5, 5));
+
+        // Bulldozing the road cuts the home off
+        tiles[0][1] = 0;
+        let map = compute_power(&tiles, Season::Spring, DayPhase::Day);
+        assert!(!is_powered(&map, 2, 0));
+    }
+
+    #[test]
+    fn solar_plants_go_dark_at_night() {
+        let mut tiles = [[0u8; GRID_SIZE]; GRID_SIZE];
+        tiles[3][3] = SOLAR;
+        tiles[3][4] = HOME;
+
+        let map = compute_power(&tiles, Season::Summer, DayPhase::Day);
+        assert!(is_powered(&map, 4, 3));
+        let map = compute_power(&tiles, Season::Summer, DayPhase::Night);
+        assert!(!is_powered(&map, 4, 3));
+    }
+
+    #[test]
+    fn consumers_past_the_capacity_stay_unpowered() {
+        let mut tiles = [[0u8; GRID_SIZE]; GRID_SIZE];
+        tiles[0][0] = PLANT;
+        tiles[0][1] = HOME;
+        tiles[0][2] = HOME;
+        tiles[0][3] = HOME;
+
+        let map = propagate(
+            &tiles,
+            |building_type| Flows {
+                power_out: if building_type == BuildingType::PowerPlant {
+                    5
+                } else {
+                    0
+                },
+                power_in: if building_type == BuildingType::Residential {
+                    2
+                } else {
+                    0
+                },
+                ..Flows::default()
+            },
+            |flows| flows.power_out,
+            |flows| flows.power_in,
+        );
+        assert!(is_powered(&map, 1, 0));
+        assert!(is_powered(&map, 2, 0));
+        assert!(!is_powered(&map, 3, 0));
+    }
+}
@@ -0,0 +1,76 @@
+//! Prestige.
+//!
+//! A mayor can start their city over with `prestige_city`: the grid, the
+//! treasury and everything built on them are wiped, but the final score of
+//! the run buys a permanent bonus recorded on the mayor's profile. The
+//! bonus raises all income and the starting treasury of every later run,
+//! and adds up across runs to `MAX_PRESTIGE_BONUS_PCT`.
+//!
+//! The score is the population plus a point per `MONEY_PER_POINT` of net
+//! worth, `City::net_worth`: the treasury less the loan and any overdraft.
+//! Sandbox cities and cities playing a scenario can't prestige. The chunks
+//! a city bought stay its own across runs; see `City::start_over`.
+
+use crate::City;
+
+/// Net worth per point of score
+pub const MONEY_PER_POINT: u64 = 100;
+
+/// Score a run needs before the city can prestige
+pub const MIN_PRESTIGE_SCORE: u64 = 1_000;
+
+/// Score per percent of bonus earned by a run
+pub const SCORE_PER_BONUS_PCT: u64 = 1_000;
+
+/// Most bonus a single run can earn, in percent
+pub const MAX_BONUS_PER_PRESTIGE_PCT: u16 = 10;
+
+/// Cap on the bonus summed over all runs, in percent
+pub const MAX_PRESTIGE_BONUS_PCT: u16 = 100;
+
+/// Final score of the city's current run
+pub fn score(city: &City) -> u64 {
+    city.population as u64 + city.net_worth() / MONEY_PER_POINT
+}
+
+/// Bonus, in percent, a run scoring `score` earns
+pub fn bonus_pct(score: u64) -> u16 {
+    (score / SCORE_PER_BONUS_PCT).min(MAX_BONUS_PER_PRESTIGE_PCT as u64) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budget::{Budget, ServiceFunding};
+    use crate::taxes;
+
+    #[test]
+    fn overdraft_counts_against_the_score() {
+        let mut city = City::for_tests(50_000);
+        let clean = score(&city);
+        city.overdraft = 20_000;
+        assert_eq!(score(&city), clean - 20_000 / MONEY_PER_POINT);
+        city.overdraft = 100_000;
+        assert_eq!(score(&city), 0);
+    }
+
+    #[test]
+    fn starting_over_resets_the_run_settings() {
+        let mut city = City::for_tests(50_000);
+        city.budget.parks_pct = 40;
+        city.funding.police_pct = 150;
+        city.tax_rate_bps = taxes::MAX_TAX_RATE_BPS;
+        city.quest_day = 7;
+        city.quest_tax = 900;
+        city.quest_claimed_day = Some(7);
+        city.chunks_owned = 3;
+
+        city.start_over(0);
+        assert_eq!(city.budget, Budget::default());
+        assert_eq!(city.funding, ServiceFunding::default());
+        assert_eq!(city.tax_rate_bps, taxes::NOMINAL_TAX_RATE_BPS);
+        assert_eq!((city.quest_day, city.quest_tax), (0, 0));
+        assert_eq!(city.quest_claimed_day, Some(7));
+        assert_eq!(city.chunks_owned, 3);
+    }
+}
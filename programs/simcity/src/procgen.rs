@@ -0,0 +1,166 @@
+//! Procedural terrain.
+//!
+//! Every city starts on its own map: `initialize_city` derives
+//! `City::terrain_seed` from the authority and grows the terrain from it. A
+//! river winds across the grid from one edge to the opposite one, up to
+//! `MAX_LAKES` lakes open up and forest clusters grow in between. The
+//! north-west `TOWN_SITE` tiles square are left open grass for the town to
+//! be founded on, so the first roads and homes always have room.
+//!
+//! Elevation is grown from the same seed on top of the terrain: the land
+//! lies just above the water, hills rise out of it, mountains stand high
+//! and the town site is a flat terrace above the flood line.
+//!
+//! The generator only depends on the seed, so a client holding the seed
+//! redraws the very map the program generated.
+
+use crate::elevation::{FLOOD_LEVEL, MAX_ELEVATION};
+use crate::randomness::{CityRandomness, RandomnessSource};
+use crate::terrain::{self, Terrain};
+use crate::GRID_SIZE;
+
+/// Side of the open north-west corner every map keeps for the town
+pub const TOWN_SITE: usize = GRID_SIZE / 2;
+
+/// Most lakes on a generated map
+pub const MAX_LAKES: u64 = 2;
+
+/// Fewest and most forest clusters on a generated map
+pub const MIN_FORESTS: u64 = 2;
+pub const MAX_FORESTS: u64 = 4;
+
+/// Chance, in percent, that a tile within a forest cluster is wooded
+pub const FOREST_DENSITY_PCT: u64 = 70;
+
+/// Fewest and most hills on a generated map
+pub const MIN_HILLS: u64 = 1;
+pub const MAX_HILLS: u64 = 3;
+
+/// Height of mountain terrain, at the least
+pub const MOUNTAIN_ELEVATION: u8 = 6;
+
+/// Terrain codes grown from `seed`
+pub fn generate(seed: u64) -> [[u8; GRID_SIZE]; GRID_SIZE] {
+    let mut terrain = [[u8::from(Terrain::Grass); GRID_SIZE]; GRID_SIZE];
+    let mut rng = CityRandomness::for_step(seed, 0);
+    let size = GRID_SIZE as u64;
+
+    // The river runs north to south or west to east past the town site,
+    // drifting by at most a tile per row so it never breaks up
+    let vertical = rng.below(2) == 0;
+    let mut lane = TOWN_SITE + 1 + rng.below((GRID_SIZE - TOWN_SITE - 2) as u64) as usize;
+    for along in 0..GRID_SIZE {
+        let (x, y) = if vertical {
+            (lane, along)
+        } else {
+            (along, lane)
+        };
+        terrain[y][x] = Terrain::Water.into();
+        lane = match rng.below(3) {
+            0 => (lane - 1).max(TOWN_SITE),
+            1 => (lane + 1).min(GRID_SIZE - 2),
+            _ => lane,
+        };
+    }
+
+    for _ in 0..rng.below(MAX_LAKES + 1) {
+        let center = (rng.below(size) as usize, rng.below(size) as usize);
+        let radius = 1 + rng.below(2) as usize;
+        for (x, y) in disc(center, radius).filter(|&tile| !in_town_site(tile)) {
+            terrain[y][x] = Terrain::Water.into();
+        }
+    }
+
+    for _ in 0..MIN_FORESTS + rng.below(MAX_FORESTS - MIN_FORESTS + 1) {
+        let center = (rng.below(size) as usize, rng.below(size) as usize);
+        let radius = 1 + rng.below(3) as usize;
+        for (x, y) in disc(center, radius).filter(|&tile| !in_town_site(tile)) {
+            if terrain[y][x] == u8::from(Terrain::Grass) && rng.below(100) < FOREST_DENSITY_PCT {
+                terrain[y][x] = Terrain::Forest.into();
+            }
+        }
+    }
+    terrain
+}
+
+/// Elevation grown from `seed` over `terrain`, as generated from that seed
+pub fn generate_elevation(
+    seed: u64,
+    terrain: &[[u8; GRID_SIZE]; GRID_SIZE],
+) -> [[u8; GRID_SIZE]; GRID_SIZE] {
+    let mut elevation = [[FLOOD_LEVEL; GRID_SIZE]; GRID_SIZE];
+    // A stream of its own, so the terrain drawn from step 0 stays put
+    let mut rng = CityRandomness::for_step(seed, 1);
+    let size = GRID_SIZE as u64;
+
+    for _ in 0..MIN_HILLS + rng.below(MAX_HILLS - MIN_HILLS + 1) {
+        let center = (rng.below(size) as usize, rng.below(size) as usize);
+        let peak = 3 + rng.below(5) as u8;
+        for (x, y) in disc(center, peak as usize) {
+            let distance = x.abs_diff(center.0) + y.abs_diff(center.1);
+            let height = FLOOD_LEVEL + peak.saturating_sub(distance as u8);
+            elevation[y][x] = elevation[y][x].max(height);
+        }
+    }
+
+    for (y, row) in elevation.iter_mut().enumerate() {
+        for (x, height) in row.iter_mut().enumerate() {
+            *height = match terrain::terrain_of(terrain[y][x]) {
+                Terrain::Water => 0,
+                _ if in_town_site((x, y)) => FLOOD_LEVEL + 1,
+                Terrain::Mountain => (*height).max(MOUNTAIN_ELEVATION),
+                _ => *height,
+            }
+            .min(MAX_ELEVATION);
+        }
+    }
+    elevation
+}
+
+fn in_town_site((x, y): (usize, usize)) -> bool {
+    x < TOWN_SITE && y < TOWN_SITE
+}
+
+/// Tiles within Manhattan distance `radius` of `center`, clipped to the grid
+fn disc(center: (usize, usize), radius: usize) -> impl Iterator<Item = (usize, usize)> {
+    let (cx, cy) = center;
+    (cy.saturating_sub(radius)..(cy + radius + 1).min(GRID_SIZE))
+        .flat_map(move |y| {
+            (cx.saturating_sub(radius)..(cx + radius + 1).min(GRID_SIZE)).map(move |x| (x, y))
+        })
+        .filter(move |&(x, y)| x.abs_diff(cx) + y.abs_diff(cy) <= radius)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_seed_grows_the_same_map() {
+        for seed in [0, 1, 42, u64::MAX] {
+            let terrain = generate(seed);
+            assert_eq!(generate(seed), terrain);
+            assert_eq!(
+                generate_elevation(seed, &terrain),
+                generate_elevation(seed, &terrain)
+            );
+        }
+        assert_ne!(generate(1), generate(2));
+    }
+
+    #[test]
+    fn every_map_has_a_river_and_an_open_town_site() {
+        for seed in 0..32 {
+            let terrain = generate(seed);
+            let elevation = generate_elevation(seed, &terrain);
+            let water = u8::from(Terrain::Water);
+            assert!(terrain.iter().flatten().filter(|&&t| t == water).count() >= GRID_SIZE);
+            for y in 0..TOWN_SITE {
+                for x in 0..TOWN_SITE {
+                    assert_eq!(terrain[y][x], u8::from(Terrain::Grass));
+                    assert_eq!(elevation[y][x], FLOOD_LEVEL + 1);
+                }
+            }
+        }
+    }
+}
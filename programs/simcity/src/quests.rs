@@ -0,0 +1,131 @@
+//! Daily quests.
+//!
+//! Every day of clock time, `QUEST_DAY_SECONDS` long, one quest from
+//! `QUESTS` is on offer to every city: which one follows from the day index
+//! and the config's `quest_seed`, so an admin can reshuffle the rotation.
+//! A mayor whose city meets the quest collects its reward once that day
+//! with `claim_quest_reward`; the `daily_quest` view shows today's quest
+//! and the city's progress on it.
+//!
+//! Tax quests count the residential tax collected since the day began, on
+//! `City::quest_tax`; building and population quests look at the city as
+//! it stands.
+
+use anchor_lang::prelude::*;
+
+use crate::buildings::{self, BuildingType, FOOTPRINT_TILE};
+use crate::City;
+
+/// Seconds from one quest to the next
+pub const QUEST_DAY_SECONDS: i64 = 86_400;
+
+/// What a quest asks of the city
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuestGoal {
+    /// Have this many buildings of a type standing
+    Build(BuildingType, u32),
+    /// Collect this much residential tax during the day
+    CollectTax(u64),
+    /// Reach this population
+    Population(u32),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quest {
+    pub goal: QuestGoal,
+    /// Paid into the treasury when claimed
+    pub reward: u64,
+}
+
+pub const QUESTS: [Quest; 5] = [
+    Quest {
+        goal: QuestGoal::Build(BuildingType::Park, 3),
+        reward: 300,
+    },
+    Quest {
+        goal: QuestGoal::CollectTax(1_000),
+        reward: 500,
+    },
+    Quest {
+        goal: QuestGoal::Build(BuildingType::School, 2),
+        reward: 400,
+    },
+    Quest {
+        goal: QuestGoal::Population(500),
+        reward: 400,
+    },
+    Quest {
+        goal: QuestGoal::Build(BuildingType::FireStation, 2),
+        reward: 400,
+    },
+];
+
+/// Index of the day `now` falls in
+pub fn day_index(now: i64) -> u32 {
+    now.div_euclid(QUEST_DAY_SECONDS) as u32
+}
+
+/// Quest on offer on `day`
+pub fn of_day(day: u32, seed: u32) -> Quest {
+    QUESTS[(day.wrapping_add(seed) % QUESTS.len() as u32) as usize]
+}
+
+/// Progress of `city` towards `goal` on `day`, and the target
+pub fn progress(city: &City, goal: QuestGoal, day: u32) -> (u64, u64) {
+    match goal {
+        QuestGoal::Build(building_type, count) => {
+            let built = city
+                .tiles
+                .iter()
+                .flatten()
+                .filter(|&&tile| {
+                    tile != FOOTPRINT_TILE && buildings::tile_type(tile) == building_type
+                })
+                .count();
+            (built as u64, count as u64)
+        }
+        QuestGoal::CollectTax(amount) => {
+            let collected = if city.quest_day == day {
+                city.quest_tax
+            } else {
+                0
+            };
+            (collected, amount)
+        }
+        QuestGoal::Population(population) => (city.population as u64, population as u64),
+    }
+}
+
+/// Add residential tax collected at `now` to the day's tally, starting a
+/// new tally when the day has turned
+pub fn tally_tax(city: &mut City, now: i64, tax: u64) {
+    let day = day_index(now);
+    if city.quest_day != day {
+        city.quest_day = day;
+        city.quest_tax = 0;
+    }
+    city.quest_tax = city.quest_tax.saturating_add(tax);
+}
+
+/// Today's quest as the `daily_quest` view reports it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuestStatus {
+    pub day: u32,
+    pub quest: Quest,
+    pub progress: u64,
+    pub target: u64,
+    pub claimed: bool,
+}
+
+pub fn status(city: &City, now: i64, seed: u32) -> QuestStatus {
+    let day = day_index(now);
+    let quest = of_day(day, seed);
+    let (progress, target) = progress(city, quest.goal, day);
+    QuestStatus {
+        day,
+        quest,
+        progress,
+        target,
+        claimed: city.quest_claimed_day == Some(day),
+    }
+}
@@ -0,0 +1,217 @@
+//! Randomness sources for simulation events.
+//!
+//! Simulation code only depends on `RandomnessSource`, so the sources here
+//! and a proof-checked one can be swapped without touching the event logic.
+//!
+//! Which source steps draw from is a deployment setting,
+//! `GameConfig::randomness_source`, never the player's choice: a player free
+//! to pick per call could project the coming rolls with one source and dodge
+//! a bad one with the other.
+//!
+//! By default each step draws from `CityRandomness`, a xorshift generator
+//! seeded from the city's `rng_seed` and step counter. The same city state
+//! always rolls the same events, on the base layer and on the ephemeral
+//! rollup alike, and projections see the events the real step will. A
+//! deployment set to `RandomnessMode::SlotHashes` draws from the SlotHashes
+//! sysvar instead, and refuses to step a city without it.
+//!
+//! That makes future rolls predictable to anyone reading the account, and
+//! the SlotHashes source is grindable by a validator. A deployment with a
+//! `randomness_oracle` reseeds every city on a fixed schedule: every
+//! `reseed_interval_steps` steps, the step catching up stops and requests
+//! randomness, and the oracle answers through `consume_randomness`. Its
+//! output is mixed into the city's seed and drives only the steps after the
+//! request. Mayors can't ask for a reseed, so a bad roll seen with
+//! `project_steps` can't be rerolled away; a projection is only good up to
+//! the next scheduled reseed.
+//!
+//! This is a trusted oracle, not a VRF: the program checks the answer's
+//! signer, not a proof. A deployment trusts its `randomness_oracle` to
+//! answer every request, with output it neither biases nor shares with
+//! players. From request to answer the city doesn't step at all, so the
+//! wait can't be used to sit out the disaster, fire and crime rolls. An
+//! oracle that hasn't answered within `randomness_timeout_seconds` is given
+//! up on: the request lapses and the city steps on with its own generator
+//! until the next reseed.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::SysvarId;
+
+use crate::{City, CityError};
+
+/// Source simulation steps draw from, see `GameConfig::randomness_source`
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub enum RandomnessMode {
+    /// `CityRandomness`
+    #[default]
+    City,
+    /// `SlotHashRandomness`
+    SlotHashes,
+}
+
+pub trait RandomnessSource {
+    fn next_u64(&mut self) -> u64;
+
+    /// Uniform-ish value in `0..bound` (returns 0 when `bound` is 0)
+    fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            0
+        } else {
+            self.next_u64() % bound
+        }
+    }
+}
+
+/// SplitMix64 step, used to stretch a seed into a stream of values
+pub fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Deterministic per-city randomness: xorshift64* seeded from the city's
+/// seed mixed with the number of the step being simulated
+pub struct CityRandomness {
+    state: u64,
+}
+
+impl CityRandomness {
+    pub fn for_step(seed: u64, step: u64) -> Self {
+        let mut mixed = seed ^ step;
+        // xorshift is stuck at zero, so never start there
+        let state = splitmix64(&mut mixed).max(1);
+        Self { state }
+    }
+}
+
+impl RandomnessSource for CityRandomness {
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+/// Seed for a new city, derived from its authority
+pub fn city_seed(authority: &Pubkey) -> u64 {
+    let mut seed = 0u64;
+    for word in authority.to_bytes().chunks_exact(8) {
+        seed ^= u64::from_le_bytes(word.try_into().unwrap());
+        splitmix64(&mut seed);
+    }
+    seed
+}
+
+/// `seed` with the oracle's output mixed in
+pub fn mix_seed(seed: u64, randomness: &[u8; 32]) -> u64 {
+    let mut mixed = seed;
+    for word in randomness.chunks_exact(8) {
+        mixed ^= u64::from_le_bytes(word.try_into().unwrap());
+        splitmix64(&mut mixed);
+    }
+    mixed
+}
+
+/// Cheap randomness from the most recent SlotHashes entry mixed with city
+/// state. Grindable by a determined validator, so only use it where oracle
+/// latency isn't worth paying.
+pub struct SlotHashRandomness {
+    state: u64,
+}
+
+impl SlotHashRandomness {
+    pub fn new(slot_hashes: &AccountInfo, city: &City) -> Result<Self> {
+        require_keys_eq!(
+            slot_hashes.key(),
+            SlotHashes::id(),
+            CityError::InvalidRandomnessSource
+        );
+
+        // SlotHashes layout: u64 entry count, then (u64 slot, [u8; 32] hash) entries,
+        // newest first. Only the newest hash is read to keep this cheap.
+        let data = slot_hashes.try_borrow_data()?;
+        require!(data.len() >= 48, CityError::InvalidRandomnessSource);
+
+        let mut state = 0u64;
+        for chunk in data[8..48].chunks_exact(8) {
+            state ^= u64::from_le_bytes(chunk.try_into().unwrap());
+            splitmix64(&mut state);
+        }
+        for word in city.authority.to_bytes().chunks_exact(8) {
+            state ^= u64::from_le_bytes(word.try_into().unwrap());
+            splitmix64(&mut state);
+        }
+        state ^= city.money ^ (city.population as u64) ^ (city.last_updated as u64);
+
+        Ok(Self { state })
+    }
+}
+
+impl RandomnessSource for SlotHashRandomness {
+    fn next_u64(&mut self) -> u64 {
+        splitmix64(&mut self.state)
+    }
+}
+
+/// Slot-hash source for a step under `mode`, `None` meaning the city's own
+/// generator. A SlotHashes deployment refuses to step without the sysvar,
+/// and a City one ignores it when supplied.
+pub fn slot_source(
+    mode: RandomnessMode,
+    slot_hashes: Option<&AccountInfo>,
+    city: &City,
+) -> Result<Option<SlotHashRandomness>> {
+    match mode {
+        RandomnessMode::City => Ok(None),
+        RandomnessMode::SlotHashes => {
+            let slot_hashes = slot_hashes.ok_or(CityError::SlotHashesRequired)?;
+            SlotHashRandomness::new(slot_hashes, city).map(Some)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slot_hashes_deployments_need_the_sysvar() {
+        let city = City::for_tests(0);
+        let err = slot_source(RandomnessMode::SlotHashes, None, &city).err();
+        assert_eq!(err, Some(CityError::SlotHashesRequired.into()));
+        assert!(slot_source(RandomnessMode::City, None, &city)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn city_deployments_ignore_a_supplied_sysvar() {
+        let city = City::for_tests(0);
+        let key = SlotHashes::id();
+        let owner = Pubkey::default();
+        let (mut lamports, mut data) = (0u64, vec![1u8; 48]);
+        let slot_hashes = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        assert!(slot_source(RandomnessMode::City, Some(&slot_hashes), &city)
+            .unwrap()
+            .is_none());
+        assert!(
+            slot_source(RandomnessMode::SlotHashes, Some(&slot_hashes), &city)
+                .unwrap()
+                .is_some()
+        );
+    }
+}
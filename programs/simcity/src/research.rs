@@ -0,0 +1,105 @@
+//! Research.
+//!
+//! Techs are researched one at a time with `research`: the price is paid up
+//! front, and the tech is learned after its research time in simulation
+//! steps. Some techs need others first. Learned techs are a bitfield on
+//! `City::techs`, bit `tech as u8`, and unlock:
+//!
+//! - buildings, which `place_building` refuses until then, see
+//!   `BuildingType::required_tech`
+//! - high-density zoning
+//! - efficiency bonuses to business income
+
+use anchor_lang::prelude::*;
+
+use crate::buildings::BuildingType;
+use crate::density::Density;
+
+/// Extra industrial income with `Tech::Automation`, in percent
+pub const AUTOMATION_INCOME_BONUS_PCT: u64 = 15;
+
+/// Extra commercial income with `Tech::Logistics`, in percent
+pub const LOGISTICS_INCOME_BONUS_PCT: u64 = 15;
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tech {
+    /// Solar plants
+    Photovoltaics,
+    /// Recycling centers
+    Recycling,
+    /// Train stations
+    Railways,
+    /// Airports; needs railways
+    Aviation,
+    /// High-density zones
+    HighRise,
+    /// More industrial income
+    Automation,
+    /// More commercial income; needs railways
+    Logistics,
+}
+
+impl Tech {
+    pub fn bit(self) -> u32 {
+        1 << self as u8
+    }
+
+    /// Price and research time in steps
+    pub fn cost(self) -> (u64, u8) {
+        match self {
+            Tech::Photovoltaics => (1_000, 5),
+            Tech::Recycling => (600, 3),
+            Tech::Railways => (800, 4),
+            Tech::Aviation => (2_000, 8),
+            Tech::HighRise => (1_500, 6),
+            Tech::Automation => (1_200, 5),
+            Tech::Logistics => (1_200, 5),
+        }
+    }
+
+    /// Techs that must be learned first, as a bitfield
+    pub fn prerequisites(self) -> u32 {
+        match self {
+            Tech::Aviation | Tech::Logistics => Tech::Railways.bit(),
+            _ => 0,
+        }
+    }
+}
+
+impl BuildingType {
+    /// Tech the city must have learned before it can build this
+    pub fn required_tech(self) -> Option<Tech> {
+        match self {
+            BuildingType::SolarPlant => Some(Tech::Photovoltaics),
+            BuildingType::RecyclingCenter => Some(Tech::Recycling),
+            BuildingType::TrainStation => Some(Tech::Railways),
+            BuildingType::Airport => Some(Tech::Aviation),
+            _ => None,
+        }
+    }
+}
+
+impl Density {
+    /// Tech the city must have learned before zones can reach this density
+    pub fn required_tech(self) -> Option<Tech> {
+        match self {
+            Density::High => Some(Tech::HighRise),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the `techs` bitfield includes `tech`
+pub fn has(techs: u32, tech: Tech) -> bool {
+    techs & tech.bit() != 0
+}
+
+/// Industrial income, in percent of the usual, given learned `techs`
+pub fn industrial_income_pct(techs: u32) -> u64 {
+    100 + has(techs, Tech::Automation) as u64 * AUTOMATION_INCOME_BONUS_PCT
+}
+
+/// Commercial income, in percent of the usual, given learned `techs`
+pub fn commercial_income_pct(techs: u32) -> u64 {
+    100 + has(techs, Tech::Logistics) as u64 * LOGISTICS_INCOME_BONUS_PCT
+}
@@ -0,0 +1,108 @@
+//! Power, water and goods.
+//!
+//! Every step the census totals what buildings produce and consume. Where
+//! power and water actually reach is worked out tile by tile in `power` and
+//! `water`. A city short on power or water overall stops working: no
+//! population growth and no workplace income until supply catches up.
+//! Goods come from industry and are sold by commerce; commercial income
+//! shrinks with the share of its goods demand that industry can't meet.
+
+use anchor_lang::prelude::*;
+
+use crate::buildings::BuildingType;
+
+/// Per-building resource flows, per step
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Flows {
+    pub power_out: u32,
+    pub power_in: u32,
+    pub water_out: u32,
+    pub water_in: u32,
+    pub goods_out: u32,
+    pub goods_in: u32,
+}
+
+impl BuildingType {
+    pub fn flows(self) -> Flows {
+        let (power_out, water_out, goods_out) = match self {
+            BuildingType::PowerPlant => (200, 0, 0),
+            BuildingType::SolarPlant => (60, 0, 0),
+            BuildingType::WaterPump => (0, 200, 0),
+            BuildingType::WaterTower => (0, 60, 0),
+            BuildingType::Industrial => (0, 0, 10),
+            BuildingType::Port => (0, 0, 30),
+            _ => (0, 0, 0),
+        };
+        let (power_in, water_in, goods_in) = match self {
+            BuildingType::Residential => (2, 2, 0),
+            BuildingType::Commercial => (3, 2, 5),
+            BuildingType::Industrial => (5, 3, 0),
+            BuildingType::PoliceStation
+            | BuildingType::FireStation
+            | BuildingType::Hospital
+            | BuildingType::School => (3, 1, 0),
+            BuildingType::Airport => (20, 5, 0),
+            BuildingType::TrainStation => (10, 2, 0),
+            BuildingType::Stadium => (15, 5, 0),
+            BuildingType::CityHall => (10, 3, 0),
+            BuildingType::RecyclingCenter => (8, 2, 0),
+            BuildingType::University => (10, 3, 0),
+            BuildingType::WaterPump | BuildingType::Port => (5, 0, 0),
+            _ => (0, 0, 0),
+        };
+        Flows {
+            power_out,
+            power_in,
+            water_out,
+            water_in,
+            goods_out,
+            goods_in,
+        }
+    }
+}
+
+/// City-wide resource totals of the last step
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Default, Clone, Copy, Debug, PartialEq, Eq,
+)]
+pub struct Resources {
+    pub power_produced: u32,
+    pub power_consumed: u32,
+    pub water_produced: u32,
+    pub water_consumed: u32,
+    pub goods_produced: u32,
+    pub goods_consumed: u32,
+}
+
+impl Resources {
+    pub fn add(&mut self, flows: Flows) {
+        self.power_produced += flows.power_out;
+        self.power_consumed += flows.power_in;
+        self.water_produced += flows.water_out;
+        self.water_consumed += flows.water_in;
+        self.goods_produced += flows.goods_out;
+        self.goods_consumed += flows.goods_in;
+    }
+
+    pub fn power_deficit(&self) -> bool {
+        self.power_consumed > self.power_produced
+    }
+
+    pub fn water_deficit(&self) -> bool {
+        self.water_consumed > self.water_produced
+    }
+
+    /// Buildings only work while power and water demand are met
+    pub fn utilities_ok(&self) -> bool {
+        !self.power_deficit() && !self.water_deficit()
+    }
+
+    /// Share of goods demand met by industry, in percent
+    pub fn goods_supply_pct(&self) -> u64 {
+        if self.goods_consumed == 0 {
+            100
+        } else {
+            (self.goods_produced as u64 * 100 / self.goods_consumed as u64).min(100)
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Road tiers.
+//!
+//! Every road tile starts as a street and can be upgraded in place to an
+//! avenue and then a highway, each carrying more traffic before it is
+//! congested (see `traffic`). Tiers are stored per tile on
+//! `City::road_tiers` and reset whenever the tile is rebuilt or cleared.
+//!
+//! Placing a `BuildingType::Highway` builds a road straight at the highway
+//! tier: the grid holds a road tile, so it joins the road network like any
+//! other, and traffic capacity and noise read the tier.
+
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoadTier {
+    Street,
+    Avenue,
+    Highway,
+}
+
+impl RoadTier {
+    /// Typed view of a stored tier; unknown codes read as `Street`
+    pub fn from_code(code: u8) -> RoadTier {
+        match code {
+            1 => RoadTier::Avenue,
+            2 => RoadTier::Highway,
+            _ => RoadTier::Street,
+        }
+    }
+
+    /// Trips a road tile of this tier carries before it is congested
+    pub fn capacity(self) -> u32 {
+        match self {
+            RoadTier::Street => 12,
+            RoadTier::Avenue => 24,
+            RoadTier::Highway => 48,
+        }
+    }
+
+    /// The tier an upgrade leads to and its price, or `None` at the top
+    pub fn upgrade(self) -> Option<(RoadTier, u64)> {
+        match self {
+            RoadTier::Street => Some((RoadTier::Avenue, 40)),
+            RoadTier::Avenue => Some((RoadTier::Highway, 120)),
+            RoadTier::Highway => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buildings::BuildingType;
+    use crate::config::GameParams;
+    use crate::{network, noise, City};
+
+    #[test]
+    fn a_placed_highway_is_a_highway_tier_road() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(10_000);
+        city.place(0, 0, BuildingType::Highway, false, &params)
+            .unwrap();
+
+        assert!(network::is_road(city.tiles[0][0]));
+        assert_eq!(
+            RoadTier::from_code(city.road_tiers[0][0]),
+            RoadTier::Highway
+        );
+        assert!(city.is_connected(0, 0));
+        assert!(city.has_road_access(1, 0));
+        assert_eq!(
+            noise::emitted_by(city.tiles[0][0], city.road_tiers[0][0]),
+            3
+        );
+        assert!(city.upgrade_road(0, 0).is_err());
+    }
+
+    #[test]
+    fn only_highway_tier_roads_are_loud() {
+        let road = network::ROAD_TILE;
+        assert_eq!(noise::emitted_by(road, RoadTier::Street as u8), 0);
+        assert_eq!(noise::emitted_by(road, RoadTier::Avenue as u8), 0);
+        assert!(noise::emitted_by(road, RoadTier::Highway as u8) > 0);
+    }
+
+    #[test]
+    fn upgrades_climb_one_tier_at_a_time() {
+        let mut tier = RoadTier::Street;
+        let mut capacity = tier.capacity();
+        while let Some((next, cost)) = tier.upgrade() {
+            assert!(cost > 0 && next.capacity() > capacity);
+            tier = next;
+            capacity = tier.capacity();
+        }
+        assert_eq!(tier, RoadTier::Highway);
+    }
+}
@@ -0,0 +1,59 @@
+//! Scenarios.
+//!
+//! The config admin publishes `Scenario`s: a starting treasury and an
+//! objective to reach `target_population` residents with pollution nowhere
+//! above `max_pollution`, within `step_limit` steps. A mayor picks one for
+//! a brand new, non-sandbox city with `start_scenario`, and anyone may then
+//! call `check_scenario_completion`, which marks the city won as soon as
+//! the objective is met, or lost once the steps run out.
+
+use anchor_lang::prelude::*;
+
+pub const SCENARIO_SEED: &[u8] = b"scenario";
+
+/// Starting state and objective of a scenario
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScenarioParams {
+    /// Treasury the city starts with in place of the usual
+    pub starting_money: u64,
+    pub target_population: u32,
+    /// Highest pollution level any tile may have, see `pollution`
+    pub max_pollution: u8,
+    /// Steps the city has to meet the objective
+    pub step_limit: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Scenario {
+    pub id: u32,
+    pub params: ScenarioParams,
+}
+
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Default, Clone, Copy, Debug, PartialEq, Eq,
+)]
+pub enum ScenarioStatus {
+    /// Free play
+    #[default]
+    None,
+    InProgress,
+    Won,
+    Lost,
+}
+
+/// Where a city playing `params` stands after `steps_taken` steps
+pub fn outcome(
+    params: &ScenarioParams,
+    population: u32,
+    peak_pollution: u8,
+    steps_taken: u64,
+) -> ScenarioStatus {
+    if population >= params.target_population && peak_pollution <= params.max_pollution {
+        ScenarioStatus::Won
+    } else if steps_taken >= params.step_limit {
+        ScenarioStatus::Lost
+    } else {
+        ScenarioStatus::InProgress
+    }
+}
@@ -0,0 +1,85 @@
+//! Seasons.
+//!
+//! The year turns every `season_length_steps` steps, from spring through
+//! winter, and the current season is stored on the city so clients can
+//! show the weather. Each season bends the simulation a little:
+//!
+//! - spring: families move in faster
+//! - summer: long sunny days boost solar plants
+//! - autumn: no change
+//! - winter: homes burn extra power on heating, solar plants yield less and
+//!   fewer people move in
+
+use anchor_lang::prelude::*;
+
+use crate::buildings::BuildingType;
+use crate::resources::Flows;
+
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Default, Clone, Copy, Debug, PartialEq, Eq,
+)]
+pub enum Season {
+    #[default]
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    /// Season of the year at `step`; a zero length never turns the year
+    pub fn at(step: u64, length_steps: u32) -> Self {
+        if length_steps == 0 {
+            return Season::Spring;
+        }
+        match step / length_steps as u64 % 4 {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    /// Population growth, in percent of the usual
+    pub fn growth_pct(self) -> u32 {
+        match self {
+            Season::Spring => 125,
+            Season::Winter => 75,
+            _ => 100,
+        }
+    }
+
+    /// Solar output, in percent of the usual
+    pub fn solar_pct(self) -> u32 {
+        match self {
+            Season::Summer => 150,
+            Season::Winter => 50,
+            _ => 100,
+        }
+    }
+
+    /// Power homes draw, in percent of the usual
+    pub fn heating_pct(self) -> u32 {
+        match self {
+            Season::Winter => 150,
+            _ => 100,
+        }
+    }
+}
+
+impl BuildingType {
+    /// `flows` adjusted for the season
+    pub fn seasonal_flows(self, season: Season) -> Flows {
+        let mut flows = self.flows();
+        match self {
+            BuildingType::SolarPlant => {
+                flows.power_out = flows.power_out * season.solar_pct() / 100
+            }
+            BuildingType::Residential => {
+                flows.power_in = flows.power_in * season.heating_pct() / 100
+            }
+            _ => {}
+        }
+        flows
+    }
+}
@@ -0,0 +1,1455 @@
+//! The simulation step, split into explicit ordered phases.
+//!
+//! Each phase reads the city plus the `StepState` produced by earlier phases,
+//! and may write to both. Phases run in this order:
+//!
+//! 1. construction - building sites count down and open
+//! 2. services     - tile census and service availability
+//! 3. economy      - money in and out of the treasury
+//! 4. population   - growth and decline of residents
+//! 5. development  - zoned tiles grow into buildings
+//! 6. environment  - slow-moving world state (pollution, decay, ...) and
+//!    random events
+
+use anchor_lang::prelude::*;
+
+use crate::adjacency;
+use crate::bankruptcy;
+use crate::budget::Department;
+use crate::buildings::{self, BuildingType};
+use crate::config::{GameParams, MAX_EDUCATION_INCOME_BONUS_PCT};
+use crate::coverage::{self, Service};
+use crate::crime::{self, CrimeFactors};
+use crate::day_night::DayPhase;
+use crate::demand::{self, DemandFactors};
+use crate::density;
+use crate::disaster::{Disaster, EARTHQUAKE_DAMAGE};
+use crate::education;
+use crate::elections;
+use crate::elevation::{self, FLOOD_DAMAGE};
+use crate::events::{
+    BuildingsRuined, CaretakerEnded, CityBankrupt, ConstructionCompleted, CrimeIncident,
+    DayPhaseChanged, DisasterStruck, ElectionHeld, FireStarted, FireUpdate, Flooded,
+    GarbageOverflow, HighCrime, InsuranceLapsed, MilestonesReached, ResearchCompleted,
+    ResidentsLeft, SeasonChanged, WaterShortage,
+};
+use crate::fire::{self, SpreadResult};
+use crate::garbage;
+use crate::health::{self, HealthFactors};
+use crate::land_value::{self, Assessment};
+use crate::milestones;
+use crate::network::Grid;
+use crate::noise;
+use crate::ordinances;
+use crate::parks;
+use crate::pollution;
+use crate::power;
+use crate::quests;
+use crate::randomness::{CityRandomness, RandomnessSource};
+use crate::research;
+use crate::resources::Resources;
+use crate::season::Season;
+use crate::taxes;
+use crate::traffic;
+use crate::water;
+use crate::zoning;
+use crate::{features, City, GRID_SIZE, MAX_CONDITION, MAX_HAPPINESS};
+
+/// Happiness parks add at full parks funding when every home is near one
+pub const PARK_HAPPINESS: u32 = 10;
+
+/// Happiness each stadium adds at full parks funding
+pub const STADIUM_HAPPINESS: u32 = 10;
+
+/// Happiness the monument adds at full parks funding
+pub const MONUMENT_HAPPINESS: u32 = 15;
+
+/// Extra housing, in percent, for each service reaching a home
+pub const SERVICE_HOUSING_BONUS_PCT: u32 = 10;
+
+/// Happiness added when police, fire and health services reach every home
+pub const SERVICE_HAPPINESS: u32 = 15;
+
+/// Services counted towards `SERVICE_HAPPINESS`: police, fire and health
+const SERVICES_AT_HOME: u32 = 3;
+
+/// Extra residential tax, in percent, collected with a working city hall
+pub const CITY_HALL_TAX_BONUS_PCT: u64 = 10;
+
+/// Upper bound on crimes per step, keeping the event log bounded
+pub const MAX_CRIME_INCIDENTS_PER_STEP: u8 = 4;
+
+/// Intermediate state handed from one phase to the next during a step
+#[derive(Default, Clone, Copy, Debug)]
+pub struct StepState {
+    /// The tile grid with construction sites left out; later phases only
+    /// see buildings that work
+    pub operational: Grid,
+    /// Tiles still under construction, and those finished this step
+    pub construction_sites: u32,
+    pub construction_completed: u32,
+    /// Zoned tiles that started developing this step
+    pub developed: u32,
+    /// Buildings that wore out into ruins this step
+    pub ruined: u32,
+    /// Tile counts by type, filled in by the services phase
+    pub residential_tiles: u32,
+    /// Residential tiles with power and water, weighted by density, see
+    /// `density`
+    pub livable_units: u32,
+    pub commercial_tiles: u32,
+    pub industrial_tiles: u32,
+    pub stadiums: u32,
+    /// Working commercial tiles with a residential neighbour
+    pub commercial_near_homes: u32,
+    /// Residential-industrial neighbour pairs
+    pub industry_near_homes: u32,
+    /// Working landmarks
+    pub city_hall: bool,
+    pub monument: bool,
+    /// The same counts restricted to tiles connected to the road network;
+    /// dry commercial tiles are left out
+    pub connected_residential: u32,
+    pub connected_commercial: u32,
+    pub connected_industrial: u32,
+    /// Condition, in percent, times density summed over the same connected
+    /// workplaces; worn-down workplaces offer fewer jobs, denser ones more
+    pub commercial_capacity: u32,
+    pub industrial_capacity: u32,
+    /// Residential and commercial tiles in police reach
+    pub policed_tiles: u32,
+    /// Residential tiles in hospital reach
+    pub treated_residential: u32,
+    /// Police, fire and health services reaching each residential tile,
+    /// summed over them
+    pub serviced_residential: u32,
+    /// Residential tiles in school reach
+    pub educated_residential: u32,
+    /// Working universities
+    pub universities: u32,
+    /// Education level, see `education`
+    pub education_level: u8,
+    /// Per-tile service coverage bitmap, see `coverage::Service`
+    pub coverage: Grid,
+    /// Power, water and goods produced and consumed
+    pub resources: Resources,
+    /// Residential and commercial tiles water didn't reach
+    pub dry_tiles: u32,
+    /// Frontage congestion summed over connected commercial tiles that
+    /// offer jobs, see `traffic`
+    pub commercial_congestion: u32,
+    /// Per-tile noise level, see `noise`
+    pub noise: Grid,
+    /// Per-tile park greenery level, see `parks`
+    pub greenery: Grid,
+    /// Residential tiles within reach of a park
+    pub residential_near_parks: u32,
+    /// Pollution level summed over residential tiles
+    pub residential_pollution: u32,
+    /// Appeal of a livable home after noise, pollution and parks, in
+    /// percent, times condition and density, summed over livable homes
+    pub residential_desirability: u32,
+    /// Taxes collected by zone, filled in by the economy phase
+    pub residential_tax: u64,
+    pub commercial_income: u64,
+    pub industrial_income: u64,
+    /// Jobs offered by connected workplaces, residents of working age, and
+    /// those filling the jobs
+    pub jobs: u32,
+    pub workforce: u32,
+    pub employed: u32,
+    /// Maintenance due by department after funding levels, its total, and
+    /// whether the treasury covered it
+    pub safety_upkeep: u64,
+    pub utilities_upkeep: u64,
+    pub parks_upkeep: u64,
+    pub other_upkeep: u64,
+    pub upkeep: u64,
+    pub upkeep_paid: bool,
+    /// Loan interest due this step, and whether the treasury covered it
+    pub loan_interest: u64,
+    pub loan_interest_paid: bool,
+    /// Disaster insurance premium charged this step
+    pub insurance_premium: u64,
+    /// Price of the ordinances in force this step
+    pub ordinance_cost: u64,
+    /// Workforce without a job and the welfare charged for them
+    pub unemployed: u32,
+    pub welfare_cost: u64,
+    /// Garbage landfills and recycling centers on the road network can
+    /// collect, filled in by the services phase
+    pub garbage_capacity: u32,
+    /// Garbage thrown out this step, filled in by the population phase
+    pub garbage_produced: u32,
+    /// Residents gained this step, filled in by the population phase
+    pub population_growth: u32,
+    /// Residents who moved away unhappy, filled in by the population phase
+    pub population_decline: u32,
+    /// Residents the residential tiles can house, filled in by the services
+    /// phase
+    pub housing_capacity: u32,
+    /// Crimes this step and the money they took, filled in by the
+    /// environment phase
+    pub crime_incidents: u8,
+    pub money_stolen: u64,
+    /// Fire activity this step, filled in by the environment phase
+    pub fire: SpreadResult,
+    pub fire_started: bool,
+}
+
+/// Run one full simulation step over `city`. Random events draw from `rng`
+/// when supplied and from the city's own deterministic generator otherwise,
+/// and coverage is recomputed unless an up-to-date cached bitmap is passed
+/// in.
+pub fn run_step(
+    city: &mut City,
+    params: &GameParams,
+    now: i64,
+    rng: Option<&mut dyn RandomnessSource>,
+    cached_coverage: Option<Grid>,
+) -> StepState {
+    let mut state = StepState::default();
+    let mut city_rng = CityRandomness::for_step(city.rng_seed, city.step_count);
+    let rng: &mut dyn RandomnessSource = match rng {
+        Some(rng) => rng,
+        None => &mut city_rng,
+    };
+    let params = &city.effective_params(params);
+    turn_season(city, params);
+    turn_day(city, params, now);
+    construction_phase(city, &mut state);
+    research_phase(city);
+    services_phase(city, params, cached_coverage, &mut state);
+    economy_phase(city, params, &mut state);
+    quests::tally_tax(city, now, state.residential_tax);
+    population_phase(city, params, &mut state);
+    development_phase(city, params, &mut state);
+    environment_phase(city, params, &mut state, rng);
+    politics(city, params);
+    mark_milestones(city);
+    city.last_updated = now;
+    city.step_count += 1;
+    state
+}
+
+/// Rate the mayor and hold the election if one is due, see `elections`
+fn politics(city: &mut City, params: &GameParams) {
+    city.approval = elections::approval(city, params);
+    let approved = city.approval >= params.approval_threshold;
+    if elections::is_election_step(city.step_count, params) {
+        emit!(ElectionHeld {
+            authority: city.authority,
+            approval: city.approval,
+            won: approved,
+        });
+        if !approved {
+            city.caretaker = true;
+            return;
+        }
+    }
+    if city.caretaker && approved {
+        city.caretaker = false;
+        emit!(CaretakerEnded {
+            authority: city.authority,
+            approval: city.approval,
+        });
+    }
+}
+
+/// Record milestones reached for the first time, see `milestones`
+fn mark_milestones(city: &mut City) {
+    let reached = milestones::met(city) & !city.milestones_reached;
+    if reached != 0 {
+        city.milestones_reached |= reached;
+        emit!(MilestonesReached {
+            authority: city.authority,
+            milestones: reached,
+        });
+    }
+}
+
+/// Move on to the season of this step
+fn turn_season(city: &mut City, params: &GameParams) {
+    let season = Season::at(city.step_count, params.season_length_steps);
+    if season != city.season {
+        city.season = season;
+        emit!(SeasonChanged {
+            authority: city.authority,
+            season,
+        });
+    }
+}
+
+/// Move on to the time of day at this step's timestamp
+fn turn_day(city: &mut City, params: &GameParams, now: i64) {
+    let phase = DayPhase::at(now, params.day_length_seconds);
+    if phase != city.day_phase {
+        city.day_phase = phase;
+        emit!(DayPhaseChanged {
+            authority: city.authority,
+            phase,
+        });
+    }
+}
+
+/// Advance every construction site by a step. Sites that finish work from
+/// this step on.
+pub fn construction_phase(city: &mut City, state: &mut StepState) {
+    state.operational = city.tiles;
+    for (y, row) in city.construction.iter_mut().enumerate() {
+        for (x, steps) in row.iter_mut().enumerate() {
+            if *steps == 0 {
+                continue;
+            }
+            *steps -= 1;
+            if *steps == 0 {
+                state.construction_completed += 1;
+            } else {
+                state.construction_sites += 1;
+                state.operational[y][x] = 0;
+            }
+        }
+    }
+
+    if state.construction_completed > 0 {
+        emit!(ConstructionCompleted {
+            authority: city.authority,
+            completed: state.construction_completed,
+        });
+    }
+}
+
+/// Advance research by a step, learning the tech once its time is up
+pub fn research_phase(city: &mut City) {
+    let Some(tech) = city.researching else {
+        return;
+    };
+    city.research_steps_left = city.research_steps_left.saturating_sub(1);
+    if city.research_steps_left == 0 {
+        city.techs |= tech.bit();
+        city.researching = None;
+        emit!(ResearchCompleted {
+            authority: city.authority,
+            tech,
+        });
+    }
+}
+
+/// Count tiles by type so later phases don't rescan the grid, total up
+/// resource flows, work out which tiles power, water and each service reach,
+/// and assess land value
+pub fn services_phase(
+    city: &mut City,
+    params: &GameParams,
+    cached_coverage: Option<Grid>,
+    state: &mut StepState,
+) {
+    let tiles = state.operational;
+    // Service buildings shut down while last step's upkeep is unpaid. The
+    // cached overlay counts stations still under construction, so it is
+    // only good once every site is finished.
+    if !city.upkeep_unpaid {
+        state.coverage = cached_coverage
+            .filter(|_| state.construction_sites == 0)
+            .unwrap_or_else(|| coverage::compute_coverage(&tiles, params));
+    }
+    state.noise = noise::compute_noise(&tiles, &city.road_tiers);
+    state.greenery = parks::compute_greenery(&tiles);
+    let homeless_pct = if city.population == 0 {
+        0
+    } else {
+        (city.homeless as u64 * 100 / city.population as u64) as u8
+    };
+    city.land_value = land_value::compute_land_value(&Assessment {
+        tiles: &tiles,
+        coverage: &state.coverage,
+        noise: &state.noise,
+        greenery: &state.greenery,
+        pollution: &city.pollution,
+        elevation: &city.elevation,
+        homeless_pct,
+    });
+    city.powered = power::compute_power(&tiles, city.season, city.day_phase);
+    city.dry = water::compute_dry(&tiles);
+    if city.has_feature(features::TRAFFIC) {
+        city.traffic = traffic::compute_traffic(&tiles);
+        let traffic_pct = ordinances::traffic_pct(city.ordinances);
+        for load in city.traffic.iter_mut().flatten() {
+            *load = (*load as u32 * traffic_pct / 100) as u8;
+        }
+        city.congestion = traffic::average_congestion_pct(&tiles, &city.traffic, &city.road_tiers);
+    } else {
+        city.traffic = [[0; GRID_SIZE]; GRID_SIZE];
+        city.congestion = 0;
+    }
+
+    for (y, row) in tiles.iter().enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            let connected = city.is_connected(x, y) as u32;
+            let condition = city.condition[y][x] as u32;
+            let density = density::factor(city.density[y][x]);
+            let building_type = buildings::tile_type(tile);
+            state
+                .resources
+                .add(building_type.flows_at(city.season, city.day_phase));
+            let dry = water::is_dry(&city.dry, x, y);
+            state.dry_tiles += dry as u32;
+            if matches!(
+                building_type,
+                BuildingType::Residential | BuildingType::Commercial
+            ) {
+                state.policed_tiles +=
+                    coverage::is_covered(&state.coverage, x, y, Service::Police) as u32;
+            }
+            match building_type {
+                BuildingType::Residential => {
+                    state.residential_tiles += 1;
+                    let powered = power::is_powered(&city.powered, x, y);
+                    let services = Service::ALL
+                        .iter()
+                        .filter(|&&service| coverage::is_covered(&state.coverage, x, y, service))
+                        .count() as u32;
+                    state.connected_residential += connected;
+                    state.treated_residential +=
+                        coverage::is_covered(&state.coverage, x, y, Service::Health) as u32;
+                    state.serviced_residential += [Service::Police, Service::Fire, Service::Health]
+                        .iter()
+                        .filter(|&&service| coverage::is_covered(&state.coverage, x, y, service))
+                        .count() as u32;
+                    state.educated_residential +=
+                        coverage::is_covered(&state.coverage, x, y, Service::Education) as u32;
+                    state.residential_near_parks += (state.greenery[y][x] > 0) as u32;
+                    state.residential_pollution += city.pollution[y][x] as u32;
+                    // Homes without power or water house and attract nobody
+                    if dry || !powered {
+                        continue;
+                    }
+                    state.housing_capacity += housing_capacity(params, density, services);
+                    state.livable_units += density;
+                    let penalty = state.noise[y][x] as u32 * params.noise_growth_penalty_pct as u32
+                        + city.pollution[y][x] as u32 * params.pollution_growth_penalty_pct as u32;
+                    let bonus = state.greenery[y][x] as u32 * params.park_growth_bonus_pct as u32;
+                    state.residential_desirability +=
+                        (100 + bonus).saturating_sub(penalty) * condition * density / 100;
+                }
+                BuildingType::Commercial => {
+                    state.commercial_tiles += 1;
+                    let working = connected * !dry as u32;
+                    state.connected_commercial += working;
+                    state.commercial_capacity += working * condition * density;
+                    state.commercial_near_homes += working
+                        * (adjacency::count_neighbors(&tiles, x, y, BuildingType::Residential) > 0)
+                            as u32;
+                    state.commercial_congestion += working
+                        * traffic::frontage_congestion_pct(&city.traffic, &city.road_tiers, x, y)
+                            as u32;
+                }
+                BuildingType::Industrial => {
+                    state.industrial_tiles += 1;
+                    state.connected_industrial += connected;
+                    state.industrial_capacity += connected * condition * density;
+                    state.industry_near_homes +=
+                        adjacency::count_neighbors(&tiles, x, y, BuildingType::Residential);
+                }
+                BuildingType::Landfill | BuildingType::RecyclingCenter => {
+                    state.garbage_capacity += connected * garbage::capacity_of(building_type);
+                }
+                BuildingType::University => state.universities += 1,
+                BuildingType::Stadium => state.stadiums += 1,
+                BuildingType::CityHall => state.city_hall = true,
+                BuildingType::Monument => state.monument = true,
+                _ => {}
+            }
+        }
+    }
+
+    state.education_level = education::education_level(
+        state.residential_tiles,
+        state.educated_residential,
+        state.universities,
+    );
+    city.education = state.education_level;
+    city.resources = state.resources;
+
+    if state.dry_tiles > 0 {
+        emit!(WaterShortage {
+            authority: city.authority,
+            dry_tiles: state.dry_tiles,
+        });
+    }
+}
+
+/// Treasury income and expenses. Residents pay a per-capita tax, more with
+/// a city hall. Residents fill the jobs of connected workplaces, and each
+/// workplace earns per worker it employs, commercial tiles more in a better
+/// educated city and next to homes, and less on congested roads and with
+/// crime about, see `crime`. With `features::DEMOGRAPHICS`, residents
+/// without a job cost welfare. Happiness is worked out from all of the above, see `happiness`.
+/// Building upkeep is charged last; if the treasury can't cover it, it is
+/// emptied and services shut down next step.
+pub fn economy_phase(city: &mut City, params: &GameParams, state: &mut StepState) {
+    state.residential_tax = (city.population as u64)
+        .saturating_mul(params.residential_tax_per_capita)
+        .saturating_mul(taxes::rate_pct(city.tax_rate_bps))
+        / 100;
+    if state.city_hall {
+        state.residential_tax += state.residential_tax * CITY_HALL_TAX_BONUS_PCT / 100;
+    }
+
+    let commercial_jobs = state
+        .commercial_capacity
+        .saturating_mul(params.jobs_per_workplace)
+        / 100;
+    let industrial_jobs = state
+        .industrial_capacity
+        .saturating_mul(params.jobs_per_workplace)
+        / 100;
+    state.jobs = commercial_jobs.saturating_add(industrial_jobs);
+    let demographics = city.has_feature(features::DEMOGRAPHICS);
+    state.workforce = if demographics {
+        (city.population as u64 * params.workforce_pct.min(100) as u64 / 100) as u32
+    } else {
+        city.population
+    };
+    city.demand = demand::compute_demand(&DemandFactors {
+        population: city.population,
+        workforce: state.workforce,
+        commercial_jobs,
+        industrial_jobs,
+        resources: state.resources,
+        tax_rate_bps: city.tax_rate_bps,
+    });
+    state.employed = state.workforce.min(state.jobs);
+    // Workers spread over all jobs evenly
+    let commercial_workers = if state.jobs == 0 {
+        0
+    } else {
+        (state.employed as u64 * commercial_jobs as u64 / state.jobs as u64) as u32
+    };
+    let industrial_workers = state.employed - commercial_workers;
+    state.unemployed = if demographics {
+        state.workforce - state.employed
+    } else {
+        0
+    };
+    city.crime = crime::crime_level(
+        &CrimeFactors {
+            population: city.population,
+            workforce: state.workforce,
+            unemployed: state.unemployed,
+            crime_prone_tiles: state.residential_tiles + state.commercial_tiles,
+            policed_tiles: state.policed_tiles,
+        },
+        params,
+    );
+    city.crime = (city.crime as u32 * ordinances::crime_pct(city.ordinances) / 100) as u8;
+
+    state.commercial_income = (commercial_workers as u64)
+        .saturating_mul(params.commercial_income_per_worker)
+        .saturating_mul(education_multiplier_pct(state.education_level, params) as u64)
+        / 100;
+    state.industrial_income =
+        (industrial_workers as u64).saturating_mul(params.industrial_income_per_worker);
+    // Shops can only sell the goods industry makes, and nothing works
+    // without power and water
+    state.commercial_income = state.commercial_income * state.resources.goods_supply_pct() / 100;
+    // Shops next to homes get more customers
+    if state.connected_commercial > 0 {
+        let bonus = state.commercial_near_homes as u64
+            * params.commercial_near_homes_bonus_pct as u64
+            / state.connected_commercial as u64;
+        state.commercial_income = state.commercial_income * (100 + bonus) / 100;
+    }
+    // Shoppers stay away from jammed streets
+    if state.connected_commercial > 0 {
+        let congestion = (state.commercial_congestion / state.connected_commercial) as u64;
+        let penalty = congestion * params.traffic_income_penalty_pct as u64 / 100;
+        state.commercial_income = state.commercial_income * (100 - penalty) / 100;
+    }
+    // Shoppers stay away from crime too
+    let crime_penalty = city.crime as u64 * params.crime_income_penalty_pct as u64 / 100;
+    state.commercial_income = state.commercial_income * (100 - crime_penalty) / 100;
+    // and mostly stay home at night
+    state.commercial_income =
+        state.commercial_income * city.day_phase.commercial_income_pct() / 100;
+    if !state.resources.utilities_ok() {
+        state.commercial_income = 0;
+        state.industrial_income = 0;
+    }
+    // Underfunded utilities hold businesses back; overfunding doesn't help
+    let utilities_pct = city.budget.utilities_pct.min(100) as u64;
+    state.commercial_income = state.commercial_income * utilities_pct / 100;
+    state.industrial_income = state.industrial_income * utilities_pct / 100;
+    // Automation and logistics make businesses more efficient
+    state.commercial_income =
+        state.commercial_income * research::commercial_income_pct(city.techs) / 100;
+    state.industrial_income =
+        state.industrial_income * research::industrial_income_pct(city.techs) / 100;
+    // Difficulty scales everything collected, the specialization its own
+    // income stream
+    let tax_pct = city.difficulty.tax_pct();
+    let specialization = city.specialization;
+    state.residential_tax =
+        state.residential_tax * tax_pct * specialization.residential_tax_pct() / 10_000;
+    state.commercial_income =
+        state.commercial_income * tax_pct * specialization.commercial_income_pct() / 10_000;
+    state.industrial_income =
+        state.industrial_income * tax_pct * specialization.industrial_income_pct() / 10_000;
+    // Earlier runs' prestige raises all of it, and a caretaker government
+    // collects less
+    let prestige_pct = 100 + city.prestige_pct as u64;
+    let caretaker_pct = elections::income_pct(city, params);
+    state.residential_tax = state.residential_tax * prestige_pct * caretaker_pct / 10_000;
+    state.commercial_income = state.commercial_income * prestige_pct * caretaker_pct / 10_000;
+    state.industrial_income = state.industrial_income * prestige_pct * caretaker_pct / 10_000;
+    city.money = city
+        .money
+        .saturating_add(state.residential_tax)
+        .saturating_add(state.commercial_income)
+        .saturating_add(state.industrial_income);
+    bankruptcy::settle(city);
+    city.jobs = state.jobs;
+    city.workforce = state.workforce;
+    city.commerce_revenue = state.commercial_income + state.industrial_income;
+    if city.crime >= crime::HIGH_CRIME_LEVEL {
+        emit!(HighCrime {
+            authority: city.authority,
+            crime: city.crime,
+        });
+    }
+
+    state.welfare_cost = (state.unemployed as u64).saturating_mul(params.welfare_per_unemployed);
+    bankruptcy::charge(city, state.welfare_cost);
+    happiness(city, params, state);
+
+    upkeep(city, state);
+    state.upkeep_paid = bankruptcy::charge(city, state.upkeep);
+    city.upkeep_unpaid = !state.upkeep_paid;
+
+    loan_interest(city, params, state);
+    insurance_premium(city, params, state);
+    state.ordinance_cost = ordinances::cost(city.ordinances);
+    bankruptcy::charge(city, state.ordinance_cost);
+
+    if !city.bankrupt && bankruptcy::is_insolvent(city, params) {
+        city.bankrupt = true;
+        emit!(CityBankrupt {
+            authority: city.authority,
+            overdraft: city.overdraft,
+        });
+    }
+}
+
+/// Charge the disaster insurance premium; a treasury that can't cover it
+/// lets the policy lapse
+fn insurance_premium(city: &mut City, params: &GameParams, state: &mut StepState) {
+    if !city.insured {
+        return;
+    }
+    if city.money < params.insurance_premium {
+        city.insured = false;
+        emit!(InsuranceLapsed {
+            authority: city.authority,
+        });
+        return;
+    }
+    city.money -= params.insurance_premium;
+    state.insurance_premium = params.insurance_premium;
+}
+
+/// Aggregate residents' mood into `City::happiness`: it starts from the
+/// employment rate, rises with services, parks, stadiums and the monument
+/// and falls with industry next door, pollution, taxes and crime. The
+/// population phase takes homelessness and garbage off afterwards.
+fn happiness(city: &mut City, params: &GameParams, state: &StepState) {
+    city.happiness = unemployment_happiness(state.workforce, state.unemployed);
+    // Services reaching their homes reassure residents
+    if state.residential_tiles > 0 {
+        let service_bonus = state.serviced_residential * SERVICE_HAPPINESS
+            / (SERVICES_AT_HOME * state.residential_tiles);
+        city.happiness = (city.happiness as u32 + service_bonus).min(MAX_HAPPINESS as u32) as u8;
+    }
+    // Parks, stadiums and the monument cheer residents up, as far as the
+    // parks budget lets them
+    let park_access = if state.residential_tiles == 0 {
+        0
+    } else {
+        state.residential_near_parks * PARK_HAPPINESS / state.residential_tiles
+    };
+    let recreation = park_access
+        + state.stadiums * STADIUM_HAPPINESS
+        + state.monument as u32 * MONUMENT_HAPPINESS;
+    let recreation_bonus = recreation * city.budget.parks_pct.min(100) as u32 / 100;
+    city.happiness = (city.happiness as u32 + recreation_bonus).min(MAX_HAPPINESS as u32) as u8;
+    // Factories next door make homes miserable
+    let industry_penalty = state
+        .industry_near_homes
+        .saturating_mul(params.industry_near_homes_penalty as u32)
+        .min(MAX_HAPPINESS as u32) as u8;
+    city.happiness = city.happiness.saturating_sub(industry_penalty);
+    // So does breathing smog
+    if state.residential_tiles > 0 {
+        let pollution_penalty = (state.residential_pollution
+            * params.pollution_happiness_penalty as u32
+            / state.residential_tiles)
+            .min(MAX_HAPPINESS as u32) as u8;
+        city.happiness = city.happiness.saturating_sub(pollution_penalty);
+    }
+    // Taxes and crime weigh on everyone
+    let tax_penalty = params
+        .residential_tax_per_capita
+        .saturating_mul(params.tax_happiness_penalty as u64)
+        .saturating_mul(taxes::rate_pct(city.tax_rate_bps))
+        / 100;
+    let tax_penalty = tax_penalty.min(MAX_HAPPINESS as u64) as u8;
+    let crime_penalty = (city.crime as u32 * params.crime_happiness_penalty_pct as u32 / 100) as u8;
+    city.happiness = city
+        .happiness
+        .saturating_sub(tax_penalty)
+        .saturating_sub(crime_penalty);
+}
+
+/// Charge interest on the outstanding loan. Interest the treasury can't
+/// cover is added to the principal; too many misses in a row and the city
+/// defaults, which freezes construction and halves happiness until the
+/// loan is repaid.
+fn loan_interest(city: &mut City, params: &GameParams, state: &mut StepState) {
+    if city.loan_principal == 0 {
+        return;
+    }
+    state.loan_interest = city
+        .loan_principal
+        .saturating_mul(city.loan_rate_bps as u64)
+        .div_ceil(10_000);
+    state.loan_interest_paid = city.money >= state.loan_interest;
+    if state.loan_interest_paid {
+        city.money -= state.loan_interest;
+        city.missed_loan_payments = 0;
+    } else {
+        city.loan_principal = city.loan_principal.saturating_add(state.loan_interest);
+        city.missed_loan_payments = city.missed_loan_payments.saturating_add(1);
+        if city.missed_loan_payments >= params.max_missed_payments {
+            city.in_default = true;
+        }
+    }
+    if city.in_default {
+        city.happiness /= 2;
+    }
+}
+
+/// Building upkeep split by paying department
+/// Construction sites cost nothing to maintain yet
+fn upkeep(city: &City, state: &mut StepState) {
+    for &tile in state.operational.iter().flatten() {
+        let building_type = buildings::tile_type(tile);
+        let cost = city.budget.upkeep(building_type)
+            * city.funding.funding_pct(building_type) as u64
+            / 100;
+        match Department::of(building_type) {
+            Some(Department::Safety) => state.safety_upkeep += cost,
+            Some(Department::Utilities) => state.utilities_upkeep += cost,
+            Some(Department::Parks) => state.parks_upkeep += cost,
+            None => state.other_upkeep += cost,
+        }
+    }
+    state.upkeep =
+        state.safety_upkeep + state.utilities_upkeep + state.parks_upkeep + state.other_upkeep;
+}
+
+/// Commercial income multiplier in percent: 100 for an uneducated city, up
+/// to 100 + `education_income_bonus_pct` when fully educated
+pub fn education_multiplier_pct(education_level: u8, params: &GameParams) -> u16 {
+    let level = education_level.min(100) as u16;
+    let bonus = params
+        .education_income_bonus_pct
+        .min(MAX_EDUCATION_INCOME_BONUS_PCT) as u16;
+    100 + bonus * level / 100
+}
+
+/// Residents a powered and watered residential tile of `density` factor
+/// houses with `services` reaching it
+pub fn housing_capacity(params: &GameParams, density: u32, services: u32) -> u32 {
+    params
+        .housing_per_residential
+        .saturating_mul(density)
+        .saturating_mul(100 + services * SERVICE_HOUSING_BONUS_PCT)
+        / 100
+}
+
+/// Share of the workforce without a job, in percent
+pub fn unemployment_pct(workforce: u32, unemployed: u32) -> u8 {
+    if workforce == 0 {
+        return 0;
+    }
+    (unemployed as u64 * 100 / workforce as u64) as u8
+}
+
+/// 100 with full employment, minus one point per percent unemployed
+pub fn unemployment_happiness(workforce: u32, unemployed: u32) -> u8 {
+    MAX_HAPPINESS - unemployment_pct(workforce, unemployed)
+}
+
+/// Grow population towards the housing capacity, faster into greener and
+/// better kept homes, slower into noisy or polluted ones and in a sickly
+/// city, see `health`, and not at all while power or water run short or
+/// residential demand isn't positive, see `demand`; unemployment holds it
+/// back too. Homes house more with services in reach, and nobody without
+/// power or water. With `features::DEMOGRAPHICS`, residents beyond the
+/// housing capacity are homeless, costing a point of happiness per percent.
+/// When happiness ends up below `unrest_happiness`, a share of residents
+/// moves away.
+pub fn population_phase(city: &mut City, params: &GameParams, state: &mut StepState) {
+    state.population_growth = if !state.resources.utilities_ok()
+        || city.demand.residential <= 0
+        || state.livable_units == 0
+    {
+        0
+    } else {
+        // Fill a share of the free housing, scaled by how appealing the
+        // homes are and by the season, so population approaches capacity
+        // without passing it
+        let vacant = state.housing_capacity.saturating_sub(city.population) as u64;
+        let appeal_pct = (state.residential_desirability / state.livable_units) as u64;
+        let season_pct = city.season.growth_pct() as u64;
+        (vacant * params.growth_rate_pct.min(100) as u64 * appeal_pct * season_pct / 1_000_000)
+            .min(vacant) as u32
+    };
+    let unemployment_penalty = unemployment_pct(state.workforce, state.unemployed) as u32
+        * params.unemployment_growth_penalty_pct.min(100) as u32
+        / 100;
+    state.population_growth = state.population_growth * (100 - unemployment_penalty) / 100;
+    city.health = health::health_level(
+        &HealthFactors {
+            residential_tiles: state.residential_tiles,
+            treated_tiles: state.treated_residential,
+            residential_pollution: state.residential_pollution,
+            garbage: city.garbage,
+        },
+        params,
+    );
+    state.population_growth = state.population_growth * health::growth_pct(city.health) / 100;
+    city.population = city.population.saturating_add(state.population_growth);
+
+    city.homeless = if city.has_feature(features::DEMOGRAPHICS) {
+        city.population.saturating_sub(state.housing_capacity)
+    } else {
+        0
+    };
+    if city.homeless > 0 {
+        let homeless_pct = (city.homeless as u64 * 100 / city.population as u64) as u8;
+        city.happiness = city.happiness.saturating_sub(homeless_pct);
+    }
+
+    collect_garbage(city, params, state);
+
+    if city.happiness < params.unrest_happiness {
+        state.population_decline =
+            (city.population as u64 * params.unrest_decline_pct as u64 / 100) as u32;
+        city.population -= state.population_decline;
+        if state.population_decline > 0 {
+            emit!(ResidentsLeft {
+                authority: city.authority,
+                left: state.population_decline,
+                happiness: city.happiness,
+            });
+        }
+    }
+}
+
+/// Residents throw out garbage and collection takes what it can, backlog
+/// first; anything left over piles up and costs happiness
+fn collect_garbage(city: &mut City, params: &GameParams, state: &mut StepState) {
+    state.garbage_produced =
+        garbage::produced_by(city.population, params.garbage_per_100_residents);
+    city.garbage = city
+        .garbage
+        .saturating_add(state.garbage_produced)
+        .saturating_sub(state.garbage_capacity);
+    if city.garbage == 0 {
+        return;
+    }
+    city.happiness = city
+        .happiness
+        .saturating_sub(params.garbage_happiness_penalty);
+    emit!(GarbageOverflow {
+        authority: city.authority,
+        uncollected: city.garbage,
+    });
+}
+
+/// Build on empty zoned tiles with road access while their zone's demand
+/// is positive and the treasury can pay, up to `MAX_DEVELOPMENTS_PER_STEP`.
+/// New buildings start as construction sites like placed ones.
+pub fn development_phase(city: &mut City, params: &GameParams, state: &mut StepState) {
+    if city.in_default {
+        return;
+    }
+    for y in 0..GRID_SIZE {
+        for x in 0..GRID_SIZE {
+            if state.developed >= zoning::MAX_DEVELOPMENTS_PER_STEP {
+                return;
+            }
+            let zone = buildings::tile_type(city.zoning[y][x]);
+            if city.tiles[y][x] != 0 || city.demand.of(zone) <= 0 || !city.has_road_access(x, y) {
+                continue;
+            }
+            let affordable = city
+                .placement_cost(x as u8, y as u8, zone, params)
+                .is_ok_and(|cost| cost <= city.money);
+            if affordable && city.place(x as u8, y as u8, zone, false, params).is_ok() {
+                state.developed += 1;
+            }
+        }
+    }
+}
+
+/// Long-running world effects and random events. Existing fires spread
+/// before new ones can start, so a fresh fire gets a step to be answered.
+pub fn environment_phase(
+    city: &mut City,
+    params: &GameParams,
+    state: &mut StepState,
+    rng: &mut dyn RandomnessSource,
+) {
+    drift_pollution(city, state);
+    fire_spread_phase(city, params, state);
+    decay(city, params, state);
+    ongoing_crime(city, params, state);
+    crime_events(city, params, state, rng);
+    fire_ignition(city, params, state, rng);
+    disasters(city, params, rng);
+    floods(city, params, rng);
+}
+
+/// Let working industry pollute and last step's pollution drift; cities
+/// without `features::POLLUTION` stay clean
+fn drift_pollution(city: &mut City, state: &StepState) {
+    city.pollution = if city.has_feature(features::POLLUTION) {
+        pollution::diffuse(
+            &city.pollution,
+            &state.operational,
+            &city.density,
+            ordinances::emission_pct(city.ordinances),
+        )
+    } else {
+        [[0; GRID_SIZE]; GRID_SIZE]
+    };
+}
+
+/// Wear down every working building; one worn out completely becomes ruins
+/// across its whole footprint
+fn decay(city: &mut City, params: &GameParams, state: &mut StepState) {
+    for y in 0..GRID_SIZE {
+        for x in 0..GRID_SIZE {
+            let building_type = buildings::tile_type(city.tiles[y][x]);
+            if city.is_under_construction(x, y)
+                || building_type == BuildingType::Empty
+                || building_type == BuildingType::Ruins
+                || building_type.is_infrastructure()
+            {
+                continue;
+            }
+            let size = building_type.footprint() as usize;
+            let condition = city.condition[y][x].saturating_sub(params.decay_per_step);
+            for (tx, ty) in buildings::footprint_tiles(x, y, size) {
+                city.condition[ty][tx] = condition;
+                if condition == 0 {
+                    city.tiles[ty][tx] = BuildingType::Ruins.into();
+                }
+            }
+            state.ruined += (condition == 0) as u32;
+        }
+    }
+
+    if state.ruined > 0 {
+        city.on_layout_changed();
+        emit!(BuildingsRuined {
+            authority: city.authority,
+            ruined: state.ruined,
+        });
+    }
+}
+
+fn fire_spread_phase(city: &mut City, params: &GameParams, state: &mut StepState) {
+    if city.fires.iter().flatten().all(|&steps| steps == 0) {
+        return;
+    }
+    state.fire = fire::spread(
+        &mut city.tiles,
+        &mut city.fires,
+        &state.coverage,
+        params.fire_burn_steps,
+    );
+    if state.fire.burned_down > 0 {
+        city.on_layout_changed();
+    }
+    emit!(FireUpdate {
+        authority: city.authority,
+        ignited: state.fire.ignited,
+        extinguished: state.fire.extinguished,
+        burned_down: state.fire.burned_down,
+    });
+}
+
+/// Maybe set one random flammable tile outside fire-station reach alight
+fn fire_ignition(
+    city: &mut City,
+    params: &GameParams,
+    state: &mut StepState,
+    rng: &mut dyn RandomnessSource,
+) {
+    if rng.below(100) >= params.fire_chance_pct as u64 {
+        return;
+    }
+    let index = rng.below((GRID_SIZE * GRID_SIZE) as u64) as usize;
+    let (x, y) = (index % GRID_SIZE, index / GRID_SIZE);
+    if !fire::is_flammable(city.tiles[y][x])
+        || city.fires[y][x] != 0
+        || coverage::is_covered(&state.coverage, x, y, Service::Fire)
+    {
+        return;
+    }
+    city.fires[y][x] = params.fire_burn_steps.max(1);
+    state.fire_started = true;
+    emit!(FireStarted {
+        authority: city.authority,
+        x: x as u8,
+        y: y as u8,
+    });
+}
+
+/// Maybe strike a random spot with a disaster, see `disaster`; sandbox
+/// cities are spared
+fn disasters(city: &mut City, params: &GameParams, rng: &mut dyn RandomnessSource) {
+    if city.sandbox
+        || !city.has_feature(features::DISASTERS)
+        || rng.below(100) >= params.disaster_chance_pct as u64
+    {
+        return;
+    }
+    let disaster = Disaster::roll(rng);
+    let area = disaster.area();
+
+    let mut hit: Vec<(usize, usize)> = Vec::new();
+    for &(x, y) in &area {
+        if let Some(anchor) = city.building_anchor(x, y) {
+            if !hit.contains(&anchor) {
+                hit.push(anchor);
+            }
+        }
+    }
+
+    let (mut damaged, mut destroyed, mut rebuild_cost) = (0, 0, 0u64);
+    for (ax, ay) in hit {
+        let building_type = buildings::tile_type(city.tiles[ay][ax]);
+        // Earthquakes shake buildings; infrastructure wears no condition
+        if building_type == BuildingType::Ruins
+            || (!disaster.destroys() && building_type.is_infrastructure())
+        {
+            continue;
+        }
+        let condition = if disaster.destroys() {
+            0
+        } else {
+            city.condition[ay][ax]
+                .saturating_sub(EARTHQUAKE_DAMAGE.saturating_mul(disaster.severity))
+        };
+        // What putting the building back costs: a repair of the wear
+        // inflicted, or the full price once it is ruined
+        let base_cost = building_type.base_cost().unwrap_or(0);
+        rebuild_cost += if condition == 0 {
+            base_cost
+        } else {
+            let wear = city.condition[ay][ax] - condition;
+            base_cost * params.repair_cost_pct as u64 / 100 * wear as u64 / MAX_CONDITION as u64
+        };
+        for (tx, ty) in buildings::footprint_tiles(ax, ay, building_type.footprint() as usize) {
+            city.condition[ty][tx] = condition;
+            if condition == 0 {
+                city.tiles[ty][tx] = BuildingType::Ruins.into();
+                city.fires[ty][tx] = 0;
+                city.road_tiers[ty][tx] = 0;
+                city.construction[ty][tx] = 0;
+                city.set_crime_scene(tx, ty, false);
+            }
+        }
+        if condition == 0 {
+            destroyed += 1;
+        } else {
+            damaged += 1;
+        }
+    }
+    if destroyed > 0 {
+        city.on_layout_changed();
+    }
+    let insurance_payout = if city.insured {
+        rebuild_cost * params.insurance_payout_pct as u64 / 100
+    } else {
+        0
+    };
+    city.money = city.money.saturating_add(insurance_payout);
+
+    emit!(DisasterStruck {
+        authority: city.authority,
+        kind: disaster.kind,
+        x: disaster.x as u8,
+        y: disaster.y as u8,
+        severity: disaster.severity,
+        tiles: area.iter().map(|&(x, y)| [x as u8, y as u8]).collect(),
+        damaged,
+        destroyed,
+        insurance_payout,
+    });
+}
+
+/// Water rising over the low land bordering it wears down the buildings
+/// there; see `elevation`
+fn floods(city: &mut City, params: &GameParams, rng: &mut dyn RandomnessSource) {
+    if city.sandbox
+        || !city.has_feature(features::DISASTERS)
+        || rng.below(100) >= params.flood_chance_pct as u64
+    {
+        return;
+    }
+    let mut tiles: Vec<(usize, usize)> = Vec::new();
+    let mut hit: Vec<(usize, usize)> = Vec::new();
+    for y in 0..GRID_SIZE {
+        for x in 0..GRID_SIZE {
+            if !elevation::flood_prone(&city.elevation, &city.terrain, x, y) {
+                continue;
+            }
+            tiles.push((x, y));
+            if let Some(anchor) = city.building_anchor(x, y) {
+                if !hit.contains(&anchor) {
+                    hit.push(anchor);
+                }
+            }
+        }
+    }
+    if tiles.is_empty() {
+        return;
+    }
+
+    let (mut damaged, mut repair_cost) = (0, 0u64);
+    for (ax, ay) in hit {
+        let building_type = buildings::tile_type(city.tiles[ay][ax]);
+        if building_type == BuildingType::Ruins || building_type.is_infrastructure() {
+            continue;
+        }
+        // The water wears buildings down but never washes them away
+        let condition = city.condition[ay][ax].saturating_sub(FLOOD_DAMAGE).max(1);
+        let wear = city.condition[ay][ax] - condition;
+        if wear == 0 {
+            continue;
+        }
+        repair_cost += building_type.base_cost().unwrap_or(0) * params.repair_cost_pct as u64 / 100
+            * wear as u64
+            / MAX_CONDITION as u64;
+        for (tx, ty) in buildings::footprint_tiles(ax, ay, building_type.footprint() as usize) {
+            city.condition[ty][tx] = condition;
+        }
+        damaged += 1;
+    }
+    let insurance_payout = if city.insured {
+        repair_cost * params.insurance_payout_pct as u64 / 100
+    } else {
+        0
+    };
+    city.money = city.money.saturating_add(insurance_payout);
+
+    emit!(Flooded {
+        authority: city.authority,
+        tiles: tiles.iter().map(|&(x, y)| [x as u8, y as u8]).collect(),
+        damaged,
+        insurance_payout,
+    });
+}
+
+/// True when a crime can happen on the tile: a residential or commercial
+/// building outside police reach
+fn crime_prone(city: &City, state: &StepState, x: usize, y: usize) -> bool {
+    matches!(
+        buildings::tile_type(city.tiles[y][x]),
+        BuildingType::Residential | BuildingType::Commercial
+    ) && !coverage::is_covered(&state.coverage, x, y, Service::Police)
+}
+
+/// Active crime scenes keep stealing every step until police reach them
+/// or an emergency is dispatched
+fn ongoing_crime(city: &mut City, params: &GameParams, state: &mut StepState) {
+    for y in 0..GRID_SIZE {
+        for x in 0..GRID_SIZE {
+            if !city.has_crime_scene(x, y) {
+                continue;
+            }
+            if !crime_prone(city, state, x, y) {
+                city.set_crime_scene(x, y, false);
+                continue;
+            }
+            commit_crime(city, params, state, x, y);
+        }
+    }
+}
+
+/// Roll for a new crime on every crime-prone tile without one
+fn crime_events(
+    city: &mut City,
+    params: &GameParams,
+    state: &mut StepState,
+    rng: &mut dyn RandomnessSource,
+) {
+    for y in 0..GRID_SIZE {
+        for x in 0..GRID_SIZE {
+            if city.has_crime_scene(x, y) || !crime_prone(city, state, x, y) {
+                continue;
+            }
+            let chance_pct = params.crime_chance_pct as u64 * city.day_phase.crime_pct() / 100;
+            if rng.below(100) >= chance_pct {
+                continue;
+            }
+            city.set_crime_scene(x, y, true);
+            commit_crime(city, params, state, x, y);
+        }
+    }
+}
+
+/// Steal from the treasury for the crime at (x, y), within the per-step cap
+fn commit_crime(city: &mut City, params: &GameParams, state: &mut StepState, x: usize, y: usize) {
+    if state.crime_incidents >= MAX_CRIME_INCIDENTS_PER_STEP || city.money == 0 {
+        return;
+    }
+    let stolen = params.crime_theft.min(city.money);
+    city.money -= stolen;
+    state.crime_incidents += 1;
+    state.money_stolen += stolen;
+    emit!(CrimeIncident {
+        authority: city.authority,
+        x: x as u8,
+        y: y as u8,
+        stolen,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn construction_sites_open_when_their_time_is_up() {
+        let mut city = City::for_tests(0);
+        city.tiles[0][0] = BuildingType::Residential as u8;
+        city.tiles[0][1] = BuildingType::Commercial as u8;
+        city.construction[0][0] = 1;
+        city.construction[0][1] = 2;
+
+        let mut state = StepState::default();
+        construction_phase(&mut city, &mut state);
+        assert_eq!(state.construction_completed, 1);
+        assert_eq!(state.construction_sites, 1);
+        assert_eq!(state.operational[0][0], BuildingType::Residential as u8);
+        assert_eq!(state.operational[0][1], 0);
+        assert_eq!(
+            city.construction[0],
+            [0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn residential_tax_follows_the_tax_rate() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(0);
+        city.population = 1_000;
+        let mut state = StepState::default();
+        economy_phase(&mut city, &params, &mut state);
+        let nominal = state.residential_tax;
+        assert_eq!(nominal, 1_000 * params.residential_tax_per_capita);
+
+        let mut city = City::for_tests(0);
+        city.population = 1_000;
+        city.tax_rate_bps = taxes::NOMINAL_TAX_RATE_BPS * 2;
+        let mut state = StepState::default();
+        economy_phase(&mut city, &params, &mut state);
+        assert_eq!(state.residential_tax, nominal * 2);
+    }
+
+    #[test]
+    fn service_funding_scales_safety_upkeep() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(1_000);
+        city.funding.police_pct = 50;
+        let mut state = StepState::default();
+        state.operational[0][0] = BuildingType::PoliceStation as u8;
+        state.operational[0][1] = BuildingType::FireStation as u8;
+        economy_phase(&mut city, &params, &mut state);
+        assert_eq!(
+            state.safety_upkeep,
+            BuildingType::PoliceStation.upkeep() / 2 + BuildingType::FireStation.upkeep()
+        );
+        assert!(state.upkeep_paid);
+        assert_eq!(city.money, 1_000 - state.upkeep);
+    }
+
+    #[test]
+    fn unpaid_loan_interest_compounds_into_default() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(0);
+        city.loan_principal = 10_000;
+        city.loan_rate_bps = 100;
+        for missed in 1..=params.max_missed_payments {
+            let mut state = StepState::default();
+            loan_interest(&mut city, &params, &mut state);
+            assert!(!state.loan_interest_paid);
+            assert_eq!(city.missed_loan_payments, missed);
+        }
+        assert!(city.loan_principal > 10_000);
+        assert!(city.in_default);
+
+        city.money = city.loan_principal;
+        city.in_default = false;
+        let mut state = StepState::default();
+        loan_interest(&mut city, &params, &mut state);
+        assert!(state.loan_interest_paid);
+        assert_eq!(city.missed_loan_payments, 0);
+    }
+
+    #[test]
+    fn population_grows_into_vacant_housing_only() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(0);
+        city.demand.residential = 10;
+        let mut state = StepState {
+            residential_tiles: 1,
+            livable_units: 1,
+            residential_desirability: 100,
+            housing_capacity: 100,
+            ..StepState::default()
+        };
+        population_phase(&mut city, &params, &mut state);
+        assert!(state.population_growth > 0);
+        assert_eq!(city.population, state.population_growth);
+        assert!(city.population <= 100);
+
+        city.demand.residential = 0;
+        let mut state = StepState {
+            housing_capacity: 100,
+            ..state
+        };
+        let before = city.population;
+        population_phase(&mut city, &params, &mut state);
+        assert_eq!(state.population_growth, 0);
+        assert_eq!(city.population, before);
+    }
+
+    #[test]
+    fn homes_without_power_or_water_draw_no_growth() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(0);
+        city.tiles[0][0] = BuildingType::PowerPlant as u8;
+        city.tiles[0][1] = BuildingType::WaterPump as u8;
+        city.tiles[0][2] = BuildingType::Residential as u8;
+        city.tiles[8][8] = BuildingType::Residential as u8;
+        city.condition[0][2] = MAX_CONDITION;
+        city.condition[8][8] = MAX_CONDITION;
+        city.demand.residential = 10;
+
+        let mut state = StepState {
+            operational: city.tiles,
+            ..StepState::default()
+        };
+        services_phase(&mut city, &params, None, &mut state);
+        assert_eq!(state.residential_tiles, 2);
+        assert_eq!(
+            state.housing_capacity,
+            housing_capacity(&params, density::factor(city.density[0][2]), 0)
+        );
+
+        population_phase(&mut city, &params, &mut state);
+        assert!(state.population_growth > 0);
+        assert!(city.population <= state.housing_capacity);
+    }
+
+    #[test]
+    fn a_lone_unpowered_home_houses_nobody() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(0);
+        city.tiles[8][8] = BuildingType::Residential as u8;
+        city.demand.residential = 10;
+
+        let mut state = StepState {
+            operational: city.tiles,
+            ..StepState::default()
+        };
+        services_phase(&mut city, &params, None, &mut state);
+        assert_eq!(state.housing_capacity, 0);
+        population_phase(&mut city, &params, &mut state);
+        assert_eq!(city.population, 0);
+    }
+
+    #[test]
+    fn demographics_off_leaves_nobody_unemployed_or_homeless() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(0);
+        city.features &= !features::DEMOGRAPHICS;
+        city.population = 150;
+        let mut state = StepState {
+            housing_capacity: 100,
+            ..StepState::default()
+        };
+        economy_phase(&mut city, &params, &mut state);
+        assert_eq!(state.workforce, 150);
+        assert_eq!((state.unemployed, state.welfare_cost), (0, 0));
+        population_phase(&mut city, &params, &mut state);
+        assert_eq!(city.homeless, 0);
+    }
+
+    #[test]
+    fn residents_beyond_housing_are_homeless() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(0);
+        city.population = 150;
+        let mut state = StepState {
+            housing_capacity: 100,
+            ..StepState::default()
+        };
+        population_phase(&mut city, &params, &mut state);
+        assert_eq!(city.homeless, 50);
+        assert!(city.happiness < MAX_HAPPINESS);
+    }
+
+    #[test]
+    fn zoned_tiles_develop_with_demand_and_road_access() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(100_000);
+        city.place(0, 0, BuildingType::Road, false, &params)
+            .unwrap();
+        city.zoning[1][0] = BuildingType::Residential as u8;
+        city.zoning[5][5] = BuildingType::Residential as u8;
+        city.demand.residential = 10;
+
+        let mut state = StepState::default();
+        development_phase(&mut city, &params, &mut state);
+        assert_eq!(state.developed, 1);
+        assert_eq!(city.tiles[1][0], BuildingType::Residential as u8);
+        assert!(city.construction[1][0] > 0);
+        assert_eq!(city.tiles[5][5], 0);
+    }
+
+    #[test]
+    fn nothing_develops_without_demand_or_in_default() {
+        let params = GameParams::default();
+        let mut city = City::for_tests(100_000);
+        city.place(0, 0, BuildingType::Road, false, &params)
+            .unwrap();
+        city.zoning[1][0] = BuildingType::Residential as u8;
+
+        let mut state = StepState::default();
+        development_phase(&mut city, &params, &mut state);
+        assert_eq!(state.developed, 0);
+
+        city.demand.residential = 10;
+        city.in_default = true;
+        development_phase(&mut city, &params, &mut state);
+        assert_eq!(state.developed, 0);
+        assert_eq!(city.tiles[1][0], 0);
+    }
+}
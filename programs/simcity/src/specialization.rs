@@ -0,0 +1,92 @@
+//! City specializations.
+//!
+//! A city may specialize when it is founded. The choice is permanent:
+//! buildings of the specialty cost `SPECIALTY_DISCOUNT_PCT` less, and the
+//! income stream it is known for brings in `SPECIALTY_INCOME_BONUS_PCT`
+//! more.
+//!
+//! - industrial: factories, power plants and landfills; industrial income
+//! - tourist: parks, the stadium, the monument and the airport; commercial
+//!   income from visitors
+//! - tech: schools, universities and solar plants; half the bonus on both
+//!   commercial and industrial income
+//! - agricultural: homes and water supply for the farmland around the city;
+//!   residential tax
+
+use anchor_lang::prelude::*;
+
+use crate::buildings::BuildingType;
+
+/// Price cut on buildings of the specialty, in percent
+pub const SPECIALTY_DISCOUNT_PCT: u64 = 20;
+
+/// Extra income from the specialty's income stream, in percent
+pub const SPECIALTY_INCOME_BONUS_PCT: u64 = 25;
+
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Default, Clone, Copy, Debug, PartialEq, Eq,
+)]
+pub enum Specialization {
+    #[default]
+    None,
+    Industrial,
+    Tourist,
+    Tech,
+    Agricultural,
+}
+
+impl Specialization {
+    /// Whether `building_type` belongs to the specialty
+    pub fn covers(self, building_type: BuildingType) -> bool {
+        use BuildingType::*;
+        match self {
+            Specialization::None => false,
+            Specialization::Industrial => {
+                matches!(building_type, Industrial | PowerPlant | Landfill)
+            }
+            Specialization::Tourist => matches!(
+                building_type,
+                Park | LargePark | Stadium | Monument | Airport
+            ),
+            Specialization::Tech => matches!(building_type, School | University | SolarPlant),
+            Specialization::Agricultural => {
+                matches!(building_type, Residential | WaterPump | WaterTower)
+            }
+        }
+    }
+
+    /// Price of `building_type`, in percent of the usual
+    pub fn cost_pct(self, building_type: BuildingType) -> u64 {
+        if self.covers(building_type) {
+            100 - SPECIALTY_DISCOUNT_PCT
+        } else {
+            100
+        }
+    }
+
+    /// Residential tax collected, in percent of the usual
+    pub fn residential_tax_pct(self) -> u64 {
+        match self {
+            Specialization::Agricultural => 100 + SPECIALTY_INCOME_BONUS_PCT,
+            _ => 100,
+        }
+    }
+
+    /// Commercial income, in percent of the usual
+    pub fn commercial_income_pct(self) -> u64 {
+        match self {
+            Specialization::Tourist => 100 + SPECIALTY_INCOME_BONUS_PCT,
+            Specialization::Tech => 100 + SPECIALTY_INCOME_BONUS_PCT / 2,
+            _ => 100,
+        }
+    }
+
+    /// Industrial income, in percent of the usual
+    pub fn industrial_income_pct(self) -> u64 {
+        match self {
+            Specialization::Industrial => 100 + SPECIALTY_INCOME_BONUS_PCT,
+            Specialization::Tech => 100 + SPECIALTY_INCOME_BONUS_PCT / 2,
+            _ => 100,
+        }
+    }
+}
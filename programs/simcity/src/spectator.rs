@@ -0,0 +1,19 @@
+//! Read-only spectating.
+//!
+//! A city's authority issues `SpectatorPass`es to viewers. A pass grants no
+//! mutation rights: its only use is `spectate`, which counts the view on
+//! the city, and as on-chain proof an ER gateway can check before serving
+//! a delegated city's state to that viewer.
+
+use anchor_lang::prelude::*;
+
+pub const SPECTATOR_SEED: &[u8] = b"spectator";
+
+#[account]
+#[derive(InitSpace)]
+pub struct SpectatorPass {
+    /// Authority of the city being watched
+    pub authority: Pubkey,
+    pub viewer: Pubkey,
+    pub issued_at: i64,
+}
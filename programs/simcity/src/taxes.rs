@@ -0,0 +1,27 @@
+//! Residential tax rate.
+//!
+//! The mayor sets the city's tax rate with `set_tax_rate`, in basis points.
+//! At `NOMINAL_TAX_RATE_BPS` residents pay the config's
+//! `residential_tax_per_capita`, and revenue scales with the rate from
+//! there. So does the unhappiness taxes cause, and residential demand
+//! shifts a point per `TAX_BPS_PER_DEMAND_POINT` the rate is off nominal,
+//! down when higher and up when lower.
+
+/// Rate at which residents pay `residential_tax_per_capita`, 10%
+pub const NOMINAL_TAX_RATE_BPS: u16 = 1_000;
+
+/// Highest rate a mayor can set, 30%
+pub const MAX_TAX_RATE_BPS: u16 = 3_000;
+
+/// Basis points off nominal per point of residential demand
+pub const TAX_BPS_PER_DEMAND_POINT: i32 = 50;
+
+/// Revenue and tax unhappiness at `rate_bps`, in percent of nominal
+pub fn rate_pct(rate_bps: u16) -> u64 {
+    rate_bps as u64 * 100 / NOMINAL_TAX_RATE_BPS as u64
+}
+
+/// Shift in residential demand at `rate_bps`
+pub fn demand_shift(rate_bps: u16) -> i32 {
+    (NOMINAL_TAX_RATE_BPS as i32 - rate_bps as i32) / TAX_BPS_PER_DEMAND_POINT
+}
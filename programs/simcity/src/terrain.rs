@@ -0,0 +1,78 @@
+//! Terrain.
+//!
+//! Under the buildings lies a terrain layer, `City::terrain`, one `Terrain`
+//! code per tile. Buildings go on grass. Forest has to be cleared first,
+//! which adds `forest_clearing_cost` per wooded tile to the placement and
+//! leaves grass behind. Mountains only take roads, highways and power lines,
+//! and water only takes bridges, which in turn need water under them.
+//! Terrain stays under a building, so bulldozing it uncovers the land it was
+//! built on.
+//!
+//! Coastal cities can grow into the water with `reclaim_land`, which fills
+//! in a water tile bordering land for `reclamation_cost`, a tile at a time.
+
+use anchor_lang::prelude::*;
+
+use crate::buildings::{footprint_tiles, BuildingType};
+use crate::config::GameParams;
+use crate::network;
+use crate::GRID_SIZE;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Terrain {
+    #[default]
+    Grass,
+    Water,
+    Forest,
+    Mountain,
+}
+
+impl Terrain {
+    /// Whether `building_type` can stand on this terrain, forest counting
+    /// as the grass it is cleared to
+    pub fn allows(self, building_type: BuildingType) -> bool {
+        match self {
+            Terrain::Water => building_type == BuildingType::Bridge,
+            _ if building_type == BuildingType::Bridge => false,
+            Terrain::Grass | Terrain::Forest => true,
+            Terrain::Mountain => building_type.is_infrastructure(),
+        }
+    }
+}
+
+impl From<Terrain> for u8 {
+    fn from(terrain: Terrain) -> u8 {
+        terrain as u8
+    }
+}
+
+/// Typed view of a stored terrain code; unknown codes read as grass
+pub fn terrain_of(code: u8) -> Terrain {
+    match code {
+        1 => Terrain::Water,
+        2 => Terrain::Forest,
+        3 => Terrain::Mountain,
+        _ => Terrain::Grass,
+    }
+}
+
+/// Whether any tile next to (x, y) is dry land
+pub fn borders_land(terrain: &[[u8; GRID_SIZE]; GRID_SIZE], x: usize, y: usize) -> bool {
+    network::neighbors(x, y).any(|(nx, ny)| terrain_of(terrain[ny][nx]) != Terrain::Water)
+}
+
+/// Price of clearing the forest off a `size` x `size` footprint at (x, y);
+/// tiles past the edge of the grid are left out
+pub fn clearing_cost(
+    terrain: &[[u8; GRID_SIZE]; GRID_SIZE],
+    x: usize,
+    y: usize,
+    size: usize,
+    params: &GameParams,
+) -> u64 {
+    let forests = footprint_tiles(x, y, size)
+        .filter(|&(tx, ty)| tx < GRID_SIZE && ty < GRID_SIZE)
+        .filter(|&(tx, ty)| terrain_of(terrain[ty][tx]) == Terrain::Forest)
+        .count() as u64;
+    forests.saturating_mul(params.forest_clearing_cost)
+}
@@ -0,0 +1,110 @@
+//! Traffic overlay.
+//!
+//! Homes and shops send trips onto every road tile within `TRAFFIC_RADIUS`
+//! tiles of Manhattan distance. A road tile's load is the sum of those
+//! trips; past the capacity of its tier the road is congested. Bus stops and train
+//! stations take a share of the trips off the roads around them; where
+//! several reach a road, the strongest counts. Shops lose income with the
+//! congestion of the busiest road they front. Only simulated for cities
+//! with `features::TRAFFIC`.
+
+use crate::buildings::{self, BuildingType};
+use crate::network::{self, Grid};
+use crate::roads::RoadTier;
+use crate::GRID_SIZE;
+
+/// Manhattan reach of the trips a building generates, in tiles
+pub const TRAFFIC_RADIUS: usize = 2;
+
+/// Trips a tile sends onto each nearby road tile every step
+pub fn trips(tile: u8) -> u32 {
+    match buildings::tile_type(tile) {
+        BuildingType::Residential => 2,
+        BuildingType::Commercial => 3,
+        _ => 0,
+    }
+}
+
+/// Reach in tiles and share of trips, in percent, that a transit building
+/// takes off nearby roads
+pub fn transit_relief(tile: u8) -> Option<(usize, u8)> {
+    match buildings::tile_type(tile) {
+        BuildingType::BusStop => Some((2, 30)),
+        BuildingType::TrainStation => Some((4, 50)),
+        _ => None,
+    }
+}
+
+/// Road tiles within `radius` of (x, y), by Manhattan distance
+fn roads_within(
+    tiles: &Grid,
+    x: usize,
+    y: usize,
+    radius: usize,
+) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let (x0, x1) = (x.saturating_sub(radius), (x + radius).min(GRID_SIZE - 1));
+    let (y0, y1) = (y.saturating_sub(radius), (y + radius).min(GRID_SIZE - 1));
+    (y0..=y1)
+        .flat_map(move |ty| (x0..=x1).map(move |tx| (tx, ty)))
+        .filter(move |&(tx, ty)| {
+            x.abs_diff(tx) + y.abs_diff(ty) <= radius && network::is_road(tiles[ty][tx])
+        })
+}
+
+/// Per-road-tile load in trips, saturating at `u8::MAX`; 0 off the roads
+pub fn compute_traffic(tiles: &Grid) -> Grid {
+    let mut traffic = [[0u8; GRID_SIZE]; GRID_SIZE];
+    let mut relief = [[0u8; GRID_SIZE]; GRID_SIZE];
+    for (y, row) in tiles.iter().enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            let trips = trips(tile);
+            if trips > 0 {
+                for (tx, ty) in roads_within(tiles, x, y, TRAFFIC_RADIUS) {
+                    traffic[ty][tx] = traffic[ty][tx].saturating_add(trips as u8);
+                }
+            }
+            if let Some((radius, pct)) = transit_relief(tile) {
+                for (tx, ty) in roads_within(tiles, x, y, radius) {
+                    relief[ty][tx] = relief[ty][tx].max(pct);
+                }
+            }
+        }
+    }
+
+    for (load, relief) in traffic.iter_mut().flatten().zip(relief.iter().flatten()) {
+        *load = (*load as u32 * (100 - *relief as u32) / 100) as u8;
+    }
+    traffic
+}
+
+/// How far a load exceeds the capacity of a road of tier code `tier`, 0-100
+pub fn congestion_pct(load: u8, tier: u8) -> u8 {
+    let capacity = RoadTier::from_code(tier).capacity();
+    ((load as u32).saturating_sub(capacity) * 100 / capacity).min(100) as u8
+}
+
+/// Congestion of the busiest road tile bordering (x, y)
+pub fn frontage_congestion_pct(traffic: &Grid, tiers: &Grid, x: usize, y: usize) -> u8 {
+    network::neighbors(x, y)
+        .map(|(nx, ny)| congestion_pct(traffic[ny][nx], tiers[ny][nx]))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Average congestion over all road tiles, 0-100
+pub fn average_congestion_pct(tiles: &Grid, traffic: &Grid, tiers: &Grid) -> u8 {
+    let (mut roads, mut total) = (0u32, 0u32);
+    for (y, row) in tiles.iter().enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            if network::is_road(tile) {
+                roads += 1;
+                total += congestion_pct(traffic[y][x], tiers[y][x]) as u32;
+            }
+        }
+    }
+    if roads == 0 {
+        0
+    } else {
+        (total / roads) as u8
+    }
+}
@@ -0,0 +1,64 @@
+//! Population-tier building unlocks.
+//!
+//! A city climbs through population tiers as it grows, and advanced
+//! buildings only become available from a tier up: `place_building` fails
+//! with `BuildingLocked` below it. Clients can fetch the whole table with
+//! the `building_unlocks` view. A city that shrinks back below a tier keeps
+//! what it built but can't build more of it.
+
+use anchor_lang::prelude::*;
+
+use crate::buildings::BuildingType;
+
+/// Population at which each tier starts; a city is in the highest tier it
+/// has reached
+pub const POPULATION_TIERS: [u32; 5] = [0, 1_000, 5_000, 20_000, 50_000];
+
+/// Tier a city of `population` is in
+pub fn tier_of(population: u32) -> u8 {
+    POPULATION_TIERS
+        .iter()
+        .rposition(|&threshold| population >= threshold)
+        .unwrap_or(0) as u8
+}
+
+impl BuildingType {
+    /// Population tier the city needs before it can build this
+    pub fn required_tier(self) -> u8 {
+        match self {
+            BuildingType::SolarPlant
+            | BuildingType::RecyclingCenter
+            | BuildingType::CityHall
+            | BuildingType::Marina => 1,
+            BuildingType::TrainStation | BuildingType::University | BuildingType::Port => 2,
+            BuildingType::Stadium | BuildingType::Monument => 3,
+            BuildingType::Airport => 4,
+            _ => 0,
+        }
+    }
+}
+
+/// A row of the unlock table
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BuildingUnlock {
+    pub building_type: BuildingType,
+    pub tier: u8,
+    /// Population the tier starts at
+    pub population: u32,
+}
+
+/// Every building that can be placed with the tier unlocking it
+pub fn table() -> Vec<BuildingUnlock> {
+    (0..=u8::MAX)
+        .map_while(|code| BuildingType::try_from(code).ok())
+        .filter(|building_type| building_type.base_cost().is_some())
+        .map(|building_type| {
+            let tier = building_type.required_tier();
+            BuildingUnlock {
+                building_type,
+                tier,
+                population: POPULATION_TIERS[tier as usize],
+            }
+        })
+        .collect()
+}
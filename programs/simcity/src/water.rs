@@ -0,0 +1,78 @@
+//! Water distribution.
+//!
+//! Pumps and water towers supply the tiles they connect to, exactly like
+//! power plants do (see `power`). Homes and shops cut off from water are
+//! dry: dry homes attract no residents and dry shops offer no jobs.
+
+use crate::buildings::{self, BuildingType};
+use crate::network::Grid;
+use crate::power;
+use crate::GRID_SIZE;
+
+/// Bit x of row y is set when the tile is a dry residential or commercial
+/// tile
+pub type DryMap = [u16; GRID_SIZE];
+
+pub fn is_dry(map: &DryMap, x: usize, y: usize) -> bool {
+    map[y] & (1 << x) != 0
+}
+
+/// Tiles that need water to work
+pub fn needs_water(building_type: BuildingType) -> bool {
+    matches!(
+        building_type,
+        BuildingType::Residential | BuildingType::Commercial
+    )
+}
+
+/// Residential and commercial tiles water doesn't reach
+pub fn compute_dry(tiles: &Grid) -> DryMap {
+    let watered = power::propagate(
+        tiles,
+        BuildingType::flows,
+        |flows| flows.water_out,
+        |flows| flows.water_in,
+    );
+    let mut dry = [0u16; GRID_SIZE];
+    for (y, row) in tiles.iter().enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            if needs_water(buildings::tile_type(tile)) && watered[y] & (1 << x) == 0 {
+                dry[y] |= 1 << x;
+            }
+        }
+    }
+    dry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network;
+
+    const PUMP: u8 = BuildingType::WaterPump as u8;
+    const HOME: u8 = BuildingType::Residential as u8;
+    const SHOP: u8 = BuildingType::Commercial as u8;
+
+    #[test]
+    fn homes_and_shops_cut_off_from_water_are_dry() {
+        let mut tiles = [[0u8; GRID_SIZE]; GRID_SIZE];
+        tiles[0][0] = PUMP;
+        tiles[0][1] = network::ROAD_TILE;
+        tiles[0][2] = HOME;
+        tiles[1][2] = SHOP;
+        tiles[6][6] = network::ROAD_TILE;
+        tiles[6][7] = HOME;
+
+        let dry = compute_dry(&tiles);
+        assert!(!is_dry(&dry, 2, 0));
+        assert!(!is_dry(&dry, 2, 1));
+        assert!(is_dry(&dry, 7, 6));
+        // Roads need no water, connected or not
+        assert!(!is_dry(&dry, 6, 6));
+
+        tiles[0][1] = 0;
+        let dry = compute_dry(&tiles);
+        assert!(is_dry(&dry, 2, 0));
+        assert!(is_dry(&dry, 2, 1));
+    }
+}
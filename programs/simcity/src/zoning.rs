@@ -0,0 +1,19 @@
+//! Zoning.
+//!
+//! Instead of placing buildings one by one, the mayor can paint residential,
+//! commercial or industrial zones with `zone_tiles`. Every step, empty zoned
+//! tiles next to a road develop into buildings of their zone while its
+//! demand is positive, see `demand`, and the treasury can pay the usual
+//! placement price.
+//!
+//! Zones outlast their buildings; a bulldozed zoned tile develops again.
+
+use crate::buildings::BuildingType;
+
+/// Cap on buildings developed per step, keeping the step's compute bounded
+pub const MAX_DEVELOPMENTS_PER_STEP: u32 = 4;
+
+/// Zones a tile can be painted with; `Empty` removes the zone
+pub fn is_zoning(building_type: BuildingType) -> bool {
+    building_type == BuildingType::Empty || building_type.is_zone()
+}
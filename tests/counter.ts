@@ -49,6 +49,27 @@ describe("simcity", () => {
       anchor.Wallet.local().publicKey
     );
     console.log("Current balance is", balance / LAMPORTS_PER_SOL, " SOL", "\n");
+
+    // The global game config must exist before any city instruction runs
+    const [configPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+    if (!(await provider.connection.getAccountInfo(configPDA))) {
+      const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+        [program.programId.toBuffer()],
+        new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+      );
+      await program.methods
+        .initializeConfig()
+        // @ts-ignore
+        .accounts({
+          admin: authority.publicKey,
+          program: program.programId,
+          programData,
+        })
+        .rpc();
+    }
   });
 
   // ========================================
@@ -59,7 +80,7 @@ describe("simcity", () => {
     it("initializes a city", async () => {
       const start = Date.now();
       let tx = await program.methods
-        .initializeCity()
+        .initializeCity(false, { normal: {} }, { none: {} })
         .accounts({
           authority: authority.publicKey,
         })
@@ -89,10 +110,27 @@ describe("simcity", () => {
   });
 
   describe("place_building", () => {
-    it("places a building", async () => {
+    it("places a road", async () => {
+      await program.methods
+        .placeBuilding({ v1: { x: 5, y: 4, buildingType: { road: {} } } })
+        // @ts-ignore
+        .accounts({
+          city: cityPDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+        })
+        .rpc();
+
+      const cityAccount = await program.account.city.fetch(cityPDA);
+      const tiles = cityAccount.tiles as number[][];
+      expect(tiles[4][5]).to.equal(1);
+      expect(cityAccount.money.toNumber()).to.equal(9990);
+    });
+
+    it("places a building next to the road", async () => {
       const start = Date.now();
       const txHash = await program.methods
-        .placeBuilding(5, 5, 2) // x=5, y=5, type=2 (Residential)
+        .placeBuilding({ v1: { x: 5, y: 5, buildingType: { residential: {} } } })
         // @ts-ignore
         .accounts({
           city: cityPDA,
@@ -106,7 +144,7 @@ describe("simcity", () => {
       const cityAccount = await program.account.city.fetch(cityPDA);
       const tiles = cityAccount.tiles as number[][];
       expect(tiles[5][5]).to.equal(2);
-      expect(cityAccount.money.toNumber()).to.equal(9900); // 10000 - 100
+      expect(cityAccount.money.toNumber()).to.equal(9890); // 9990 - 100
     });
   });
 
@@ -114,7 +152,7 @@ describe("simcity", () => {
     it("bulldozes a tile", async () => {
       const start = Date.now();
       const txHash = await program.methods
-        .bulldoze(5, 5)
+        .bulldoze({ v1: { x: 5, y: 5 } })
         // @ts-ignore
         .accounts({
           city: cityPDA,
@@ -170,7 +208,7 @@ describe("simcity", () => {
       const start = Date.now();
       // Build transaction using base program
       let tx = await program.methods
-        .placeBuilding(3, 3, 3) // x=3, y=3, type=3(Commercial)
+        .placeBuilding({ v1: { x: 5, y: 3, buildingType: { commercial: {} } } })
         // @ts-ignore
         .accounts({
           city: cityPDA,
@@ -227,16 +265,20 @@ describe("simcity", () => {
       console.log(`${duration}ms (ER) Undelegate txHash: ${txHash}`);
 
       // Verify the city was updated
-      // We placed a building at 3,3 type 3. and we bulldozed 5,5 previously.
+      // We placed a building at 5,3 type 3. and we bulldozed 5,5 previously.
       // And initialized with 10000.
-      // -100 for place 5,5 (then bulldozed).
-      // -100 for place 3,3 on ER.
-      // Total money should be 9800.
+      // -10 for the road at 5,4.
+      // -100 for place 5,5 (residential), +50 refunded by the bulldoze.
+      // -150 for place 5,3 on ER (commercial).
+      // Total money should be 9790, less upkeep for any simulation steps
+      // that came due while the tests ran: each of the four moves catches
+      // up at most 4 steps, costing at most 2 (road and house) apiece.
       const cityAccount = await program.account.city.fetch(cityPDA);
       console.log(`City money after undelegation: ${cityAccount.money}`);
-      expect(cityAccount.money.toNumber()).to.equal(9800);
+      expect(cityAccount.money.toNumber()).to.be.at.most(9790);
+      expect(cityAccount.money.toNumber()).to.be.at.least(9790 - 4 * 4 * 2);
       const tiles = cityAccount.tiles as number[][];
-      expect(tiles[3][3]).to.equal(3);
+      expect(tiles[3][5]).to.equal(3);
     });
   });
 });